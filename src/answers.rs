@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+
+// Expected answers keyed by (day, part), read from a small subset of TOML:
+//
+// [day1]
+// part1 = 436404
+// part2 = "some text"
+#[derive(Debug, Default, PartialEq)]
+pub struct Answers {
+    answers: HashMap<(u32, u32), String>,
+}
+
+impl Answers {
+    pub fn from_string(input: &str) -> Result<Self, String> {
+        let mut answers = HashMap::new();
+        let mut day = None;
+
+        for (i, line) in input.lines().enumerate() {
+            let error = |what: &str| format!("line {}: {}", i + 1, what);
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(section) = line.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+                let n = section
+                    .trim()
+                    .strip_prefix("day")
+                    .and_then(|n| n.parse().ok())
+                    .ok_or_else(|| error("expected a section like [day1]"))?;
+                day = Some(n);
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| error("expected partN = answer"))?;
+            let part = key
+                .trim()
+                .strip_prefix("part")
+                .and_then(|n| n.parse().ok())
+                .ok_or_else(|| error("expected partN = answer"))?;
+            let day = day.ok_or_else(|| error("answer outside of a [dayN] section"))?;
+
+            let value = value.trim();
+            let value = match value.strip_prefix('"') {
+                Some(quoted) => quoted
+                    .strip_suffix('"')
+                    .ok_or_else(|| error("unterminated string"))?,
+                None => value,
+            };
+            if answers.insert((day, part), value.to_string()).is_some() {
+                return Err(error("duplicate answer"));
+            }
+        }
+
+        Ok(Self { answers })
+    }
+
+    pub fn get(&self, day: u32, part: u32) -> Option<&str> {
+        self.answers.get(&(day, part)).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    static EXAMPLE_INPUT: &str = "\
+# Comment
+[day1]
+part1 = 436404
+part2 = 274879808
+
+[day21]
+part2 = \"cdqvp,dglm\"
+";
+
+    #[test]
+    fn test_answers() {
+        let answers = Answers::from_string(EXAMPLE_INPUT).unwrap();
+        assert_eq!(answers.get(1, 1), Some("436404"));
+        assert_eq!(answers.get(1, 2), Some("274879808"));
+        assert_eq!(answers.get(21, 1), None);
+        assert_eq!(answers.get(21, 2), Some("cdqvp,dglm"));
+    }
+
+    #[test]
+    fn test_answers_invalid() {
+        assert_eq!(
+            Answers::from_string("part1 = 5"),
+            Err("line 1: answer outside of a [dayN] section".to_string())
+        );
+        assert_eq!(
+            Answers::from_string("[day1]\npart1 = 5\npart1 = 6"),
+            Err("line 3: duplicate answer".to_string())
+        );
+        assert_eq!(
+            Answers::from_string("[day1]\npart1 = \"abc"),
+            Err("line 2: unterminated string".to_string())
+        );
+        assert_eq!(
+            Answers::from_string("[first]"),
+            Err("line 1: expected a section like [day1]".to_string())
+        );
+    }
+}
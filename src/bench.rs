@@ -0,0 +1,263 @@
+use std::{fmt::Write, time::Duration};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Json,
+    Csv,
+}
+
+impl Format {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "json" => Some(Self::Json),
+            "csv" => Some(Self::Csv),
+            _ => None,
+        }
+    }
+}
+
+const CSV_HEADER: &str = "day,part,runs,min_ns,mean_ns,median_ns,stddev_ns";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Measurement {
+    pub day: u32,
+    pub part: u32,
+    pub runs: usize,
+    pub min: Duration,
+    pub mean: Duration,
+    pub median: Duration,
+    pub stddev: Duration,
+}
+
+impl Measurement {
+    pub fn from_samples(day: u32, part: u32, samples: &[Duration]) -> Self {
+        assert!(samples.is_empty() == false);
+
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+        let len = sorted.len();
+        let median = if len.is_multiple_of(2) {
+            (sorted[len / 2 - 1] + sorted[len / 2]) / 2
+        } else {
+            sorted[len / 2]
+        };
+
+        let nanos: Vec<f64> = sorted.iter().map(|d| d.as_nanos() as f64).collect();
+        let mean = nanos.iter().sum::<f64>() / len as f64;
+        let variance = nanos.iter().map(|n| (n - mean).powi(2)).sum::<f64>() / len as f64;
+
+        Self {
+            day,
+            part,
+            runs: len,
+            min: sorted[0],
+            mean: Duration::from_nanos(mean.round() as u64),
+            median,
+            stddev: Duration::from_nanos(variance.sqrt().round() as u64),
+        }
+    }
+
+    fn from_fields(fields: &[(&str, u64)]) -> Option<Self> {
+        let get = |name: &str| fields.iter().find(|(k, _)| *k == name).map(|(_, v)| *v);
+        Some(Self {
+            day: get("day")? as u32,
+            part: get("part")? as u32,
+            runs: get("runs")? as usize,
+            min: Duration::from_nanos(get("min_ns")?),
+            mean: Duration::from_nanos(get("mean_ns")?),
+            median: Duration::from_nanos(get("median_ns")?),
+            stddev: Duration::from_nanos(get("stddev_ns")?),
+        })
+    }
+
+    fn values(&self) -> [u64; 7] {
+        [
+            self.day as u64,
+            self.part as u64,
+            self.runs as u64,
+            self.min.as_nanos() as u64,
+            self.mean.as_nanos() as u64,
+            self.median.as_nanos() as u64,
+            self.stddev.as_nanos() as u64,
+        ]
+    }
+}
+
+pub fn to_string(measurements: &[Measurement], format: Format) -> String {
+    let columns: Vec<&str> = CSV_HEADER.split(',').collect();
+    let mut output = String::new();
+    match format {
+        Format::Csv => {
+            writeln!(output, "{}", CSV_HEADER).unwrap();
+            for m in measurements {
+                let values: Vec<String> = m.values().iter().map(|v| v.to_string()).collect();
+                writeln!(output, "{}", values.join(",")).unwrap();
+            }
+        }
+        Format::Json => {
+            writeln!(output, "[").unwrap();
+            for (i, m) in measurements.iter().enumerate() {
+                let fields: Vec<String> = columns
+                    .iter()
+                    .zip(m.values().iter())
+                    .map(|(k, v)| format!("\"{}\": {}", k, v))
+                    .collect();
+                let separator = if i + 1 < measurements.len() { "," } else { "" };
+                writeln!(output, "  {{{}}}{}", fields.join(", "), separator).unwrap();
+            }
+            writeln!(output, "]").unwrap();
+        }
+    }
+    output
+}
+
+// Reads back a file written by to_string, in either format. The JSON reader only understands the flat
+// objects with numeric values that we write ourselves.
+pub fn parse_baseline(input: &str) -> Result<Vec<Measurement>, String> {
+    let input = input.trim();
+    if input.starts_with('[') {
+        let body = input
+            .strip_prefix('[')
+            .and_then(|s| s.strip_suffix(']'))
+            .ok_or("unterminated JSON array")?;
+        body.split('}')
+            .map(|obj| obj.trim().trim_start_matches(',').trim())
+            .filter(|obj| obj.is_empty() == false)
+            .map(|obj| {
+                let obj = obj
+                    .strip_prefix('{')
+                    .ok_or(format!("expected an object, found '{}'", obj))?;
+                let fields = obj
+                    .split(',')
+                    .map(|field| {
+                        let (key, value) = field.split_once(':')?;
+                        let key = key.trim().trim_matches('"');
+                        Some((key, value.trim().parse().ok()?))
+                    })
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or(format!("invalid object '{{{}}}'", obj))?;
+                Measurement::from_fields(&fields).ok_or(format!("missing field in '{{{}}}'", obj))
+            })
+            .collect()
+    } else {
+        let mut lines = input.lines();
+        let header: Vec<&str> = lines
+            .next()
+            .unwrap_or("")
+            .split(',')
+            .map(str::trim)
+            .collect();
+        lines
+            .map(|line| {
+                let fields = header
+                    .iter()
+                    .zip(line.split(','))
+                    .map(|(k, v)| Some((*k, v.trim().parse().ok()?)))
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or(format!("invalid CSV row '{}'", line))?;
+                Measurement::from_fields(&fields).ok_or(format!("missing column in '{}'", line))
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Regression {
+    pub day: u32,
+    pub part: u32,
+    pub baseline: Duration,
+    pub current: Duration,
+}
+
+impl Regression {
+    pub fn slowdown(&self) -> f64 {
+        self.current.as_secs_f64() / self.baseline.as_secs_f64() - 1.0
+    }
+}
+
+// Medians are compared since they're the least affected by the occasional slow run
+pub fn find_regressions(
+    current: &[Measurement],
+    baseline: &[Measurement],
+    threshold: f64,
+) -> Vec<Regression> {
+    current
+        .iter()
+        .filter_map(|m| {
+            let base = baseline
+                .iter()
+                .find(|b| b.day == m.day && b.part == m.part)?;
+            let limit = base.median.as_secs_f64() * (1.0 + threshold);
+            if m.median.as_secs_f64() > limit {
+                Some(Regression {
+                    day: m.day,
+                    part: m.part,
+                    baseline: base.median,
+                    current: m.median,
+                })
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn micros(values: &[u64]) -> Vec<Duration> {
+        values.iter().map(|&v| Duration::from_micros(v)).collect()
+    }
+
+    #[test]
+    fn test_from_samples() {
+        let m = Measurement::from_samples(15, 2, &micros(&[4, 2, 8, 6]));
+        assert_eq!(m.runs, 4);
+        assert_eq!(m.min, Duration::from_micros(2));
+        assert_eq!(m.mean, Duration::from_micros(5));
+        assert_eq!(m.median, Duration::from_micros(5));
+        assert_eq!(m.stddev, Duration::from_nanos(2236));
+
+        let m = Measurement::from_samples(15, 2, &micros(&[3, 1, 2]));
+        assert_eq!(m.median, Duration::from_micros(2));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let measurements = vec![
+            Measurement::from_samples(22, 1, &micros(&[10, 12, 11])),
+            Measurement::from_samples(22, 2, &micros(&[1000, 1200, 1100])),
+        ];
+        for &format in &[Format::Csv, Format::Json] {
+            let output = to_string(&measurements, format);
+            assert_eq!(parse_baseline(&output), Ok(measurements.clone()));
+        }
+        assert_eq!(parse_baseline("[]"), Ok(vec![]));
+        assert!(parse_baseline("day,part\n1,x").is_err());
+    }
+
+    #[test]
+    fn test_find_regressions() {
+        let baseline = vec![
+            Measurement::from_samples(15, 2, &micros(&[100])),
+            Measurement::from_samples(23, 2, &micros(&[100])),
+        ];
+        let current = vec![
+            Measurement::from_samples(15, 2, &micros(&[109])),
+            Measurement::from_samples(22, 2, &micros(&[500])),
+            Measurement::from_samples(23, 2, &micros(&[150])),
+        ];
+        let regressions = find_regressions(&current, &baseline, 0.1);
+        assert_eq!(
+            regressions,
+            vec![Regression {
+                day: 23,
+                part: 2,
+                baseline: Duration::from_micros(100),
+                current: Duration::from_micros(150),
+            }]
+        );
+        assert!((regressions[0].slowdown() - 0.5).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,563 @@
+use crate::common::{Cycle, CycleDetector, HexPoint, Point, PointN};
+use std::collections::{hash_map::DefaultHasher, HashMap};
+use std::hash::{BuildHasherDefault, Hash, Hasher};
+
+// How cells are connected to each other
+pub trait Topology {
+    type Cell: Copy + Eq + Hash;
+
+    fn for_each_neighbor<F: FnMut(Self::Cell)>(&self, cell: &Self::Cell, f: F);
+}
+
+// Lets a topology that is expensive to build be borrowed by the automaton rather than moved into it
+impl<T: Topology> Topology for &T {
+    type Cell = T::Cell;
+
+    fn for_each_neighbor<F: FnMut(Self::Cell)>(&self, cell: &Self::Cell, f: F) {
+        (*self).for_each_neighbor(cell, f);
+    }
+}
+
+// How a cell changes each generation, given how many of its neighbors are live
+pub trait Rule {
+    // Cells not stored by the automaton are in the default state. A default cell with no live neighbors
+    // must stay that way, otherwise the automaton would have to visit every cell in existence.
+    type State: Copy + Default + PartialEq;
+
+    fn is_live(&self, state: Self::State) -> bool;
+    fn next(&self, state: Self::State, live_neighbors: usize) -> Self::State;
+
+    // Whether a cell hides the cells behind it, for topologies like GridTopology where neighbors are seen
+    // along lines. By default only the nearest cell on each line counts.
+    fn blocks_sight(&self, _state: Self::State) -> bool {
+        true
+    }
+}
+
+// The usual two state rule, where a cell is born or survives when its number of live neighbors is in the
+// matching list
+#[derive(Clone, Debug, PartialEq)]
+pub struct LifeRule {
+    pub birth: Vec<usize>,
+    pub survive: Vec<usize>,
+}
+
+impl LifeRule {
+    pub fn new(birth: &[usize], survive: &[usize]) -> Self {
+        Self {
+            birth: birth.to_vec(),
+            survive: survive.to_vec(),
+        }
+    }
+}
+
+impl Rule for LifeRule {
+    type State = bool;
+
+    fn is_live(&self, state: bool) -> bool {
+        state
+    }
+
+    fn next(&self, state: bool, live_neighbors: usize) -> bool {
+        if state == true {
+            self.survive.contains(&live_neighbors)
+        } else {
+            self.birth.contains(&live_neighbors)
+        }
+    }
+}
+
+// A 2D grid of squares, where neighbors are either orthogonal only or also diagonal
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Square {
+    pub diagonals: bool,
+}
+
+impl Square {
+    pub fn orthogonal() -> Self {
+        Self { diagonals: false }
+    }
+
+    pub fn adjacent() -> Self {
+        Self { diagonals: true }
+    }
+}
+
+impl Topology for Square {
+    type Cell = Point;
+
+    fn for_each_neighbor<F: FnMut(Point)>(&self, cell: &Point, f: F) {
+        if self.diagonals == true {
+            cell.adjacents().for_each(f);
+        } else {
+            cell.orthogonals().for_each(f);
+        }
+    }
+}
+
+// An N dimensional grid of cubes, where every cube touching this one (even at a corner) is a neighbor
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cube<const N: usize> {
+    offsets: Vec<PointN<N>>,
+}
+
+impl<const N: usize> Cube<N> {
+    pub fn new() -> Self {
+        Self {
+            offsets: PointN::neighbor_offsets().collect(),
+        }
+    }
+}
+
+impl<const N: usize> Default for Cube<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Topology for Cube<N> {
+    type Cell = PointN<N>;
+
+    fn for_each_neighbor<F: FnMut(PointN<N>)>(&self, cell: &PointN<N>, f: F) {
+        self.offsets.iter().map(|offset| cell + offset).for_each(f);
+    }
+}
+
+// Any neighborhood on a 2D grid, given the offset to each neighbor
+#[derive(Clone, Debug, PartialEq)]
+pub struct Offsets2d {
+    pub offsets: Vec<Point>,
+}
+
+impl Topology for Offsets2d {
+    type Cell = Point;
+
+    fn for_each_neighbor<F: FnMut(Point)>(&self, cell: &Point, f: F) {
+        self.offsets.iter().map(|offset| cell + offset).for_each(f);
+    }
+}
+
+// A grid of hexagons, each with six neighbors
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hex;
+
+impl Topology for Hex {
+    type Cell = HexPoint;
+
+    fn for_each_neighbor<F: FnMut(HexPoint)>(&self, cell: &HexPoint, f: F) {
+        cell.adjacents().for_each(f);
+    }
+}
+
+// The automaton hashes every neighbor of every live cell each step, which is where nearly all of its time
+// goes. Cells are small and not attacker controlled, so a simple multiply and rotate (as used by rustc's
+// FxHasher) is plenty and much faster than the default SipHash.
+#[derive(Default)]
+struct CellHasher {
+    hash: u64,
+}
+
+impl Hasher for CellHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.write_u64(b as u64);
+        }
+    }
+
+    fn write_u32(&mut self, n: u32) {
+        self.write_u64(n as u64);
+    }
+
+    fn write_i32(&mut self, n: i32) {
+        self.write_u64(n as u32 as u64);
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ n).wrapping_mul(0x51_7c_c1_b7_27_22_0a_95);
+    }
+
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+}
+
+type CellMap<K, V> = HashMap<K, V, BuildHasherDefault<CellHasher>>;
+
+// A simulation of a two state rule, so that different ways of storing the cells can be chosen at runtime
+pub trait Simulation<C> {
+    fn is_live(&self, cell: &C) -> bool;
+    fn set_live(&mut self, cell: C, live: bool);
+    fn advance(&mut self, generations: usize);
+    fn generation(&self) -> usize;
+    fn count_live(&self) -> usize;
+    fn live_cells(&self) -> Vec<C>;
+}
+
+pub struct Automaton<T: Topology, R: Rule> {
+    topology: T,
+    rule: R,
+    cells: CellMap<T::Cell, R::State>,
+    counts: CellMap<T::Cell, usize>, // Kept between steps to save reallocating it
+    generation: usize,
+}
+
+impl<T: Topology, R: Rule> Automaton<T, R> {
+    pub fn new(topology: T, rule: R) -> Self {
+        let default = R::State::default();
+        assert!(
+            rule.next(default, 0) == default,
+            "Rule must leave default cells with no live neighbors unchanged"
+        );
+        Self {
+            topology,
+            rule,
+            cells: CellMap::default(),
+            counts: CellMap::default(),
+            generation: 0,
+        }
+    }
+
+    pub fn with_cells<I>(mut self, cells: I) -> Self
+    where
+        I: std::iter::IntoIterator<Item = (T::Cell, R::State)>,
+    {
+        for (cell, state) in cells {
+            self.set(cell, state);
+        }
+        self
+    }
+
+    pub fn topology(&self) -> &T {
+        &self.topology
+    }
+
+    pub fn rule(&self) -> &R {
+        &self.rule
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn get(&self, cell: &T::Cell) -> R::State {
+        self.cells.get(cell).copied().unwrap_or_default()
+    }
+
+    pub fn set(&mut self, cell: T::Cell, state: R::State) {
+        if state == R::State::default() {
+            self.cells.remove(&cell);
+        } else {
+            self.cells.insert(cell, state);
+        }
+    }
+
+    // Every cell which isn't in the default state
+    pub fn cells(&self) -> impl Iterator<Item = (&T::Cell, &R::State)> {
+        self.cells.iter()
+    }
+
+    pub fn live_cells(&self) -> impl Iterator<Item = &T::Cell> {
+        self.cells
+            .iter()
+            .filter(move |(_cell, &state)| self.rule.is_live(state))
+            .map(|(cell, _state)| cell)
+    }
+
+    pub fn count_live(&self) -> usize {
+        self.live_cells().count()
+    }
+
+    // Advances one generation, returning whether any cell changed
+    pub fn step(&mut self) -> bool {
+        self.counts.clear();
+        for (cell, &state) in self.cells.iter() {
+            if self.rule.is_live(state) == true {
+                let counts = &mut self.counts;
+                self.topology.for_each_neighbor(cell, |neighbor| {
+                    *counts.entry(neighbor).or_insert(0) += 1;
+                });
+            }
+        }
+
+        // Only stored cells and those next to a live cell can change
+        let default = R::State::default();
+        let mut changes: Vec<(T::Cell, R::State)> = Vec::new();
+        for (cell, &state) in self.cells.iter() {
+            let live_neighbors = self.counts.get(cell).copied().unwrap_or(0);
+            let new_state = self.rule.next(state, live_neighbors);
+            if new_state != state {
+                changes.push((*cell, new_state));
+            }
+        }
+        for (cell, &live_neighbors) in self.counts.iter() {
+            if self.cells.contains_key(cell) == false {
+                let new_state = self.rule.next(default, live_neighbors);
+                if new_state != default {
+                    changes.push((*cell, new_state));
+                }
+            }
+        }
+
+        for &(cell, state) in changes.iter() {
+            self.set(cell, state);
+        }
+        self.generation += 1;
+        changes.is_empty() == false
+    }
+
+    pub fn simulate(&mut self, steps: usize) {
+        for _ in 0..steps {
+            self.step();
+        }
+    }
+
+    // Steps until nothing changes, returning the number of steps that made a change
+    pub fn run_until_stable(&mut self) -> usize {
+        let mut steps = 0;
+        while self.step() == true {
+            steps += 1;
+        }
+        steps
+    }
+}
+
+impl<T: Topology> Simulation<T::Cell> for Automaton<T, LifeRule> {
+    fn is_live(&self, cell: &T::Cell) -> bool {
+        self.get(cell)
+    }
+
+    fn set_live(&mut self, cell: T::Cell, live: bool) {
+        self.set(cell, live);
+    }
+
+    fn advance(&mut self, generations: usize) {
+        self.simulate(generations);
+    }
+
+    fn generation(&self) -> usize {
+        self.generation
+    }
+
+    fn count_live(&self) -> usize {
+        Automaton::count_live(self)
+    }
+
+    fn live_cells(&self) -> Vec<T::Cell> {
+        Automaton::live_cells(self).copied().collect()
+    }
+}
+
+impl<T: Topology, R: Rule> Automaton<T, R>
+where
+    T::Cell: Hash,
+    R::State: Hash,
+{
+    // A hash of every cell's state which doesn't depend on the order they're stored in
+    pub fn state_hash(&self) -> u64 {
+        self.cells
+            .iter()
+            .map(|entry| {
+                let mut hasher = DefaultHasher::new();
+                entry.hash(&mut hasher);
+                hasher.finish()
+            })
+            .fold(0, u64::wrapping_add)
+    }
+
+    // Steps until a state repeats, giving up after max_steps. The cycle's generations are the same as
+    // the automaton's and it is left at the first generation which repeated.
+    pub fn find_cycle(&mut self, max_steps: usize) -> Option<Cycle> {
+        let first = self.generation;
+        let mut detector = CycleDetector::new();
+        self.run_detector(&mut detector, max_steps)
+            .map(|cycle| Cycle {
+                start: first + cycle.start,
+                length: cycle.length,
+            })
+    }
+
+    // Moves to the given generation, jumping ahead once the states start to repeat. Returns whether the
+    // generation was reached, which it won't be if there's no cycle within max_steps.
+    pub fn simulate_to(&mut self, generation: usize, max_steps: usize) -> bool {
+        assert!(generation >= self.generation, "Can't simulate backwards");
+        let first = self.generation;
+        let mut detector = CycleDetector::new();
+        let max_steps = max_steps.min(generation - first);
+        if self.run_detector(&mut detector, max_steps).is_none() {
+            return self.generation == generation;
+        }
+
+        self.cells = detector.state_at(generation - first).unwrap().clone();
+        self.generation = generation;
+        true
+    }
+
+    fn run_detector(
+        &mut self,
+        detector: &mut CycleDetector<CellMap<T::Cell, R::State>>,
+        max_steps: usize,
+    ) -> Option<Cycle> {
+        for _ in 0..max_steps {
+            if let Some(cycle) = detector.push(self.state_hash(), self.cells.clone()) {
+                return Some(cycle);
+            }
+            self.step();
+        }
+        detector.push(self.state_hash(), self.cells.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn live_points(automaton: &Automaton<Square, LifeRule>) -> HashSet<Point> {
+        automaton.live_cells().copied().collect()
+    }
+
+    #[test]
+    fn test_blinker() {
+        let conway = LifeRule::new(&[3], &[2, 3]);
+        let horizontal: HashSet<Point> = vec![(0, 1), (1, 1), (2, 1)]
+            .into_iter()
+            .map(Point::from)
+            .collect();
+        let vertical: HashSet<Point> = vec![(1, 0), (1, 1), (1, 2)]
+            .into_iter()
+            .map(Point::from)
+            .collect();
+
+        let mut automaton = Automaton::new(Square::adjacent(), conway)
+            .with_cells(horizontal.iter().map(|&p| (p, true)));
+        assert_eq!(automaton.step(), true);
+        assert_eq!(live_points(&automaton), vertical);
+        assert_eq!(automaton.step(), true);
+        assert_eq!(live_points(&automaton), horizontal);
+        assert_eq!(automaton.generation(), 2);
+    }
+
+    #[test]
+    fn test_find_cycle() {
+        let conway = LifeRule::new(&[3], &[2, 3]);
+        let horizontal: Vec<Point> = vec![(0, 1), (1, 1), (2, 1)]
+            .into_iter()
+            .map(Point::from)
+            .collect();
+
+        // A blinker repeats every other generation from the start
+        let mut automaton = Automaton::new(Square::adjacent(), conway.clone())
+            .with_cells(horizontal.iter().map(|&p| (p, true)));
+        automaton.simulate(3);
+        assert_eq!(
+            automaton.find_cycle(10),
+            Some(Cycle {
+                start: 3,
+                length: 2
+            })
+        );
+        assert_eq!(automaton.generation(), 5);
+
+        // So after an even number of generations it's back where it started
+        let mut automaton = Automaton::new(Square::adjacent(), conway.clone())
+            .with_cells(horizontal.iter().map(|&p| (p, true)));
+        assert_eq!(automaton.simulate_to(1_000_000_000_000, 10), true);
+        assert_eq!(automaton.generation(), 1_000_000_000_000);
+        assert_eq!(
+            live_points(&automaton),
+            horizontal.iter().copied().collect::<HashSet<Point>>()
+        );
+
+        // A glider never repeats exactly since it keeps moving
+        let glider = vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+        let mut automaton = Automaton::new(Square::adjacent(), conway)
+            .with_cells(glider.into_iter().map(|p| (Point::from(p), true)));
+        assert_eq!(automaton.find_cycle(20), None);
+        assert_eq!(automaton.simulate_to(1_000, 20), false);
+        assert_eq!(automaton.generation(), 40);
+    }
+
+    #[test]
+    fn test_block_is_stable() {
+        let conway = LifeRule::new(&[3], &[2, 3]);
+        let block = vec![(0, 0), (1, 0), (0, 1), (1, 1)];
+        let mut automaton = Automaton::new(Square::adjacent(), conway)
+            .with_cells(block.into_iter().map(|p| (Point::from(p), true)));
+        assert_eq!(automaton.run_until_stable(), 0);
+        assert_eq!(automaton.count_live(), 4);
+    }
+
+    #[test]
+    fn test_orthogonal() {
+        // Each live cell spreads to its four orthogonal neighbors and then dies
+        let rule = LifeRule::new(&[1], &[]);
+        let mut automaton =
+            Automaton::new(Square::orthogonal(), rule).with_cells(vec![(Point::origin(), true)]);
+        automaton.step();
+        assert_eq!(automaton.count_live(), 4);
+        assert_eq!(automaton.get(&Point::origin()), false);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_birth_from_nothing() {
+        // Every empty cell in the infinite plane would be born, which a sparse automaton can't represent
+        Automaton::new(Square::adjacent(), LifeRule::new(&[0], &[]));
+    }
+
+    #[test]
+    fn test_cube_and_hex() {
+        let mut automaton = Automaton::new(Cube::<3>::new(), LifeRule::new(&[1], &[]))
+            .with_cells(vec![(PointN::origin(), true)]);
+        automaton.step();
+        assert_eq!(automaton.count_live(), 26);
+
+        let mut automaton = Automaton::new(Hex, LifeRule::new(&[1], &[]))
+            .with_cells(vec![(HexPoint::origin(), true)]);
+        automaton.step();
+        assert_eq!(automaton.count_live(), 6);
+    }
+
+    // A three state rule, where dying cells take a generation to clear and don't count as live
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    enum Brain {
+        #[default]
+        Off,
+        On,
+        Dying,
+    }
+
+    struct BriansBrain;
+
+    impl Rule for BriansBrain {
+        type State = Brain;
+
+        fn is_live(&self, state: Brain) -> bool {
+            state == Brain::On
+        }
+
+        fn next(&self, state: Brain, live_neighbors: usize) -> Brain {
+            match state {
+                Brain::Off if live_neighbors == 2 => Brain::On,
+                Brain::Off => Brain::Off,
+                Brain::On => Brain::Dying,
+                Brain::Dying => Brain::Off,
+            }
+        }
+    }
+
+    #[test]
+    fn test_per_cell_state() {
+        let cells = vec![
+            (Point { x: 0, y: 0 }, Brain::On),
+            (Point { x: 1, y: 0 }, Brain::On),
+        ];
+        let mut automaton = Automaton::new(Square::adjacent(), BriansBrain).with_cells(cells);
+        automaton.step();
+        assert_eq!(automaton.get(&Point { x: 0, y: 0 }), Brain::Dying);
+        assert_eq!(automaton.get(&Point { x: 0, y: 1 }), Brain::On);
+        assert_eq!(automaton.get(&Point { x: 1, y: -1 }), Brain::On);
+        assert_eq!(automaton.count_live(), 4);
+        assert_eq!(automaton.cells().count(), 6);
+    }
+}
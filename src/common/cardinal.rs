@@ -7,18 +7,18 @@ pub enum Cardinal {
 }
 
 impl Cardinal {
-    pub fn from_arrow(c: char) -> Self {
-        match c {
+    pub fn from_arrow(c: char) -> Option<Self> {
+        Some(match c {
             '^' => Self::North,
             'v' => Self::South,
             '>' => Self::East,
             '<' => Self::West,
-            _ => panic!("Cardinal: invalid input '{}'", c),
-        }
+            _ => return None,
+        })
     }
 
-    pub fn to_arrow(&self) -> char {
-        match *self {
+    pub fn to_arrow(self) -> char {
+        match self {
             Self::North => '^',
             Self::South => 'v',
             Self::East => '>',
@@ -26,18 +26,18 @@ impl Cardinal {
         }
     }
 
-    pub fn from_char(c: char) -> Self {
-        match c {
+    pub fn from_char(c: char) -> Option<Self> {
+        Some(match c {
             'N' | 'n' => Self::North,
             'S' | 's' => Self::South,
             'E' | 'e' => Self::East,
             'W' | 'w' => Self::West,
-            _ => panic!("Invalid cardinal character: {}", c),
-        }
+            _ => return None,
+        })
     }
 
-    pub fn to_char(&self) -> char {
-        match *self {
+    pub fn to_char(self) -> char {
+        match self {
             Self::North => 'N',
             Self::South => 'S',
             Self::East => 'E',
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+
+// A sequence of states which repeats forever once it reaches the given generation. A length of 1 means it
+// has settled on a fixed point.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Cycle {
+    pub start: usize,
+    pub length: usize,
+}
+
+impl Cycle {
+    pub fn is_fixed_point(&self) -> bool {
+        self.length == 1
+    }
+
+    // The earliest generation which has the same state as the given one
+    pub fn equivalent(&self, generation: usize) -> usize {
+        if generation < self.start {
+            generation
+        } else {
+            self.start + (generation - self.start) % self.length
+        }
+    }
+}
+
+// Remembers every state it's given, one per generation starting at 0, until one repeats. States are looked
+// up by a hash supplied by the caller so they don't need to implement Hash themselves, but they are still
+// compared in full so a collision can't cause a false match.
+pub struct CycleDetector<S: PartialEq> {
+    history: Vec<S>,
+    seen: HashMap<u64, Vec<usize>>,
+    cycle: Option<Cycle>,
+}
+
+impl<S: PartialEq> CycleDetector<S> {
+    pub fn new() -> Self {
+        Self {
+            history: Vec::new(),
+            seen: HashMap::new(),
+            cycle: None,
+        }
+    }
+
+    // Records the state of the next generation, returning the cycle once a state repeats
+    pub fn push(&mut self, hash: u64, state: S) -> Option<Cycle> {
+        if self.cycle.is_some() {
+            return self.cycle;
+        }
+
+        let generation = self.history.len();
+        let history = &mut self.history;
+        let matches = self.seen.entry(hash).or_default();
+        if let Some(&start) = matches.iter().find(|&&g| history[g] == state) {
+            self.cycle = Some(Cycle {
+                start,
+                length: generation - start,
+            });
+        } else {
+            matches.push(generation);
+            history.push(state);
+        }
+        self.cycle
+    }
+
+    pub fn cycle(&self) -> Option<Cycle> {
+        self.cycle
+    }
+
+    pub fn generations(&self) -> usize {
+        self.history.len()
+    }
+
+    // The state at any generation, which can be far beyond those recorded once a cycle is found
+    pub fn state_at(&self, generation: usize) -> Option<&S> {
+        match self.cycle {
+            Some(cycle) => self.history.get(cycle.equivalent(generation)),
+            None => self.history.get(generation),
+        }
+    }
+}
+
+impl<S: PartialEq> Default for CycleDetector<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cycle_detector() {
+        // 0, 1, 2, 3, 4, 2, 3, 4, ...
+        let sequence = [0, 1, 2, 3, 4, 2, 3, 4];
+        let mut detector = CycleDetector::new();
+        let mut found = None;
+        for (i, &n) in sequence.iter().enumerate() {
+            found = detector.push(n as u64, n);
+            if found.is_some() {
+                assert_eq!(i, 5);
+                break;
+            }
+        }
+
+        let cycle = found.unwrap();
+        assert_eq!(
+            cycle,
+            Cycle {
+                start: 2,
+                length: 3
+            }
+        );
+        assert_eq!(cycle.is_fixed_point(), false);
+        assert_eq!(detector.generations(), 5);
+        assert_eq!(detector.state_at(1), Some(&1));
+        assert_eq!(detector.state_at(7), Some(&4));
+        assert_eq!(detector.state_at(1_000_000_000_000), Some(&4));
+    }
+
+    #[test]
+    fn test_hash_collision() {
+        // Every state has the same hash, but only a real repeat is reported
+        let mut detector = CycleDetector::new();
+        assert_eq!(detector.push(0, 'a'), None);
+        assert_eq!(detector.push(0, 'b'), None);
+        assert_eq!(
+            detector.push(0, 'b'),
+            Some(Cycle {
+                start: 1,
+                length: 1
+            })
+        );
+        assert_eq!(detector.cycle().unwrap().is_fixed_point(), true);
+        assert_eq!(detector.state_at(100), Some(&'b'));
+    }
+}
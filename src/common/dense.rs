@@ -0,0 +1,365 @@
+use crate::common::{HexPoint, LifeRule, Point, PointN, RectN, Rule, Simulation};
+
+// A life-like rule on an N dimensional grid, storing one bit per cell in a box which grows and shrinks to
+// fit the live cells. This avoids hashing entirely, which makes it much faster than Automaton for small
+// dense patterns, but it uses memory for every cell in the box.
+pub struct Dense<const N: usize> {
+    offsets: Vec<PointN<N>>,
+    next: [Vec<bool>; 2], // Whether a dead or live cell is live next, indexed by its live neighbor count
+    bounds: RectN<N>,
+    bits: Vec<u64>,
+    generation: usize,
+}
+
+impl<const N: usize> Dense<N> {
+    pub fn new(rule: &LifeRule, offsets: Vec<PointN<N>>) -> Self {
+        assert!(
+            rule.next(false, 0) == false,
+            "Rule must leave dead cells with no live neighbors unchanged"
+        );
+
+        let counts = 0..=offsets.len();
+        let next = [
+            counts.clone().map(|n| rule.next(false, n)).collect(),
+            counts.map(|n| rule.next(true, n)).collect(),
+        ];
+        Self {
+            offsets,
+            next,
+            bounds: RectN::new(PointN::origin(), PointN::origin()),
+            bits: vec![0],
+            generation: 0,
+        }
+    }
+
+    // Every cube touching each cube is a neighbor, like Cube
+    pub fn cube(rule: &LifeRule) -> Self {
+        Self::new(rule, PointN::neighbor_offsets().collect())
+    }
+
+    pub fn with_cells<'a, I>(mut self, cells: I) -> Self
+    where
+        I: std::iter::IntoIterator<Item = &'a PointN<N>>,
+    {
+        for cell in cells {
+            self.set(*cell, true);
+        }
+        self
+    }
+
+    pub fn get(&self, p: &PointN<N>) -> bool {
+        self.bounds.contains(p) && get_bit(&self.bits, index(&self.bounds, p))
+    }
+
+    pub fn set(&mut self, p: PointN<N>, live: bool) {
+        if self.bounds.contains(&p) == false {
+            if live == false {
+                return;
+            }
+            self.resize(self.bounds.union(&RectN::new(p, p)));
+        }
+
+        let i = index(&self.bounds, &p);
+        if live == true {
+            self.bits[i / 64] |= 1 << (i % 64);
+        } else {
+            self.bits[i / 64] &= !(1 << (i % 64));
+        }
+    }
+
+    pub fn count_live(&self) -> usize {
+        self.bits
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn live_cells(&self) -> Vec<PointN<N>> {
+        let mut cells = Vec::new();
+        for (w, &word) in self.bits.iter().enumerate() {
+            let mut word = word;
+            while word != 0 {
+                let i = w * 64 + word.trailing_zeros() as usize;
+                cells.push(point(&self.bounds, i));
+                word &= word - 1;
+            }
+        }
+        cells
+    }
+
+    pub fn step(&mut self) {
+        let live = self.live_cells();
+        let new_bounds = match RectN::from_points(&live) {
+            Some(live_bounds) => live_bounds.grow(1),
+            None => {
+                self.generation += 1;
+                return;
+            }
+        };
+
+        // With the new layout every neighbor is a fixed distance away in the arrays
+        let volume = new_bounds.volume();
+        let strides = strides(&new_bounds);
+        let deltas: Vec<isize> = self
+            .offsets
+            .iter()
+            .map(|offset| {
+                (0..N)
+                    .map(|d| offset[d] as isize * strides[d] as isize)
+                    .sum()
+            })
+            .collect();
+        let mut counts = vec![0u16; volume];
+        let mut was_live = vec![0u64; volume.div_ceil(64)];
+        for p in live.iter() {
+            let i = index(&new_bounds, p);
+            was_live[i / 64] |= 1 << (i % 64);
+            for delta in deltas.iter() {
+                counts[(i as isize + delta) as usize] += 1;
+            }
+        }
+
+        let mut bits = vec![0u64; volume.div_ceil(64)];
+        for (i, &count) in counts.iter().enumerate() {
+            let live = get_bit(&was_live, i);
+            if (count > 0 || live == true) && self.next[live as usize][count as usize] == true {
+                bits[i / 64] |= 1 << (i % 64);
+            }
+        }
+
+        self.bounds = new_bounds;
+        self.bits = bits;
+        self.generation += 1;
+    }
+
+    pub fn simulate(&mut self, steps: usize) {
+        for _ in 0..steps {
+            self.step();
+        }
+    }
+
+    fn resize(&mut self, bounds: RectN<N>) {
+        let live = self.live_cells();
+        self.bounds = bounds;
+        self.bits = vec![0; bounds.volume().div_ceil(64)];
+        for p in live {
+            self.set(p, true);
+        }
+    }
+}
+
+// Cells are in the same order as RectN::points, with the first dimension changing fastest
+fn strides<const N: usize>(bounds: &RectN<N>) -> [usize; N] {
+    let mut strides = [1; N];
+    for d in 1..N {
+        strides[d] = strides[d - 1] * bounds.len(d - 1);
+    }
+    strides
+}
+
+fn index<const N: usize>(bounds: &RectN<N>, p: &PointN<N>) -> usize {
+    let strides = strides(bounds);
+    (0..N)
+        .map(|d| (p[d] - bounds.min[d]) as usize * strides[d])
+        .sum()
+}
+
+fn point<const N: usize>(bounds: &RectN<N>, mut i: usize) -> PointN<N> {
+    let mut p = bounds.min;
+    for d in 0..N {
+        let len = bounds.len(d);
+        p[d] += (i % len) as i32;
+        i /= len;
+    }
+    p
+}
+
+fn get_bit(bits: &[u64], i: usize) -> bool {
+    bits[i / 64] & (1 << (i % 64)) != 0
+}
+
+impl<const N: usize> Simulation<PointN<N>> for Dense<N> {
+    fn is_live(&self, cell: &PointN<N>) -> bool {
+        self.get(cell)
+    }
+
+    fn set_live(&mut self, cell: PointN<N>, live: bool) {
+        self.set(cell, live);
+    }
+
+    fn advance(&mut self, generations: usize) {
+        self.simulate(generations);
+    }
+
+    fn generation(&self) -> usize {
+        self.generation
+    }
+
+    fn count_live(&self) -> usize {
+        Dense::count_live(self)
+    }
+
+    fn live_cells(&self) -> Vec<PointN<N>> {
+        Dense::live_cells(self)
+    }
+}
+
+// A hex grid stored densely, using the first two cube coordinates as a skewed 2D grid
+pub struct DenseHex {
+    grid: Dense<2>,
+}
+
+impl DenseHex {
+    pub fn new(rule: &LifeRule) -> Self {
+        let offsets = HexPoint::DIRECTIONS
+            .iter()
+            .map(|h| PointN::new([h.x, h.y]))
+            .collect();
+        Self {
+            grid: Dense::new(rule, offsets),
+        }
+    }
+
+    fn to_grid(cell: &HexPoint) -> PointN<2> {
+        PointN::new([cell.x, cell.y])
+    }
+}
+
+impl Simulation<HexPoint> for DenseHex {
+    fn is_live(&self, cell: &HexPoint) -> bool {
+        self.grid.get(&Self::to_grid(cell))
+    }
+
+    fn set_live(&mut self, cell: HexPoint, live: bool) {
+        self.grid.set(Self::to_grid(&cell), live);
+    }
+
+    fn advance(&mut self, generations: usize) {
+        self.grid.simulate(generations);
+    }
+
+    fn generation(&self) -> usize {
+        self.grid.generation
+    }
+
+    fn count_live(&self) -> usize {
+        self.grid.count_live()
+    }
+
+    fn live_cells(&self) -> Vec<HexPoint> {
+        self.grid
+            .live_cells()
+            .into_iter()
+            .map(|p| HexPoint::new(p[0], p[1], -p[0] - p[1]))
+            .collect()
+    }
+}
+
+// Any 2D neighborhood, with offsets from Neighborhood::offsets_2d
+impl Dense<2> {
+    pub fn from_offsets_2d(rule: &LifeRule, offsets: &[Point]) -> Self {
+        Self::new(rule, offsets.iter().map(|&p| PointN::from(p)).collect())
+    }
+}
+
+impl Simulation<Point> for Dense<2> {
+    fn is_live(&self, cell: &Point) -> bool {
+        self.get(&PointN::from(*cell))
+    }
+
+    fn set_live(&mut self, cell: Point, live: bool) {
+        self.set(PointN::from(cell), live);
+    }
+
+    fn advance(&mut self, generations: usize) {
+        self.simulate(generations);
+    }
+
+    fn generation(&self) -> usize {
+        self.generation
+    }
+
+    fn count_live(&self) -> usize {
+        Dense::count_live(self)
+    }
+
+    fn live_cells(&self) -> Vec<Point> {
+        Dense::live_cells(self)
+            .into_iter()
+            .map(Point::from)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::{Automaton, Cube, Hex};
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_grows_and_shrinks() {
+        let conway = LifeRule::new(&[3], &[2, 3]);
+        let blinker: Vec<PointN<2>> = vec![[5, 9], [6, 9], [7, 9]]
+            .into_iter()
+            .map(PointN::new)
+            .collect();
+        let mut dense = Dense::cube(&conway).with_cells(&blinker);
+        assert_eq!(dense.get(&PointN::new([6, 9])), true);
+        assert_eq!(dense.get(&PointN::new([0, 0])), false);
+
+        dense.step();
+        let vertical: HashSet<PointN<2>> = vec![[6, 8], [6, 9], [6, 10]]
+            .into_iter()
+            .map(PointN::new)
+            .collect();
+        assert_eq!(
+            dense.live_cells().into_iter().collect::<HashSet<_>>(),
+            vertical
+        );
+        assert_eq!(dense.bounds.volume(), 5 * 3);
+
+        dense.set(PointN::new([6, 9]), false);
+        dense.simulate(2);
+        assert_eq!(dense.count_live(), 0);
+        assert_eq!(dense.generation, 3);
+    }
+
+    #[test]
+    fn test_matches_sparse() {
+        let rule = LifeRule::new(&[3], &[2, 3]);
+        let seed: Vec<PointN<3>> = vec![[1, 0, 0], [2, 1, 0], [0, 2, 0], [1, 2, 0], [2, 2, 0]]
+            .into_iter()
+            .map(PointN::new)
+            .collect();
+        let mut dense = Dense::cube(&rule).with_cells(&seed);
+        let mut sparse = Automaton::new(Cube::<3>::new(), rule.clone())
+            .with_cells(seed.iter().map(|&p| (p, true)));
+        for _ in 0..4 {
+            dense.step();
+            sparse.step();
+            let dense_cells: HashSet<PointN<3>> = dense.live_cells().into_iter().collect();
+            let sparse_cells: HashSet<PointN<3>> = sparse.live_cells().copied().collect();
+            assert_eq!(dense_cells, sparse_cells);
+        }
+
+        let rule = LifeRule::new(&[2], &[1, 2]);
+        let seed = [
+            HexPoint::new(0, 0, 0),
+            HexPoint::new(1, -1, 0),
+            HexPoint::new(2, -1, -1),
+        ];
+        let mut dense = DenseHex::new(&rule);
+        for &cell in seed.iter() {
+            dense.set_live(cell, true);
+        }
+        let mut sparse = Automaton::new(Hex, rule).with_cells(seed.iter().map(|&p| (p, true)));
+        for _ in 0..10 {
+            dense.advance(1);
+            sparse.step();
+            let dense_cells: HashSet<HexPoint> = dense.live_cells().into_iter().collect();
+            let sparse_cells: HashSet<HexPoint> = sparse.live_cells().copied().collect();
+            assert_eq!(dense_cells, sparse_cells);
+        }
+    }
+}
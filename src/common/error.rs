@@ -0,0 +1,120 @@
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    pub day: u32,
+    pub line: usize,
+    pub column: usize,
+    pub expected: String,
+    pub found: String,
+}
+
+impl ParseError {
+    pub fn new(day: u32, line: usize, column: usize, expected: &str, found: &str) -> Self {
+        Self {
+            day,
+            line,
+            column,
+            expected: expected.to_owned(),
+            found: found.to_owned(),
+        }
+    }
+
+    // Locates the error within the input. The location must be a slice of the input, which is the case
+    // for any remaining input handed back by a nom parser.
+    pub fn at(day: u32, input: &str, location: &str, expected: &str) -> Self {
+        let offset = (location.as_ptr() as usize)
+            .checked_sub(input.as_ptr() as usize)
+            .filter(|&offset| offset <= input.len())
+            .unwrap_or(input.len());
+
+        let before = &input[..offset];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+        let column = input[line_start..offset].chars().count() + 1;
+        let found = input[offset..].lines().next().unwrap_or("").trim_end();
+
+        Self::new(day, line, column, expected, found)
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "day {}, line {}, column {}: expected {}, ",
+            self.day, self.line, self.column, self.expected
+        )?;
+        if self.found.is_empty() {
+            write!(f, "found end of line")
+        } else {
+            write!(f, "found \"{}\"", self.found)
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// Finds the first character that isn't whitespace or one of the allowed characters, for inputs that are
+// parsed leniently (such as a TileSet, which treats any unknown character as empty).
+pub fn check_chars(day: u32, input: &str, allowed: &str, expected: &str) -> Result<(), ParseError> {
+    match input.find(|c: char| !c.is_whitespace() && !allowed.contains(c)) {
+        Some(idx) => Err(ParseError::at(day, input, &input[idx..], expected)),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_error_at() {
+        let input = "abc\ndef\nghi";
+        assert_eq!(
+            ParseError::at(1, input, &input[0..], "x"),
+            ParseError::new(1, 1, 1, "x", "abc")
+        );
+        assert_eq!(
+            ParseError::at(1, input, &input[5..], "x"),
+            ParseError::new(1, 2, 2, "x", "ef")
+        );
+        assert_eq!(
+            ParseError::at(1, input, &input[8..], "x"),
+            ParseError::new(1, 3, 1, "x", "ghi")
+        );
+        assert_eq!(
+            ParseError::at(1, input, &input[11..], "x"),
+            ParseError::new(1, 3, 4, "x", "")
+        );
+
+        // A location from a different string is reported as the end of the input
+        assert_eq!(
+            ParseError::at(1, input, "def", "x"),
+            ParseError::new(1, 3, 4, "x", "")
+        );
+    }
+
+    #[test]
+    fn test_parse_error_display() {
+        let input = "nop +0\nacc +1\nfoo +2";
+        let error = ParseError::at(8, input, &input[14..], "an instruction");
+        assert_eq!(
+            error.to_string(),
+            "day 8, line 3, column 1: expected an instruction, found \"foo +2\""
+        );
+
+        let error = ParseError::at(8, input, &input[20..], "a number");
+        assert_eq!(
+            error.to_string(),
+            "day 8, line 3, column 7: expected a number, found end of line"
+        );
+    }
+
+    #[test]
+    fn test_check_chars() {
+        assert_eq!(check_chars(3, "..#\n#..\n", ".#", "'.' or '#'"), Ok(()));
+        assert_eq!(
+            check_chars(3, "..#\n#x.\n", ".#", "'.' or '#'"),
+            Err(ParseError::new(3, 2, 2, "'.' or '#'", "x."))
+        );
+    }
+}
@@ -0,0 +1,330 @@
+use crate::common::{
+    Grid, NetpbmFormat, PointN, Rect, RectN, Rgb, RgbImage, Simulation, TileChar, TileMap, TileSet,
+};
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+pub type Pixel = (char, Rgb);
+
+pub const BACKGROUND: Pixel = ('.', [0, 0, 0]);
+pub const FOREGROUND: Pixel = ('#', [255, 255, 255]);
+// Fills the gaps between slices of 3D and higher dimensional frames
+pub const SEPARATOR: Pixel = (' ', [96, 96, 96]);
+
+// A picture of one generation, which can be written as text or as an image
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frame {
+    width: usize,
+    height: usize,
+    pixels: Vec<Pixel>,
+}
+
+impl Frame {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![BACKGROUND; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Pixel {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, pixel: Pixel) {
+        self.pixels[y * self.width + x] = pixel;
+    }
+
+    pub fn from_grid<T: TileChar>(grid: &Grid<T>) -> Self {
+        let pixels = grid
+            .tiles()
+            .iter()
+            .map(|t| (t.to_char(), t.color()))
+            .collect();
+        Self {
+            width: grid.width(),
+            height: grid.height(),
+            pixels,
+        }
+    }
+
+    pub fn from_tilemap<T: TileChar>(tilemap: &TileMap<T>, bounds: Rect) -> Self {
+        let mut frame = Self::new(bounds.width(), bounds.height());
+        for (p, t) in tilemap.iter().filter(|(p, _)| bounds.contains(p)) {
+            let (x, y) = ((p.x - bounds.min.x) as usize, (p.y - bounds.min.y) as usize);
+            frame.set(x, y, (t.to_char(), t.color()));
+        }
+        frame
+    }
+
+    pub fn from_tileset(tileset: &TileSet, bounds: Rect) -> Self {
+        let mut frame = Self::new(bounds.width(), bounds.height());
+        for p in tileset.iter().filter(|p| bounds.contains(p)) {
+            let (x, y) = ((p.x - bounds.min.x) as usize, (p.y - bounds.min.y) as usize);
+            frame.set(x, y, FOREGROUND);
+        }
+        frame
+    }
+
+    // Draws an x/y slice for each combination of the other coordinates. Slices are laid out in a grid with
+    // z increasing to the right and the remaining dimensions increasing downwards, w changing fastest.
+    pub fn from_cells<'a, I, const N: usize>(cells: I, bounds: RectN<N>) -> Self
+    where
+        I: std::iter::IntoIterator<Item = &'a PointN<N>>,
+    {
+        let (slice_width, slice_height) = (bounds.len(0), bounds.len(1));
+        let columns = if N > 2 { bounds.len(2) } else { 1 };
+        let rows: usize = (3..N).map(|d| bounds.len(d)).product();
+        let mut frame = Self::new(
+            columns * (slice_width + 1) - 1,
+            rows * (slice_height + 1) - 1,
+        );
+        for x in 0..frame.width {
+            for y in 0..frame.height {
+                if x % (slice_width + 1) == slice_width || y % (slice_height + 1) == slice_height {
+                    frame.set(x, y, SEPARATOR);
+                }
+            }
+        }
+
+        for p in cells.into_iter().filter(|p| bounds.contains(p)) {
+            let offset = |d: usize| (p[d] - bounds.min[d]) as usize;
+            let column = if N > 2 { offset(2) } else { 0 };
+            let mut row = 0;
+            for d in (3..N).rev() {
+                row = row * bounds.len(d) + offset(d);
+            }
+            let x = column * (slice_width + 1) + offset(0);
+            let y = row * (slice_height + 1) + offset(1);
+            frame.set(x, y, FOREGROUND);
+        }
+        frame
+    }
+
+    pub fn to_image(&self) -> RgbImage {
+        let mut image = RgbImage::new(self.width, self.height, BACKGROUND.1);
+        for (i, &(_, rgb)) in self.pixels.iter().enumerate() {
+            image.set(i % self.width, i / self.width, rgb);
+        }
+        image
+    }
+
+    pub fn to_ppm(&self) -> Vec<u8> {
+        self.to_image().encode(NetpbmFormat::Ppm)
+    }
+
+    pub fn to_pgm(&self) -> Vec<u8> {
+        self.to_image().encode(NetpbmFormat::Pgm)
+    }
+}
+
+impl std::fmt::Display for Frame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // A frame with no columns has nothing to split into rows
+        if self.width == 0 {
+            return Ok(());
+        }
+        for row in self.pixels.chunks(self.width) {
+            let line: String = row.iter().map(|(c, _)| c).collect();
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FrameFormat {
+    Ppm,
+    Pgm,
+    Ascii,
+}
+
+impl FrameFormat {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "ppm" => Some(Self::Ppm),
+            "pgm" => Some(Self::Pgm),
+            "ascii" => Some(Self::Ascii),
+            _ => None,
+        }
+    }
+}
+
+// Writes each frame as a numbered image in a directory, or appends them all to one text file with a
+// separator line before each frame
+pub struct FrameWriter {
+    format: FrameFormat,
+    path: PathBuf,
+    ascii: Option<BufWriter<File>>,
+    frames: usize,
+}
+
+impl FrameWriter {
+    pub fn create<P: AsRef<Path>>(path: P, format: FrameFormat) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let ascii = match format {
+            FrameFormat::Ascii => Some(BufWriter::new(File::create(&path)?)),
+            FrameFormat::Ppm | FrameFormat::Pgm => {
+                fs::create_dir_all(&path)?;
+                None
+            }
+        };
+        Ok(Self {
+            format,
+            path,
+            ascii,
+            frames: 0,
+        })
+    }
+
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    pub fn write(&mut self, frame: &Frame) -> io::Result<()> {
+        match (self.format, self.ascii.as_mut()) {
+            (FrameFormat::Ascii, Some(file)) => {
+                write!(file, "-- frame {} --\n{}", self.frames, frame)?
+            }
+            (FrameFormat::Ppm, _) => fs::write(self.image_path("ppm"), frame.to_ppm())?,
+            (FrameFormat::Pgm, _) => fs::write(self.image_path("pgm"), frame.to_pgm())?,
+            (FrameFormat::Ascii, None) => unreachable!(),
+        }
+        self.frames += 1;
+        Ok(())
+    }
+
+    pub fn finish(self) -> io::Result<()> {
+        match self.ascii {
+            Some(mut file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+
+    fn image_path(&self, extension: &str) -> PathBuf {
+        self.path
+            .join(format!("frame_{:05}.{}", self.frames, extension))
+    }
+}
+
+// Writes the current generation and the given number after it. Every generation is run before anything is
+// drawn so that all the frames can cover the same bounds.
+pub fn record_cells<const N: usize>(
+    simulation: &mut dyn Simulation<PointN<N>>,
+    generations: usize,
+    writer: &mut FrameWriter,
+) -> io::Result<()> {
+    let mut history = vec![simulation.live_cells()];
+    for _ in 0..generations {
+        simulation.advance(1);
+        history.push(simulation.live_cells());
+    }
+
+    let bounds = RectN::from_points(history.iter().flatten())
+        .unwrap_or_else(|| RectN::new(PointN::origin(), PointN::origin()));
+    for cells in history.iter() {
+        writer.write(&Frame::from_cells(cells, bounds))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::{Dense, LifeRule, Point};
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("aoc_2020_frames_{}_{}", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_from_cells() {
+        // One cell in each of the four z/w slices, at a different x/y each time
+        let cells: Vec<PointN<4>> = vec![[0, 0, 0, 0], [1, 0, 1, 0], [0, 1, 0, 1], [1, 1, 1, 1]]
+            .into_iter()
+            .map(PointN::new)
+            .collect();
+        let bounds = RectN::from_points(&cells).unwrap();
+        let frame = Frame::from_cells(&cells, bounds);
+        let expected = ["#. .#", ".. ..", "     ", ".. ..", "#. .#"];
+        assert_eq!(frame.to_string(), expected.join("\n") + "\n");
+
+        let flat = Frame::from_cells(
+            &[PointN::new([2, 3])],
+            RectN::new(PointN::new([1, 3]), PointN::new([3, 3])),
+        );
+        assert_eq!(flat.to_string(), ".#.\n");
+    }
+
+    #[test]
+    fn test_images() {
+        let tileset = TileSet::new().with_tiles(&[Point { x: 1, y: 0 }]);
+        let frame = Frame::from_tileset(
+            &tileset,
+            Rect::new(Point { x: 0, y: 0 }, Point { x: 1, y: 0 }),
+        );
+        assert_eq!(frame.to_ppm(), b"P6\n2 1\n255\n\0\0\0\xff\xff\xff".to_vec());
+        assert_eq!(frame.to_pgm(), b"P5\n2 1\n255\n\0\xff".to_vec());
+    }
+
+    #[test]
+    fn test_empty() {
+        assert_eq!(Frame::new(0, 3).to_string(), "");
+        assert_eq!(Frame::new(3, 0).to_string(), "");
+        assert_eq!(Frame::new(0, 3).to_pgm(), b"P5\n0 3\n255\n".to_vec());
+    }
+
+    #[test]
+    fn test_record() {
+        let conway = LifeRule::new(&[3], &[2, 3]);
+        let blinker: Vec<PointN<2>> = vec![[0, 1], [1, 1], [2, 1]]
+            .into_iter()
+            .map(PointN::new)
+            .collect();
+
+        let path = temp_path("blinker.txt");
+        let mut writer = FrameWriter::create(&path, FrameFormat::Ascii).unwrap();
+        let mut simulation = Dense::cube(&conway).with_cells(&blinker);
+        record_cells(&mut simulation, 2, &mut writer).unwrap();
+        assert_eq!(writer.frames(), 3);
+        writer.finish().unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "\
+-- frame 0 --
+...
+###
+...
+-- frame 1 --
+.#.
+.#.
+.#.
+-- frame 2 --
+...
+###
+...
+"
+        );
+        fs::remove_file(&path).unwrap();
+
+        let path = temp_path("blinker");
+        let mut writer = FrameWriter::create(&path, FrameFormat::Pgm).unwrap();
+        record_cells(&mut simulation, 1, &mut writer).unwrap();
+        let image = fs::read(path.join("frame_00001.pgm")).unwrap();
+        assert_eq!(&image[..11], b"P5\n3 3\n255\n");
+        assert_eq!(fs::read_dir(&path).unwrap().count(), 2);
+        fs::remove_dir_all(&path).unwrap();
+    }
+}
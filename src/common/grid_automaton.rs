@@ -0,0 +1,381 @@
+use crate::common::{Cycle, CycleDetector, Grid, LifeRule, Point, Rule, Simulation, Topology};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+// The neighbors of every cell of a bounded grid, worked out once up front as indexes into it. Each cell sees
+// its neighbors along lines, nearest first, and a cell the rule says blocks sight hides the rest of its
+// line. Most neighborhoods give every neighbor a line of its own, so nothing is ever hidden.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GridTopology {
+    width: usize,
+    height: usize,
+    cells: Vec<usize>, // Cell i's lines are lines[cells[i]..=cells[i + 1]]
+    lines: Vec<usize>, // Line j's neighbors are neighbors[lines[j]..lines[j + 1]]
+    neighbors: Vec<usize>,
+    single: bool, // Every line has just one neighbor, so counting can skip looking for blockers
+}
+
+impl GridTopology {
+    // Builds each cell's lines of neighbors with the given function. Neighbors outside the grid are ignored.
+    pub fn new<F>(width: usize, height: usize, mut lines_of: F) -> Self
+    where
+        F: FnMut(Point) -> Vec<Vec<Point>>,
+    {
+        let bounds: Grid<()> = Grid::new(width, height, ());
+        let mut cells = vec![0];
+        let mut lines = vec![0];
+        let mut neighbors = Vec::new();
+        for p in bounds.points() {
+            for line in lines_of(p) {
+                neighbors.extend(line.iter().filter_map(|n| bounds.point_to_idx(n)));
+                if neighbors.len() > *lines.last().unwrap() {
+                    lines.push(neighbors.len());
+                }
+            }
+            cells.push(lines.len() - 1);
+        }
+        let single = lines.windows(2).all(|w| w[1] - w[0] == 1);
+        Self {
+            width,
+            height,
+            cells,
+            lines,
+            neighbors,
+            single,
+        }
+    }
+
+    // Every neighbor given by an unbounded topology which is inside the grid, each on a line of its own
+    pub fn bounded<T: Topology<Cell = Point>>(width: usize, height: usize, topology: &T) -> Self {
+        Self::new(width, height, |p| {
+            let mut lines = Vec::new();
+            topology.for_each_neighbor(&p, |n| lines.push(vec![n]));
+            lines
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn count_live<L, B>(&self, cell: usize, is_live: L, blocks: B) -> usize
+    where
+        L: Fn(usize) -> bool,
+        B: Fn(usize) -> bool,
+    {
+        let lines = &self.lines[self.cells[cell]..=self.cells[cell + 1]];
+        let (first, last) = (lines[0], lines[lines.len() - 1]);
+        if self.single == true {
+            return self.neighbors[first..last]
+                .iter()
+                .filter(|&&n| is_live(n))
+                .count();
+        }
+
+        let mut count = 0;
+        for line in lines.windows(2) {
+            for &n in self.neighbors[line[0]..line[1]].iter() {
+                if is_live(n) == true {
+                    count += 1;
+                }
+                if blocks(n) == true {
+                    break;
+                }
+            }
+        }
+        count
+    }
+}
+
+impl Topology for GridTopology {
+    type Cell = Point;
+
+    // Every cell on any of the cell's lines, whether or not it could be hidden
+    fn for_each_neighbor<F: FnMut(Point)>(&self, cell: &Point, f: F) {
+        let bounds: Grid<()> = Grid::new(self.width, self.height, ());
+        if let Some(idx) = bounds.point_to_idx(cell) {
+            let lines = &self.lines[self.cells[idx]..=self.cells[idx + 1]];
+            self.neighbors[lines[0]..lines[lines.len() - 1]]
+                .iter()
+                .map(|&n| bounds.idx_to_point(n))
+                .for_each(f);
+        }
+    }
+}
+
+// An automaton on a bounded grid, storing every cell's state. Unlike Automaton there's no endless space
+// around the cells, so any rule works, including those where a cell with no live neighbors changes.
+pub struct GridAutomaton<R: Rule> {
+    topology: GridTopology,
+    rule: R,
+    grid: Grid<R::State>,
+    changes: Vec<(usize, R::State)>, // Kept between steps to save reallocating it
+    generation: usize,
+}
+
+impl<R: Rule> GridAutomaton<R> {
+    // Every cell starts in the default state
+    pub fn new(topology: GridTopology, rule: R) -> Self {
+        let grid = Grid::new(topology.width, topology.height, R::State::default());
+        Self {
+            topology,
+            rule,
+            grid,
+            changes: Vec::new(),
+            generation: 0,
+        }
+    }
+
+    pub fn with_grid(self, grid: Grid<R::State>) -> Self {
+        assert!(
+            grid.width() == self.topology.width && grid.height() == self.topology.height,
+            "Grid size doesn't match the topology"
+        );
+        Self { grid, ..self }
+    }
+
+    pub fn topology(&self) -> &GridTopology {
+        &self.topology
+    }
+
+    pub fn rule(&self) -> &R {
+        &self.rule
+    }
+
+    pub fn grid(&self) -> &Grid<R::State> {
+        &self.grid
+    }
+
+    pub fn into_grid(self) -> Grid<R::State> {
+        self.grid
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    // Cells outside the grid are always in the default state
+    pub fn get(&self, cell: &Point) -> R::State {
+        self.grid.get(cell).copied().unwrap_or_default()
+    }
+
+    pub fn set(&mut self, cell: Point, state: R::State) {
+        *self.grid.get_mut(&cell).expect("Cell is outside the grid") = state;
+    }
+
+    pub fn live_cells(&self) -> impl Iterator<Item = Point> + '_ {
+        self.grid
+            .iter()
+            .filter(move |(_cell, &state)| self.rule.is_live(state))
+            .map(|(cell, _state)| cell)
+    }
+
+    pub fn count_live(&self) -> usize {
+        self.grid
+            .tiles()
+            .iter()
+            .filter(|&&state| self.rule.is_live(state))
+            .count()
+    }
+
+    // Advances one generation, returning whether any cell changed
+    pub fn step(&mut self) -> bool {
+        let (topology, rule, tiles) = (&self.topology, &self.rule, self.grid.tiles());
+        self.changes.clear();
+        for (idx, &state) in tiles.iter().enumerate() {
+            let live_neighbors = topology.count_live(
+                idx,
+                |n| rule.is_live(tiles[n]),
+                |n| rule.blocks_sight(tiles[n]),
+            );
+            let new_state = rule.next(state, live_neighbors);
+            if new_state != state {
+                self.changes.push((idx, new_state));
+            }
+        }
+
+        let tiles = self.grid.tiles_mut();
+        for &(idx, state) in self.changes.iter() {
+            tiles[idx] = state;
+        }
+        self.generation += 1;
+        self.changes.is_empty() == false
+    }
+
+    pub fn simulate(&mut self, steps: usize) {
+        for _ in 0..steps {
+            self.step();
+        }
+    }
+}
+
+impl<R: Rule> GridAutomaton<R>
+where
+    R::State: Hash,
+{
+    pub fn state_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.grid.tiles().hash(&mut hasher);
+        hasher.finish()
+    }
+
+    // Steps until a state repeats, giving up after max_steps. The cycle's generations are the same as
+    // the automaton's and it is left at the first generation which repeated.
+    pub fn find_cycle(&mut self, max_steps: usize) -> Option<Cycle> {
+        let first = self.generation;
+        let mut detector = CycleDetector::new();
+        self.run_detector(&mut detector, max_steps)
+            .map(|cycle| Cycle {
+                start: first + cycle.start,
+                length: cycle.length,
+            })
+    }
+
+    // Moves to the given generation, jumping ahead once the states start to repeat. Returns whether the
+    // generation was reached, which it won't be if there's no cycle within max_steps.
+    pub fn simulate_to(&mut self, generation: usize, max_steps: usize) -> bool {
+        assert!(generation >= self.generation, "Can't simulate backwards");
+        let first = self.generation;
+        let mut detector = CycleDetector::new();
+        let max_steps = max_steps.min(generation - first);
+        if self.run_detector(&mut detector, max_steps).is_none() {
+            return self.generation == generation;
+        }
+
+        let tiles = detector.state_at(generation - first).unwrap().clone();
+        self.grid = Grid::from_vec(self.grid.width(), self.grid.height(), tiles);
+        self.generation = generation;
+        true
+    }
+
+    fn run_detector(
+        &mut self,
+        detector: &mut CycleDetector<Vec<R::State>>,
+        max_steps: usize,
+    ) -> Option<Cycle> {
+        for _ in 0..max_steps {
+            if let Some(cycle) = detector.push(self.state_hash(), self.grid.tiles().to_vec()) {
+                return Some(cycle);
+            }
+            self.step();
+        }
+        detector.push(self.state_hash(), self.grid.tiles().to_vec())
+    }
+}
+
+// Cells outside the grid are always dead, so setting one live panics
+impl Simulation<Point> for GridAutomaton<LifeRule> {
+    fn is_live(&self, cell: &Point) -> bool {
+        self.get(cell)
+    }
+
+    fn set_live(&mut self, cell: Point, live: bool) {
+        if live == true || self.grid.in_bounds(&cell) {
+            self.set(cell, live);
+        }
+    }
+
+    fn advance(&mut self, generations: usize) {
+        self.simulate(generations);
+    }
+
+    fn generation(&self) -> usize {
+        self.generation
+    }
+
+    fn count_live(&self) -> usize {
+        GridAutomaton::count_live(self)
+    }
+
+    fn live_cells(&self) -> Vec<Point> {
+        GridAutomaton::live_cells(self).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::{Automaton, Square};
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_matches_sparse() {
+        // Away from the edges the bounded grid behaves just like the endless one
+        let conway = LifeRule::new(&[3], &[2, 3]);
+        let glider = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+        let mut sparse = Automaton::new(Square::adjacent(), conway.clone())
+            .with_cells(glider.iter().map(|&p| (Point::from(p), true)));
+        let mut dense =
+            GridAutomaton::new(GridTopology::bounded(20, 20, &Square::adjacent()), conway);
+        for &p in glider.iter() {
+            dense.set_live(p.into(), true);
+        }
+        for _ in 0..20 {
+            assert_eq!(sparse.step(), dense.step());
+            let sparse_cells: HashSet<Point> = sparse.live_cells().copied().collect();
+            let dense_cells: HashSet<Point> = dense.live_cells().collect();
+            assert_eq!(dense_cells, sparse_cells);
+        }
+    }
+
+    #[test]
+    fn test_birth_from_nothing() {
+        // Every cell with no live neighbors is born, which only works because the grid ends
+        let rule = LifeRule::new(&[0], &[]);
+        let mut automaton =
+            GridAutomaton::new(GridTopology::bounded(3, 3, &Square::adjacent()), rule);
+        automaton.step();
+        assert_eq!(automaton.count_live(), 9);
+        automaton.step();
+        assert_eq!(automaton.count_live(), 0);
+        assert_eq!(
+            automaton.find_cycle(10),
+            Some(Cycle {
+                start: 2,
+                length: 2
+            })
+        );
+        assert_eq!(automaton.simulate_to(1_000_001, 10), true);
+        assert_eq!(automaton.count_live(), 9);
+    }
+
+    #[test]
+    fn test_lines() {
+        // A row of cells which each see along the row in both directions, where live cells block sight
+        let lines_of = |p: Point| {
+            let left = (0..p.x).rev().map(|x| Point { x, y: 0 }).collect();
+            let right = (p.x + 1..5).map(|x| Point { x, y: 0 }).collect();
+            vec![left, right]
+        };
+        let topology = GridTopology::new(5, 1, lines_of);
+        let mut neighbors = Vec::new();
+        topology.for_each_neighbor(&Point { x: 2, y: 0 }, |n| neighbors.push(n.x));
+        assert_eq!(neighbors, vec![1, 0, 3, 4]);
+
+        struct Blocking;
+        impl Rule for Blocking {
+            type State = bool;
+            fn is_live(&self, state: bool) -> bool {
+                state
+            }
+            fn next(&self, _state: bool, live_neighbors: usize) -> bool {
+                live_neighbors == 2
+            }
+            fn blocks_sight(&self, state: bool) -> bool {
+                state
+            }
+        }
+
+        // Only the middle cell sees both live cells, since each hides the other from the ends
+        let mut automaton = GridAutomaton::new(topology, Blocking);
+        automaton.set(Point { x: 1, y: 0 }, true);
+        automaton.set(Point { x: 3, y: 0 }, true);
+        automaton.step();
+        let live: Vec<i32> = automaton.live_cells().map(|p| p.x).collect();
+        assert_eq!(live, vec![2]);
+    }
+}
@@ -0,0 +1,467 @@
+use crate::common::{LifeRule, Neighborhood, Point, Rule, Simulation, TileSet};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+// Nodes are squares of cells with a side of 2^level. Leaves (level 0) are single cells, and every other node
+// is made of four children of the level below. Identical squares are only stored once, so a node's id is
+// enough to compare them, and each node's future can be remembered and reused wherever it appears.
+type NodeId = usize;
+
+const DEAD: NodeId = 0;
+const ALIVE: NodeId = 1;
+
+#[derive(Clone, Copy)]
+struct Node {
+    children: [NodeId; 4], // nw, ne, sw, se
+    level: usize,
+    population: u64,
+}
+
+// Simulates a 2D life-like rule using Gosper's hashlife algorithm, which can advance huge numbers of
+// generations at once for patterns with a lot of repetition in space or time.
+pub struct Hashlife {
+    nodes: Vec<Node>,
+    lookup: HashMap<[NodeId; 4], NodeId>,
+    results: HashMap<(NodeId, usize), NodeId>,
+    empty: Vec<NodeId>,
+    rule: LifeRule,
+    offsets: Vec<(usize, usize)>,
+    root: NodeId,
+    generation: usize,
+}
+
+impl Hashlife {
+    pub fn new(rule: LifeRule, neighborhood: Neighborhood) -> Self {
+        let offsets = neighborhood
+            .offsets_2d()
+            .expect("Hashlife only supports 2D neighborhoods");
+        assert!(
+            rule.next(false, 0) == false,
+            "Rule must leave dead cells with no live neighbors unchanged"
+        );
+
+        let leaf = |population| Node {
+            children: [DEAD; 4],
+            level: 0,
+            population,
+        };
+        let mut hashlife = Self {
+            nodes: vec![leaf(0), leaf(1)],
+            lookup: HashMap::new(),
+            results: HashMap::new(),
+            empty: vec![DEAD],
+            rule,
+            // Neighbors as offsets from the cell at (1, 1) so they can index a small grid
+            offsets: offsets
+                .into_iter()
+                .map(|p| ((p.x + 1) as usize, (p.y + 1) as usize))
+                .collect(),
+            root: DEAD,
+            generation: 0,
+        };
+        hashlife.root = hashlife.empty(3);
+        hashlife
+    }
+
+    pub fn with_tileset(mut self, tiles: &TileSet) -> Self {
+        for p in tiles.iter() {
+            self.set(p, true);
+        }
+        self
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn population(&self) -> u64 {
+        self.nodes[self.root].population
+    }
+
+    pub fn get(&self, p: &Point) -> bool {
+        let half = 1i64 << (self.level() - 1);
+        let (x, y) = (p.x as i64 + half, p.y as i64 + half);
+        let size = 2 * half;
+        if x < 0 || y < 0 || x >= size || y >= size {
+            return false;
+        }
+
+        let mut node = self.root;
+        let (mut x, mut y) = (x as u64, y as u64);
+        while self.nodes[node].level > 0 {
+            let half = 1 << (self.nodes[node].level - 1);
+            node = self.children(node)[(x >= half) as usize + 2 * (y >= half) as usize];
+            x %= half;
+            y %= half;
+        }
+        node == ALIVE
+    }
+
+    pub fn set(&mut self, p: &Point, live: bool) {
+        if live == self.get(p) {
+            return;
+        }
+
+        // Grow until the point fits, with the root covering -2^(level-1) to 2^(level-1) - 1
+        let fits = |level: usize| {
+            let half = 1i64 << (level - 1);
+            (-half..half).contains(&(p.x as i64)) && (-half..half).contains(&(p.y as i64))
+        };
+        while fits(self.level()) == false {
+            self.root = self.expand(self.root);
+        }
+
+        let half = 1i64 << (self.level() - 1);
+        let (x, y) = (p.x as i64 + half, p.y as i64 + half);
+        let leaf = if live == true { ALIVE } else { DEAD };
+        self.root = self.set_in(self.root, x as u64, y as u64, leaf);
+    }
+
+    // Advances 2^k generations in one go
+    pub fn step_pow2(&mut self, k: usize) {
+        // The pattern can only spread one cell per generation, so before advancing it must be within the
+        // middle quarter of the root, with at least 2^k cells of space around it in the result.
+        loop {
+            if self.level() >= k + 3 {
+                let middle = self.center(self.root);
+                let middle = self.center(middle);
+                if self.nodes[middle].population == self.population() {
+                    break;
+                }
+            }
+            self.root = self.expand(self.root);
+        }
+
+        self.root = self.result(self.root, k);
+        self.generation += 1 << k;
+    }
+
+    pub fn simulate(&mut self, generations: usize) {
+        for k in 0..usize::BITS as usize {
+            if generations & (1 << k) != 0 {
+                self.step_pow2(k);
+            }
+        }
+    }
+
+    // The pattern can spread far enough to leave a Point's range behind, so this is None if any live cell
+    // is out of reach. live_cells works however far they've gone.
+    pub fn to_tileset(&self) -> Option<TileSet> {
+        self.points()
+            .map(|points| TileSet::new().with_tiles(&points))
+    }
+
+    pub fn live_cells(&self) -> Vec<(i64, i64)> {
+        let mut cells = Vec::new();
+        let half = 1i64 << (self.level() - 1);
+        self.collect_cells(self.root, -half, -half, &mut cells);
+        cells
+    }
+
+    fn points(&self) -> Option<Vec<Point>> {
+        self.live_cells()
+            .into_iter()
+            .map(|(x, y)| {
+                Some(Point {
+                    x: i32::try_from(x).ok()?,
+                    y: i32::try_from(y).ok()?,
+                })
+            })
+            .collect()
+    }
+
+    fn collect_cells(&self, node: NodeId, x: i64, y: i64, cells: &mut Vec<(i64, i64)>) {
+        let Node {
+            children,
+            level,
+            population,
+        } = self.nodes[node];
+        if population == 0 {
+            return;
+        }
+        if level == 0 {
+            cells.push((x, y));
+            return;
+        }
+
+        let half = 1i64 << (level - 1);
+        for (i, &child) in children.iter().enumerate() {
+            let dx = (i % 2) as i64 * half;
+            let dy = (i / 2) as i64 * half;
+            self.collect_cells(child, x + dx, y + dy, cells);
+        }
+    }
+
+    fn level(&self) -> usize {
+        self.nodes[self.root].level
+    }
+
+    fn children(&self, node: NodeId) -> [NodeId; 4] {
+        self.nodes[node].children
+    }
+
+    fn join(&mut self, nw: NodeId, ne: NodeId, sw: NodeId, se: NodeId) -> NodeId {
+        let children = [nw, ne, sw, se];
+        if let Some(&id) = self.lookup.get(&children) {
+            return id;
+        }
+
+        let id = self.nodes.len();
+        let level = self.nodes[nw].level + 1;
+        let population = children.iter().map(|&c| self.nodes[c].population).sum();
+        self.nodes.push(Node {
+            children,
+            level,
+            population,
+        });
+        self.lookup.insert(children, id);
+        id
+    }
+
+    fn empty(&mut self, level: usize) -> NodeId {
+        while self.empty.len() <= level {
+            let e = *self.empty.last().unwrap();
+            let next = self.join(e, e, e, e);
+            self.empty.push(next);
+        }
+        self.empty[level]
+    }
+
+    // Surrounds the node with empty space, giving a node of the next level with this one in the middle
+    fn expand(&mut self, node: NodeId) -> NodeId {
+        let level = self.nodes[node].level;
+        let e = self.empty(level - 1);
+        let [nw, ne, sw, se] = self.children(node);
+        let nw = self.join(e, e, e, nw);
+        let ne = self.join(e, e, ne, e);
+        let sw = self.join(e, sw, e, e);
+        let se = self.join(se, e, e, e);
+        self.join(nw, ne, sw, se)
+    }
+
+    // The middle of the node, at the level below
+    fn center(&mut self, node: NodeId) -> NodeId {
+        let [nw, ne, sw, se] = self.children(node);
+        self.join(
+            self.children(nw)[3],
+            self.children(ne)[2],
+            self.children(sw)[1],
+            self.children(se)[0],
+        )
+    }
+
+    fn set_in(&mut self, node: NodeId, x: u64, y: u64, leaf: NodeId) -> NodeId {
+        let level = self.nodes[node].level;
+        if level == 0 {
+            return leaf;
+        }
+
+        let half = 1 << (level - 1);
+        let mut children = self.children(node);
+        let i = (x >= half) as usize + 2 * (y >= half) as usize;
+        children[i] = self.set_in(children[i], x % half, y % half, leaf);
+        let [nw, ne, sw, se] = children;
+        self.join(nw, ne, sw, se)
+    }
+
+    // The middle of the node (at the level below) after 2^k generations. This can advance at most
+    // 2^(level - 2) generations, since cells further out could be affected by what's beyond the node.
+    fn result(&mut self, node: NodeId, k: usize) -> NodeId {
+        let level = self.nodes[node].level;
+        let k = k.min(level - 2);
+        if self.nodes[node].population == 0 {
+            return self.empty(level - 1);
+        }
+        if let Some(&result) = self.results.get(&(node, k)) {
+            return result;
+        }
+
+        let result = if level == 2 {
+            self.result_4x4(node)
+        } else {
+            // Nine overlapping squares of the level below, covering the node in a 3x3 grid
+            let [a, b, c, d] = self.children(node);
+            let [_, a_ne, a_sw, a_se] = self.children(a);
+            let [b_nw, _, b_sw, b_se] = self.children(b);
+            let [c_nw, c_ne, _, c_se] = self.children(c);
+            let [d_nw, d_ne, d_sw, _] = self.children(d);
+            let squares = [
+                a,
+                self.join(a_ne, b_nw, a_se, b_sw),
+                b,
+                self.join(a_sw, a_se, c_nw, c_ne),
+                self.join(a_se, b_sw, c_ne, d_nw),
+                self.join(b_sw, b_se, d_nw, d_ne),
+                c,
+                self.join(c_ne, d_nw, c_se, d_sw),
+                d,
+            ];
+
+            // Go half of the way while shrinking to the middle of each square, then the other half while
+            // shrinking again. When going slower than the maximum, the first half just shrinks.
+            let mut r = [DEAD; 9];
+            for (i, &square) in squares.iter().enumerate() {
+                r[i] = if k == level - 2 {
+                    self.result(square, k)
+                } else {
+                    self.center(square)
+                };
+            }
+            let nw = self.join(r[0], r[1], r[3], r[4]);
+            let ne = self.join(r[1], r[2], r[4], r[5]);
+            let sw = self.join(r[3], r[4], r[6], r[7]);
+            let se = self.join(r[4], r[5], r[7], r[8]);
+            let nw = self.result(nw, k);
+            let ne = self.result(ne, k);
+            let sw = self.result(sw, k);
+            let se = self.result(se, k);
+            self.join(nw, ne, sw, se)
+        };
+
+        self.results.insert((node, k), result);
+        result
+    }
+
+    // The middle 2x2 cells of a 4x4 node after one generation, worked out directly
+    fn result_4x4(&mut self, node: NodeId) -> NodeId {
+        let mut cells = [[false; 4]; 4];
+        for (i, &child) in self.children(node).iter().enumerate() {
+            for (j, &leaf) in self.children(child).iter().enumerate() {
+                let x = (i % 2) * 2 + j % 2;
+                let y = (i / 2) * 2 + j / 2;
+                cells[y][x] = leaf == ALIVE;
+            }
+        }
+
+        let mut next = [DEAD; 4];
+        for (j, n) in next.iter_mut().enumerate() {
+            let (x, y) = (1 + j % 2, 1 + j / 2);
+            let live_neighbors = self
+                .offsets
+                .iter()
+                .filter(|&&(dx, dy)| cells[y + dy - 1][x + dx - 1] == true)
+                .count();
+            if self.rule.next(cells[y][x], live_neighbors) == true {
+                *n = ALIVE;
+            }
+        }
+        let [nw, ne, sw, se] = next;
+        self.join(nw, ne, sw, se)
+    }
+}
+
+impl Simulation<Point> for Hashlife {
+    fn is_live(&self, cell: &Point) -> bool {
+        self.get(cell)
+    }
+
+    fn set_live(&mut self, cell: Point, live: bool) {
+        self.set(&cell, live);
+    }
+
+    fn advance(&mut self, generations: usize) {
+        self.simulate(generations);
+    }
+
+    fn generation(&self) -> usize {
+        self.generation
+    }
+
+    fn count_live(&self) -> usize {
+        self.population() as usize
+    }
+
+    // Panics once any live cell is too far out for a Point
+    fn live_cells(&self) -> Vec<Point> {
+        self.points().expect("Cell is too far out for a Point")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::LifeLike;
+
+    // A small xorshift generator, so random seeds can be tested without any extra dependencies
+    fn random_soup(seed: u64, size: i32) -> TileSet {
+        let mut state = seed;
+        let mut points = Vec::new();
+        for y in 0..size {
+            for x in 0..size {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                if state.is_multiple_of(3) {
+                    points.push(Point { x, y });
+                }
+            }
+        }
+        TileSet::new().with_tiles(&points)
+    }
+
+    #[test]
+    fn test_matches_sparse() {
+        for rule in &["B3/S23", "B36/S23", "B1/S012V", "B2/S12H"] {
+            let life_like: LifeLike = rule.parse().unwrap();
+            for seed in 1..=4u64 {
+                let soup = random_soup(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15), 12);
+                let mut hashlife = Hashlife::new(life_like.rule.clone(), life_like.neighborhood)
+                    .with_tileset(&soup);
+                let mut generation = 0;
+                for &steps in &[1, 2, 5, 16] {
+                    hashlife.simulate(steps);
+                    generation += steps;
+                    let expected = life_like.simulate_2d(&soup, generation).unwrap();
+                    let tileset = hashlife.to_tileset().unwrap();
+                    assert_eq!(tileset, expected, "{} seed {}", rule, seed);
+                    assert_eq!(hashlife.population(), expected.len() as u64);
+                }
+                assert_eq!(hashlife.generation(), 24);
+            }
+        }
+    }
+
+    #[test]
+    fn test_huge_generations() {
+        let conway: LifeLike = "B3/S23".parse().unwrap();
+
+        // A glider moves one cell diagonally every four generations
+        let glider = TileSet::from_string(".#.\n..#\n###", '#');
+        let mut hashlife =
+            Hashlife::new(conway.rule.clone(), conway.neighborhood).with_tileset(&glider);
+        hashlife.step_pow2(30);
+        let offset = 1 << 28;
+        let expected: Vec<Point> = glider.iter().map(|p| *p + (offset, offset)).collect();
+        assert_eq!(
+            hashlife.to_tileset(),
+            Some(TileSet::new().with_tiles(&expected))
+        );
+
+        // Three more doublings take it past where a Point can go
+        hashlife.step_pow2(33);
+        assert_eq!(hashlife.to_tileset(), None);
+        let offset = (1i64 << 28) + (1i64 << 31);
+        let mut expected: Vec<(i64, i64)> = glider
+            .iter()
+            .map(|p| (p.x as i64 + offset, p.y as i64 + offset))
+            .collect();
+        let mut cells = hashlife.live_cells();
+        expected.sort_unstable();
+        cells.sort_unstable();
+        assert_eq!(cells, expected);
+
+        // A blinker flips back and forth forever
+        let blinker = TileSet::from_string("###", '#');
+        let mut hashlife = Hashlife::new(conway.rule, conway.neighborhood).with_tileset(&blinker);
+        hashlife.simulate(1_000_000_000_001);
+        assert_eq!(hashlife.generation(), 1_000_000_000_001);
+        let vertical: Vec<Point> = vec![(1, -1), (1, 0), (1, 1)]
+            .into_iter()
+            .map(Point::from)
+            .collect();
+        assert_eq!(
+            hashlife.to_tileset(),
+            Some(TileSet::new().with_tiles(&vertical))
+        );
+    }
+}
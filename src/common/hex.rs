@@ -0,0 +1,88 @@
+use auto_ops::*;
+
+// A hexagon on a grid using cube coordinates, where x + y + z is always 0
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct HexPoint {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl HexPoint {
+    pub const fn new(x: i32, y: i32, z: i32) -> Self {
+        Self { x, y, z }
+    }
+
+    pub const fn origin() -> Self {
+        Self { x: 0, y: 0, z: 0 }
+    }
+
+    // The direction to each of the six neighbors, going clockwise from east (with pointy topped hexagons)
+    pub const DIRECTIONS: [HexPoint; 6] = [
+        HexPoint::new(1, -1, 0),
+        HexPoint::new(0, -1, 1),
+        HexPoint::new(-1, 0, 1),
+        HexPoint::new(-1, 1, 0),
+        HexPoint::new(0, 1, -1),
+        HexPoint::new(1, 0, -1),
+    ];
+
+    pub fn adjacents(&self) -> impl Iterator<Item = Self> + '_ {
+        Self::DIRECTIONS.iter().map(move |d| *self + *d)
+    }
+
+    pub fn distance(a: Self, b: Self) -> u32 {
+        let delta = a - b;
+        delta
+            .x
+            .unsigned_abs()
+            .max(delta.y.unsigned_abs())
+            .max(delta.z.unsigned_abs())
+    }
+}
+
+impl_op_ex!(+ |a: &HexPoint, b: &HexPoint| -> HexPoint {
+    HexPoint {
+        x: a.x + b.x,
+        y: a.y + b.y,
+        z: a.z + b.z,
+    }
+});
+
+impl_op_ex!(+= |a: &mut HexPoint, b: &HexPoint| { *a = *a + b });
+
+impl_op_ex!(-|a: &HexPoint, b: &HexPoint| -> HexPoint {
+    HexPoint {
+        x: a.x - b.x,
+        y: a.y - b.y,
+        z: a.z - b.z,
+    }
+});
+
+impl_op_ex!(-= |a: &mut HexPoint, b: &HexPoint| { *a = *a - b });
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_adjacents() {
+        let p = HexPoint::new(2, -1, -1);
+        assert_eq!(p.adjacents().count(), 6);
+        assert!(p.adjacents().all(|a| a.x + a.y + a.z == 0));
+        assert!(p.adjacents().all(|a| HexPoint::distance(a, p) == 1));
+        assert_eq!(
+            HexPoint::DIRECTIONS
+                .iter()
+                .fold(HexPoint::origin(), |acc, d| acc + d),
+            HexPoint::origin()
+        );
+    }
+
+    #[test]
+    fn test_distance() {
+        let a = HexPoint::new(1, -3, 2);
+        assert_eq!(HexPoint::distance(a, HexPoint::origin()), 3);
+        assert_eq!(HexPoint::distance(a, a), 0);
+    }
+}
@@ -0,0 +1,376 @@
+use crate::common::{
+    unsigned, Automaton, Cube, Dense, DenseHex, Hashlife, Hex, HexPoint, LifeRule, Offsets2d,
+    Point, PointN, Simulation, TileSet,
+};
+use nom::{
+    branch::alt,
+    character::complete::{char, one_of},
+    combinator::{all_consuming, map, opt, value, verify},
+    error::Error,
+    multi::{many0, separated_list1},
+    sequence::{delimited, pair, preceded, separated_pair},
+    Finish, IResult,
+};
+
+// The most dimensions a cube neighborhood can have, since each one needs its own instance of the automaton
+pub const MAX_CUBE_DIMENSIONS: usize = 6;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Neighborhood {
+    Moore,       // The 8 surrounding squares, which is the default
+    VonNeumann,  // The 4 orthogonal squares, with the suffix "V"
+    Hex,         // The 6 surrounding hexagons, with the suffix "H"
+    Cube(usize), // Every touching cube in N dimensions, with a suffix like "/3D"
+    Sight, // The nearest seat in each of the 8 directions, with the suffix "L", for day 11 only
+}
+
+impl Neighborhood {
+    pub fn size(&self) -> usize {
+        match self {
+            Self::Moore | Self::Sight => 8,
+            Self::VonNeumann => 4,
+            Self::Hex => 6,
+            Self::Cube(n) => 3usize.pow(*n as u32) - 1,
+        }
+    }
+
+    // The offset to each neighbor, for any neighborhood which is 2D. Hex cells use (x, y) as their first
+    // two cube coordinates, so each row is skewed from the one above.
+    pub fn offsets_2d(&self) -> Option<Vec<Point>> {
+        match self {
+            Self::Moore | Self::Cube(2) => Some(Point::origin().adjacents().collect()),
+            Self::VonNeumann => Some(Point::origin().orthogonals().collect()),
+            Self::Hex => Some(
+                HexPoint::DIRECTIONS
+                    .iter()
+                    .map(|h| Point { x: h.x, y: h.y })
+                    .collect(),
+            ),
+            Self::Cube(_) | Self::Sight => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Backend {
+    Sparse,   // An Automaton storing just the live cells, which works for any neighborhood
+    Dense, // A bit per cell in a box around the live cells, which is faster for small busy patterns
+    Hashlife, // A memoized quadtree for 2D neighborhoods, which is far faster for huge numbers of steps
+}
+
+impl Backend {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "sparse" => Some(Self::Sparse),
+            "dense" => Some(Self::Dense),
+            "hashlife" => Some(Self::Hashlife),
+            _ => None,
+        }
+    }
+}
+
+// A life-like rule in the usual B/S notation, e.g. "B3/S23" for Conway's Game of Life. Counts are single
+// digits unless they are separated by commas, which is needed for any count above 9 (e.g. "B3/S2,3,10/4D"),
+// with a trailing comma after a count on its own (e.g. "B3/S10,/4D"). Rules with B0 would bring the endless
+// empty space around a pattern to life, so they only run on bounded grids like day 11's waiting area and
+// the simulations here have none for them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LifeLike {
+    pub rule: LifeRule,
+    pub neighborhood: Neighborhood,
+}
+
+impl LifeLike {
+    pub fn parser(input: &str) -> IResult<&str, Self> {
+        let (input, (birth, survive)) = separated_pair(
+            preceded(char('B'), Self::counts),
+            char('/'),
+            preceded(char('S'), Self::counts),
+        )(input)?;
+        let (input, neighborhood) = opt(alt((
+            value(Neighborhood::VonNeumann, char('V')),
+            value(Neighborhood::Hex, char('H')),
+            value(Neighborhood::Sight, char('L')),
+            map(
+                verify(delimited(char('/'), unsigned, char('D')), |&n: &usize| {
+                    (2..=MAX_CUBE_DIMENSIONS).contains(&n)
+                }),
+                Neighborhood::Cube,
+            ),
+        )))(input)?;
+        let neighborhood = neighborhood.unwrap_or(Neighborhood::Moore);
+
+        // No count can be more than the number of neighbors
+        let max = neighborhood.size();
+        if birth.iter().chain(survive.iter()).any(|&count| count > max) {
+            return Err(nom::Err::Error(Error::new(
+                input,
+                nom::error::ErrorKind::Verify,
+            )));
+        }
+
+        Ok((
+            input,
+            Self {
+                rule: LifeRule::new(&birth, &survive),
+                neighborhood,
+            },
+        ))
+    }
+
+    fn counts(input: &str) -> IResult<&str, Vec<usize>> {
+        alt((
+            map(
+                verify(
+                    pair(separated_list1(char(','), unsigned), opt(char(','))),
+                    |(counts, trailing): &(Vec<usize>, Option<char>)| {
+                        counts.len() > 1 || trailing.is_some()
+                    },
+                ),
+                |(counts, _)| counts,
+            ),
+            many0(map(one_of("0123456789"), |c| {
+                c.to_digit(10).unwrap() as usize
+            })),
+        ))(input)
+    }
+
+    pub fn births_from_nothing(&self) -> bool {
+        self.rule.birth.contains(&0)
+    }
+
+    // Runs the rule starting from a 2D pattern and returns the number of live cells at the end. Cube
+    // neighborhoods start with the pattern as a single slice, like day 17. Panics for B0 or line of sight
+    // rules, which need day 11's waiting area.
+    pub fn simulate(&self, seed: &TileSet, steps: usize) -> usize {
+        self.simulate_with(seed, steps, Backend::Sparse).unwrap()
+    }
+
+    // The same as simulate, or None if the backend doesn't support the neighborhood
+    pub fn simulate_with(&self, seed: &TileSet, steps: usize, backend: Backend) -> Option<usize> {
+        match self.neighborhood {
+            Neighborhood::Cube(3) => {
+                Self::run_cube(self.cube_simulation::<3>(backend)?, seed, steps)
+            }
+            Neighborhood::Cube(4) => {
+                Self::run_cube(self.cube_simulation::<4>(backend)?, seed, steps)
+            }
+            Neighborhood::Cube(5) => {
+                Self::run_cube(self.cube_simulation::<5>(backend)?, seed, steps)
+            }
+            Neighborhood::Cube(6) => {
+                Self::run_cube(self.cube_simulation::<6>(backend)?, seed, steps)
+            }
+            _ => self
+                .simulate_2d_with(seed, steps, backend)
+                .map(|tiles| tiles.len()),
+        }
+    }
+
+    // The same as simulate, but returning the final pattern for any neighborhood which is still 2D
+    pub fn simulate_2d(&self, seed: &TileSet, steps: usize) -> Option<TileSet> {
+        self.simulate_2d_with(seed, steps, Backend::Sparse)
+    }
+
+    pub fn simulate_2d_with(
+        &self,
+        seed: &TileSet,
+        steps: usize,
+        backend: Backend,
+    ) -> Option<TileSet> {
+        let mut simulation = self.simulation_2d(backend)?;
+        for p in seed.iter() {
+            simulation.set_live(*p, true);
+        }
+        simulation.advance(steps);
+        Some(TileSet::new().with_tiles(&simulation.live_cells()))
+    }
+
+    fn run_cube<const N: usize>(
+        mut simulation: Box<dyn Simulation<PointN<N>>>,
+        seed: &TileSet,
+        steps: usize,
+    ) -> Option<usize> {
+        for p in seed.iter() {
+            simulation.set_live(PointN::from_2d(*p), true);
+        }
+        simulation.advance(steps);
+        Some(simulation.count_live())
+    }
+
+    // An empty simulation of this rule on a 2D grid, if the neighborhood is 2D
+    pub fn simulation_2d(&self, backend: Backend) -> Option<Box<dyn Simulation<Point>>> {
+        if self.births_from_nothing() == true {
+            return None;
+        }
+        let offsets = self.neighborhood.offsets_2d()?;
+        Some(match backend {
+            Backend::Sparse => Box::new(Automaton::new(Offsets2d { offsets }, self.rule.clone())),
+            Backend::Dense => Box::new(Dense::from_offsets_2d(&self.rule, &offsets)),
+            Backend::Hashlife => Box::new(Hashlife::new(self.rule.clone(), self.neighborhood)),
+        })
+    }
+
+    // An empty simulation of this rule in N dimensions, whatever the neighborhood is. Hashlife is 2D only.
+    pub fn cube_simulation<const N: usize>(
+        &self,
+        backend: Backend,
+    ) -> Option<Box<dyn Simulation<PointN<N>>>> {
+        if self.births_from_nothing() == true {
+            return None;
+        }
+        match backend {
+            Backend::Sparse => Some(Box::new(Automaton::new(Cube::new(), self.rule.clone()))),
+            Backend::Dense => Some(Box::new(Dense::cube(&self.rule))),
+            Backend::Hashlife => None,
+        }
+    }
+
+    // An empty simulation of this rule on a hex grid, whatever the neighborhood is. Hashlife works on
+    // points rather than hexagons, so it's only available through simulation_2d.
+    pub fn hex_simulation(&self, backend: Backend) -> Option<Box<dyn Simulation<HexPoint>>> {
+        if self.births_from_nothing() == true {
+            return None;
+        }
+        match backend {
+            Backend::Sparse => Some(Box::new(Automaton::new(Hex, self.rule.clone()))),
+            Backend::Dense => Some(Box::new(DenseHex::new(&self.rule))),
+            Backend::Hashlife => None,
+        }
+    }
+}
+
+impl std::fmt::Display for LifeLike {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let counts = |counts: &[usize]| -> String {
+            let digits: Vec<String> = counts.iter().map(|c| c.to_string()).collect();
+            match counts {
+                [c] if *c > 9 => format!("{},", c),
+                _ if counts.iter().any(|&c| c > 9) => digits.join(","),
+                _ => digits.concat(),
+            }
+        };
+        write!(
+            f,
+            "B{}/S{}",
+            counts(&self.rule.birth),
+            counts(&self.rule.survive)
+        )?;
+        match self.neighborhood {
+            Neighborhood::Moore => Ok(()),
+            Neighborhood::VonNeumann => write!(f, "V"),
+            Neighborhood::Hex => write!(f, "H"),
+            Neighborhood::Sight => write!(f, "L"),
+            Neighborhood::Cube(n) => write!(f, "/{}D", n),
+        }
+    }
+}
+
+impl std::str::FromStr for LifeLike {
+    type Err = Error<String>;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match all_consuming(Self::parser)(s.trim()).finish() {
+            Ok((_remaining, life_like)) => Ok(life_like),
+            Err(Error { input, code }) => Err(Error {
+                input: input.to_string(),
+                code,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let conway: LifeLike = "B3/S23".parse().unwrap();
+        assert_eq!(conway.rule, LifeRule::new(&[3], &[2, 3]));
+        assert_eq!(conway.neighborhood, Neighborhood::Moore);
+
+        let hex: LifeLike = "B2/S12H".parse().unwrap();
+        assert_eq!(hex.rule, LifeRule::new(&[2], &[1, 2]));
+        assert_eq!(hex.neighborhood, Neighborhood::Hex);
+
+        let cube: LifeLike = "B3/S2,3,10/4D".parse().unwrap();
+        assert_eq!(cube.rule, LifeRule::new(&[3], &[2, 3, 10]));
+        assert_eq!(cube.neighborhood, Neighborhood::Cube(4));
+
+        let single: LifeLike = "B3/S10,/3D".parse().unwrap();
+        assert_eq!(single.rule, LifeRule::new(&[3], &[10]));
+        let digits: LifeLike = "B3/S10/3D".parse().unwrap();
+        assert_eq!(digits.rule, LifeRule::new(&[3], &[1, 0]));
+
+        let empty: LifeLike = "B/SV".parse().unwrap();
+        assert_eq!(empty.rule, LifeRule::new(&[], &[]));
+        assert_eq!(empty.neighborhood, Neighborhood::VonNeumann);
+
+        for rule in &[
+            "B3/S23",
+            "B2/S12H",
+            "B3/S2,3,10/4D",
+            "B12,/S3/3D",
+            "B/SV",
+            "B1/S0123",
+            "B0/S01234L",
+        ] {
+            assert_eq!(rule.parse::<LifeLike>().unwrap().to_string(), *rule);
+        }
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!("S23/B3".parse::<LifeLike>().is_err());
+        assert!("B3/S23X".parse::<LifeLike>().is_err());
+        assert!("B3/S9".parse::<LifeLike>().is_err());
+        assert!("B3/S5V".parse::<LifeLike>().is_err());
+        assert!("B3/S23/1D".parse::<LifeLike>().is_err());
+        assert!("B3/S23/7D".parse::<LifeLike>().is_err());
+        assert!("B3/S9L".parse::<LifeLike>().is_err());
+    }
+
+    #[test]
+    fn test_simulate() {
+        let glider = TileSet::from_string(".#.\n..#\n###", '#');
+        let conway: LifeLike = "B3/S23".parse().unwrap();
+        let moved = conway.simulate_2d(&glider, 4).unwrap();
+        let expected: Vec<Point> = glider.iter().map(|p| *p + (1, 1)).collect();
+        assert_eq!(moved, TileSet::new().with_tiles(&expected));
+        assert_eq!(conway.simulate(&glider, 4), 5);
+
+        // Day 17's example
+        let seed = TileSet::from_string(".#.\n..#\n###", '#');
+        let cube: LifeLike = "B3/S23/3D".parse().unwrap();
+        assert_eq!(cube.simulate(&seed, 6), 112);
+        assert_eq!(cube.simulate_2d(&seed, 6), None);
+
+        // Either backend gives the same result
+        let hex: LifeLike = "B2/S12H".parse().unwrap();
+        assert_eq!(
+            hex.simulate_2d_with(&seed, 20, Backend::Hashlife),
+            hex.simulate_2d_with(&seed, 20, Backend::Sparse)
+        );
+        assert_eq!(cube.simulate_2d_with(&seed, 6, Backend::Hashlife), None);
+        assert_eq!(cube.simulate_with(&seed, 6, Backend::Dense), Some(112));
+        assert_eq!(cube.simulate_with(&seed, 6, Backend::Hashlife), None);
+        for backend in &["sparse", "dense", "hashlife"] {
+            let backend = Backend::from_str(backend).unwrap();
+            assert_eq!(
+                conway.simulate_2d_with(&glider, 4, backend),
+                Some(moved.clone())
+            );
+        }
+        assert_eq!(Backend::from_str("quadtree"), None);
+
+        // B0 needs a bounded grid, and line of sight needs seats
+        let b0: LifeLike = "B0/S23".parse().unwrap();
+        assert_eq!(b0.births_from_nothing(), true);
+        assert_eq!(b0.simulate_2d(&seed, 1), None);
+        assert_eq!(b0.simulate_with(&seed, 1, Backend::Dense), None);
+        let cube_b0: LifeLike = "B0,12/S23/3D".parse().unwrap();
+        assert_eq!(cube_b0.simulate_with(&seed, 1, Backend::Sparse), None);
+        let sight: LifeLike = "B1/S23L".parse().unwrap();
+        assert_eq!(sight.simulate_2d(&seed, 1), None);
+    }
+}
@@ -1,15 +1,42 @@
+mod automaton;
 mod cardinal;
+mod cycle;
+mod dense;
+mod error;
+mod frames;
+mod grid_automaton;
+mod hashlife;
+mod hex;
+mod life_like;
 mod linked_list_circ;
 mod mode;
 mod modulus;
+mod netpbm;
+mod orientation;
 mod parser;
+mod pattern;
 mod point;
+mod rect;
 mod tile;
 
+pub use automaton::*;
 pub use cardinal::*;
+pub use cycle::*;
+pub use dense::*;
+pub use error::*;
+pub use frames::*;
+pub use grid_automaton::*;
+pub use hashlife::*;
+pub use hex::*;
+pub use life_like::*;
+#[allow(unused_imports)]
 pub use linked_list_circ::*;
 pub use mode::*;
 pub use modulus::*;
+pub use netpbm::*;
+pub use orientation::*;
 pub use parser::*;
+pub use pattern::*;
 pub use point::*;
+pub use rect::*;
 pub use tile::*;
@@ -0,0 +1,421 @@
+use crate::common::{Point, Rect, Rgb, TileChar, TileMap, TileSet};
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NetpbmFormat {
+    Pbm,
+    Pgm,
+    Ppm,
+}
+
+impl NetpbmFormat {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "pbm" => Some(Self::Pbm),
+            "pgm" => Some(Self::Pgm),
+            "ppm" => Some(Self::Ppm),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Pbm => "pbm",
+            Self::Pgm => "pgm",
+            Self::Ppm => "ppm",
+        }
+    }
+}
+
+pub fn luma(rgb: Rgb) -> u8 {
+    let [r, g, b] = rgb;
+    ((299 * r as u32 + 587 * g as u32 + 114 * b as u32) / 1000) as u8
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RgbImage {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl RgbImage {
+    pub fn new(width: usize, height: usize, fill: Rgb) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![fill; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Rgb {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, rgb: Rgb) {
+        self.pixels[y * self.width + x] = rgb;
+    }
+
+    // Blows each pixel up into a square of factor x factor pixels
+    pub fn scale(&self, factor: usize) -> Self {
+        assert!(factor > 0, "Can't scale an image to nothing");
+        let mut scaled = Self::new(self.width * factor, self.height * factor, [0; 3]);
+        for y in 0..scaled.height {
+            for x in 0..scaled.width {
+                scaled.set(x, y, self.get(x / factor, y / factor));
+            }
+        }
+        scaled
+    }
+
+    // Binary (raw) encodings. A PBM has one bit per pixel, set for dark pixels since it stores ink on paper.
+    pub fn encode(&self, format: NetpbmFormat) -> Vec<u8> {
+        let mut bytes = match format {
+            NetpbmFormat::Pbm => format!("P4\n{} {}\n", self.width, self.height),
+            NetpbmFormat::Pgm => format!("P5\n{} {}\n255\n", self.width, self.height),
+            NetpbmFormat::Ppm => format!("P6\n{} {}\n255\n", self.width, self.height),
+        }
+        .into_bytes();
+
+        match format {
+            NetpbmFormat::Pbm => {
+                // Each row starts on a new byte, with the leftmost pixel in the highest bit
+                for row in self.pixels.chunks(self.width.max(1)) {
+                    for chunk in row.chunks(8) {
+                        let mut byte = 0;
+                        for (i, &rgb) in chunk.iter().enumerate() {
+                            if luma(rgb) < 128 {
+                                byte |= 0x80 >> i;
+                            }
+                        }
+                        bytes.push(byte);
+                    }
+                }
+            }
+            NetpbmFormat::Pgm => bytes.extend(self.pixels.iter().map(|&rgb| luma(rgb))),
+            NetpbmFormat::Ppm => {
+                for rgb in self.pixels.iter() {
+                    bytes.extend_from_slice(rgb);
+                }
+            }
+        }
+        bytes
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P, format: NetpbmFormat) -> io::Result<()> {
+        fs::write(path, self.encode(format))
+    }
+}
+
+// Draws tiles as images, one square of scale x scale pixels per tile. Each tile is colored by its
+// character, using the palette if it has an entry and TileChar::color otherwise. A TileSet's tiles are black
+// unless given a color, so they're set in a PBM.
+#[derive(Clone, Debug)]
+pub struct NetpbmWriter {
+    format: NetpbmFormat,
+    scale: usize,
+    palette: HashMap<char, Rgb>,
+    background: Rgb,
+    bounds: Option<Rect>,
+}
+
+impl NetpbmWriter {
+    pub fn new(format: NetpbmFormat) -> Self {
+        Self {
+            format,
+            scale: 1,
+            palette: HashMap::new(),
+            background: [255; 3],
+            bounds: None,
+        }
+    }
+
+    pub fn with_scale(self, scale: usize) -> Self {
+        assert!(scale > 0, "Scale must be at least 1");
+        Self { scale, ..self }
+    }
+
+    pub fn with_color(mut self, c: char, rgb: Rgb) -> Self {
+        self.palette.insert(c, rgb);
+        self
+    }
+
+    // The color of any point without a tile
+    pub fn with_background(self, background: Rgb) -> Self {
+        Self { background, ..self }
+    }
+
+    // Only draws the tiles in the given bounds, instead of just enough to fit all of them
+    pub fn with_bounds(self, bounds: Rect) -> Self {
+        Self {
+            bounds: Some(bounds),
+            ..self
+        }
+    }
+
+    pub fn tileset_image(&self, tileset: &TileSet) -> RgbImage {
+        let rgb = self.color(tileset.active_char(), [0; 3]);
+        self.draw(tileset.iter().map(|&p| (p, rgb)))
+    }
+
+    pub fn tilemap_image<T: TileChar>(&self, tilemap: &TileMap<T>) -> RgbImage {
+        self.draw(
+            tilemap
+                .iter()
+                .map(|(&p, t)| (p, self.color(t.to_char(), t.color()))),
+        )
+    }
+
+    pub fn encode_tileset(&self, tileset: &TileSet) -> Vec<u8> {
+        self.tileset_image(tileset).encode(self.format)
+    }
+
+    pub fn encode_tilemap<T: TileChar>(&self, tilemap: &TileMap<T>) -> Vec<u8> {
+        self.tilemap_image(tilemap).encode(self.format)
+    }
+
+    pub fn write_tileset<P: AsRef<Path>>(&self, path: P, tileset: &TileSet) -> io::Result<()> {
+        fs::write(path, self.encode_tileset(tileset))
+    }
+
+    pub fn write_tilemap<P, T>(&self, path: P, tilemap: &TileMap<T>) -> io::Result<()>
+    where
+        P: AsRef<Path>,
+        T: TileChar,
+    {
+        fs::write(path, self.encode_tilemap(tilemap))
+    }
+
+    fn color(&self, c: char, default: Rgb) -> Rgb {
+        self.palette.get(&c).copied().unwrap_or(default)
+    }
+
+    fn draw<I>(&self, tiles: I) -> RgbImage
+    where
+        I: Iterator<Item = (Point, Rgb)>,
+    {
+        let tiles: Vec<(Point, Rgb)> = tiles.collect();
+        let bounds = match self.bounds {
+            Some(bounds) => bounds,
+            None => match Point::get_range(tiles.iter().map(|(p, _)| p)) {
+                Some(bounds) => bounds,
+                None => return RgbImage::new(0, 0, self.background),
+            },
+        };
+
+        let mut image = RgbImage::new(bounds.width(), bounds.height(), self.background);
+        for (p, rgb) in tiles.into_iter().filter(|(p, _)| bounds.contains(p)) {
+            let offset = p - bounds.min;
+            image.set(offset.x as usize, offset.y as usize, rgb);
+        }
+        image.scale(self.scale)
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message.to_string())
+}
+
+// Splits off the next whitespace separated token of a header, skipping any comments
+fn next_token<'a>(bytes: &mut &'a [u8]) -> io::Result<&'a [u8]> {
+    loop {
+        match bytes.first() {
+            Some(b'#') => {
+                let end = bytes
+                    .iter()
+                    .position(|&b| b == b'\n')
+                    .unwrap_or(bytes.len());
+                *bytes = &bytes[end..];
+            }
+            Some(b) if b.is_ascii_whitespace() => *bytes = &bytes[1..],
+            Some(_) => break,
+            None => return Err(invalid_data("unexpected end of PBM header")),
+        }
+    }
+    let end = bytes
+        .iter()
+        .position(|b| b.is_ascii_whitespace() || *b == b'#')
+        .unwrap_or(bytes.len());
+    let (token, rest) = bytes.split_at(end);
+    *bytes = rest;
+    Ok(token)
+}
+
+fn next_number(bytes: &mut &[u8]) -> io::Result<usize> {
+    let token = next_token(bytes)?;
+    std::str::from_utf8(token)
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| invalid_data("invalid number in PBM header"))
+}
+
+// Reads a plain (P1) or raw (P4) PBM, with a tile for every set pixel and the top left pixel at the origin
+pub fn parse_pbm(bytes: &[u8]) -> io::Result<TileSet> {
+    let mut rest = bytes;
+    let magic = next_token(&mut rest)?;
+    let width = next_number(&mut rest)?;
+    let height = next_number(&mut rest)?;
+
+    let mut tiles = Vec::new();
+    match magic {
+        b"P1" => {
+            // Pixels are 0 or 1, optionally separated by whitespace
+            let mut pixels = rest
+                .iter()
+                .filter(|b| b.is_ascii_whitespace() == false)
+                .map(|&b| match b {
+                    b'0' => Ok(false),
+                    b'1' => Ok(true),
+                    _ => Err(invalid_data("invalid pixel in plain PBM")),
+                });
+            for y in 0..height {
+                for x in 0..width {
+                    match pixels.next() {
+                        Some(set) => {
+                            if set? == true {
+                                tiles.push(Point::from((x as i32, y as i32)));
+                            }
+                        }
+                        None => return Err(invalid_data("PBM is missing pixels")),
+                    }
+                }
+            }
+        }
+        b"P4" => {
+            // A single whitespace character separates the header from the pixels
+            let pixels = rest.get(1..).unwrap_or(&[]);
+            let row_bytes = width.div_ceil(8);
+            let size = row_bytes
+                .checked_mul(height)
+                .ok_or_else(|| invalid_data("PBM is too big"))?;
+            if pixels.len() < size {
+                return Err(invalid_data("PBM is missing pixels"));
+            }
+            for y in 0..height {
+                for x in 0..width {
+                    if pixels[y * row_bytes + x / 8] & (0x80 >> (x % 8)) != 0 {
+                        tiles.push(Point::from((x as i32, y as i32)));
+                    }
+                }
+            }
+        }
+        _ => return Err(invalid_data("not a PBM, expected P1 or P4")),
+    }
+    Ok(TileSet::new().with_tiles(&tiles))
+}
+
+pub fn read_pbm<P: AsRef<Path>>(path: P) -> io::Result<TileSet> {
+    parse_pbm(&fs::read(path)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::Transform;
+
+    #[derive(Clone, Copy, PartialEq)]
+    enum Pixel {
+        Off,
+        Dim,
+        On,
+    }
+
+    impl TileChar for Pixel {
+        fn to_char(&self) -> char {
+            match self {
+                Self::Off => '.',
+                Self::Dim => '+',
+                Self::On => '#',
+            }
+        }
+
+        fn from_char(c: char) -> Option<Self> {
+            Some(match c {
+                '.' => Self::Off,
+                '+' => Self::Dim,
+                '#' => Self::On,
+                _ => return None,
+            })
+        }
+
+        fn all_chars() -> Vec<char> {
+            vec!['.', '+', '#']
+        }
+    }
+
+    #[test]
+    fn test_encode() {
+        let tileset = TileSet::from_string("#..\n.#.\n", '#');
+        let writer = NetpbmWriter::new(NetpbmFormat::Pbm);
+        assert_eq!(
+            writer.encode_tileset(&tileset),
+            b"P4\n2 2\n\x80\x40".to_vec()
+        );
+
+        let writer = NetpbmWriter::new(NetpbmFormat::Pgm).with_scale(2);
+        assert_eq!(
+            writer.encode_tileset(&tileset),
+            b"P5\n4 4\n255\n\0\0\xff\xff\0\0\xff\xff\xff\xff\0\0\xff\xff\0\0".to_vec()
+        );
+
+        // TileChar colors are shades of grey, unless the palette says otherwise
+        let tilemap: TileMap<Pixel> = TileMap::from_string(".+#");
+        let writer = NetpbmWriter::new(NetpbmFormat::Ppm);
+        assert_eq!(
+            writer.encode_tilemap(&tilemap),
+            b"P6\n3 1\n255\n\0\0\0\x7f\x7f\x7f\xff\xff\xff".to_vec()
+        );
+        let writer = writer
+            .with_color('#', [255, 0, 0])
+            .with_background([0, 0, 255])
+            .with_bounds(Rect::new((1, 0).into(), (3, 0).into()));
+        assert_eq!(
+            writer.encode_tilemap(&tilemap),
+            b"P6\n3 1\n255\n\x7f\x7f\x7f\xff\0\0\0\0\xff".to_vec()
+        );
+    }
+
+    #[test]
+    fn test_parse_pbm() {
+        let plain = b"P1\n# a comment\n3 2\n1 0 0\n0 1 1\n";
+        let expected = TileSet::from_string("#..\n.##\n", '#');
+        assert_eq!(parse_pbm(plain).unwrap(), expected);
+
+        // Round trip through a raw PBM, with rows wider than a byte
+        let tileset = TileSet::from_string("#........#\n..........\n.#.#.#.#.#\n", '#');
+        let bytes = NetpbmWriter::new(NetpbmFormat::Pbm).encode_tileset(&tileset);
+        assert_eq!(parse_pbm(&bytes).unwrap(), tileset);
+
+        assert!(parse_pbm(b"P2\n1 1\n255\n0").is_err());
+        assert!(parse_pbm(b"P1\n2 2\n1 0 1").is_err());
+        assert!(parse_pbm(b"P4\n16 2\n\xff").is_err());
+        let error = parse_pbm(b"P4\n64 18446744073709551615\n\xff").unwrap_err();
+        assert_eq!(error.to_string(), "PBM is too big");
+    }
+
+    #[test]
+    fn test_write_pbm() {
+        // The file starts at the origin, wherever the tiles are
+        let tileset = TileSet::from_string("#.\n.#\n", '#').translate(Point { x: -3, y: 5 });
+        let path = std::env::temp_dir().join(format!("aoc_2020_netpbm_{}.pbm", std::process::id()));
+        NetpbmWriter::new(NetpbmFormat::Pbm)
+            .write_tileset(&path, &tileset)
+            .unwrap();
+        let read_back = read_pbm(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read_back, TileSet::from_string("#.\n.#\n", '#'));
+    }
+}
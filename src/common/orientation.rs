@@ -0,0 +1,60 @@
+use crate::common::Point;
+
+// One of the eight ways to turn a grid over and around. Points are mirrored left to right first if flipped,
+// then rotated clockwise a quarter turn at a time, both about the origin.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Orientation {
+    pub rotation: u32,
+    pub flipped: bool,
+}
+
+impl Orientation {
+    pub const ROTATIONS: u32 = 4;
+
+    pub fn new(rotation: u32, flipped: bool) -> Self {
+        Self {
+            rotation: rotation % Self::ROTATIONS,
+            flipped,
+        }
+    }
+
+    pub fn all() -> impl Iterator<Item = Self> {
+        [false, true].iter().flat_map(|&flipped| {
+            (0..Self::ROTATIONS).map(move |rotation| Self { rotation, flipped })
+        })
+    }
+
+    pub fn apply(&self, p: Point) -> Point {
+        let mut p = p;
+        if self.flipped == true {
+            p.x = -p.x;
+        }
+        for _ in 0..self.rotation % Self::ROTATIONS {
+            p = Point { x: -p.y, y: p.x };
+        }
+        p
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_apply() {
+        let p = Point { x: 2, y: 1 };
+        assert_eq!(Orientation::new(1, false).apply(p), Point { x: -1, y: 2 });
+        assert_eq!(Orientation::new(2, false).apply(p), Point { x: -2, y: -1 });
+        assert_eq!(Orientation::new(3, false).apply(p), Point { x: 1, y: -2 });
+        assert_eq!(Orientation::new(4, false).apply(p), p);
+        assert_eq!(Orientation::new(0, true).apply(p), Point { x: -2, y: 1 });
+        assert_eq!(Orientation::new(1, true).apply(p), Point { x: -1, y: -2 });
+
+        let all: Vec<Point> = Orientation::all().map(|o| o.apply(p)).collect();
+        assert_eq!(all.len(), 8);
+        assert!(all
+            .iter()
+            .enumerate()
+            .all(|(i, a)| all[..i].contains(a) == false));
+    }
+}
@@ -1,9 +1,10 @@
+use crate::common::ParseError;
 use nom::{
     character::complete::{digit1, multispace0, one_of},
     combinator::{map, map_res, opt, recognize},
-    error::ParseError,
+    error::{Error, ParseError as NomParseError},
     sequence::{delimited, pair, preceded, terminated},
-    IResult, Parser,
+    Err, IResult, Parser,
 };
 use std::str::FromStr;
 
@@ -27,7 +28,7 @@ where
 
 pub fn trim<'a, E, O, P>(parser: P) -> impl FnMut(&'a str) -> IResult<&'a str, O, E>
 where
-    E: ParseError<&'a str>,
+    E: NomParseError<&'a str>,
     P: Parser<&'a str, O, E>,
 {
     delimited(multispace0, parser, multispace0)
@@ -35,7 +36,7 @@ where
 
 pub fn trim_start<'a, E, O, P>(parser: P) -> impl FnMut(&'a str) -> IResult<&'a str, O, E>
 where
-    E: ParseError<&'a str>,
+    E: NomParseError<&'a str>,
     P: Parser<&'a str, O, E>,
 {
     preceded(multispace0, parser)
@@ -43,12 +44,68 @@ where
 
 pub fn trim_end<'a, E, O, P>(parser: P) -> impl FnMut(&'a str) -> IResult<&'a str, O, E>
 where
-    E: ParseError<&'a str>,
+    E: NomParseError<&'a str>,
     P: Parser<&'a str, O, E>,
 {
     terminated(parser, multispace0)
 }
 
+// Runs the parser over the whole input, allowing only trailing whitespace to remain
+pub fn parse_input<'a, O, P>(
+    day: u32,
+    input: &'a str,
+    expected: &str,
+    mut parser: P,
+) -> Result<O, ParseError>
+where
+    P: Parser<&'a str, O, Error<&'a str>>,
+{
+    match parser.parse(input) {
+        Ok((remaining, output)) => {
+            let remaining = remaining.trim_start();
+            if remaining.is_empty() {
+                Ok(output)
+            } else {
+                Err(ParseError::at(day, input, remaining, expected))
+            }
+        }
+        Err(Err::Error(e)) | Err(Err::Failure(e)) => {
+            Err(ParseError::at(day, input, e.input, expected))
+        }
+        Err(Err::Incomplete(_)) => Err(ParseError::at(day, input, &input[input.len()..], expected)),
+    }
+}
+
+// Runs the parser over each line of the input, which must consume the whole line
+pub fn parse_lines<'a, O, P>(
+    day: u32,
+    input: &'a str,
+    expected: &str,
+    mut parser: P,
+) -> Result<Vec<O>, ParseError>
+where
+    P: Parser<&'a str, O, Error<&'a str>>,
+{
+    input
+        .lines()
+        .map(|line| match parser.parse(line) {
+            Ok((remaining, output)) => {
+                if remaining.trim().is_empty() {
+                    Ok(output)
+                } else {
+                    Err(ParseError::at(day, input, remaining, expected))
+                }
+            }
+            Err(Err::Error(e)) | Err(Err::Failure(e)) => {
+                Err(ParseError::at(day, input, e.input, expected))
+            }
+            Err(Err::Incomplete(_)) => {
+                Err(ParseError::at(day, input, &line[line.len()..], expected))
+            }
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -157,6 +214,46 @@ mod test {
         assert_eq!(s, b"abcd".to_vec());
     }
 
+    #[test]
+    fn test_parse_input() {
+        let input = "12 34\n56\n";
+        let result = parse_input(
+            1,
+            input,
+            "a number",
+            nom::multi::many1(trim(unsigned::<u32>)),
+        );
+        assert_eq!(result, Ok(vec![12, 34, 56]));
+
+        let input = "12 34\n5x6\n";
+        let result = parse_input(
+            1,
+            input,
+            "a number",
+            nom::multi::many1(trim(unsigned::<u32>)),
+        );
+        assert_eq!(result, Err(ParseError::new(1, 2, 2, "a number", "x6")));
+
+        let input = "x12";
+        let result = parse_input(1, input, "a number", unsigned::<u32>);
+        assert_eq!(result, Err(ParseError::new(1, 1, 1, "a number", "x12")));
+    }
+
+    #[test]
+    fn test_parse_lines() {
+        let input = "12\n34\n56";
+        let result = parse_lines(9, input, "a number", unsigned::<u32>);
+        assert_eq!(result, Ok(vec![12, 34, 56]));
+
+        let input = "12\n34\n56 78";
+        let result = parse_lines(9, input, "a number", unsigned::<u32>);
+        assert_eq!(result, Err(ParseError::new(9, 3, 3, "a number", " 78")));
+
+        let input = "12\n\n56";
+        let result = parse_lines(9, input, "a number", unsigned::<u32>);
+        assert_eq!(result, Err(ParseError::new(9, 2, 1, "a number", "")));
+    }
+
     #[test]
     fn test_trim() {
         let input = "1234";
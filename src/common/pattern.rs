@@ -0,0 +1,227 @@
+use crate::common::{Orientation, Point, TileSet};
+use std::collections::HashSet;
+
+// Where a pattern was found, with the top left of its range at the position after turning it to the
+// orientation
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct PatternMatch {
+    pub position: Point,
+    pub orientation: Orientation,
+}
+
+// A shape to look for in a TileSet. Its set tiles must all be present wherever it matches, and every other
+// point in its range must be empty unless the don't care mask covers it.
+#[derive(Clone, Debug)]
+pub struct Pattern {
+    set: Vec<Point>,
+    clear: Vec<Point>,
+    overlapping: bool,
+}
+
+impl Pattern {
+    pub fn new(tiles: &TileSet) -> Self {
+        assert!(tiles.is_empty() == false, "Pattern must have some tiles");
+        let range = tiles.get_range().unwrap();
+        Self {
+            set: tiles.iter().copied().collect(),
+            clear: range
+                .points()
+                .filter(|p| tiles.contains(p) == false)
+                .collect(),
+            overlapping: true,
+        }
+    }
+
+    // '#' must be set and '.' must be empty, while anything else matches either
+    pub fn from_string(input: &str) -> Self {
+        let mut set = Vec::new();
+        let mut clear = Vec::new();
+        for (y, line) in input.lines().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                let p = Point::from((x as i32, y as i32));
+                match c {
+                    '#' => set.push(p),
+                    '.' => clear.push(p),
+                    _ => (),
+                }
+            }
+        }
+        assert!(set.is_empty() == false, "Pattern must have some tiles");
+        Self {
+            set,
+            clear,
+            overlapping: true,
+        }
+    }
+
+    pub fn with_dont_care(self, mask: &TileSet) -> Self {
+        Self {
+            clear: self
+                .clear
+                .into_iter()
+                .filter(|p| mask.contains(p) == false)
+                .collect(),
+            ..self
+        }
+    }
+
+    // Whether matches can share set tiles. If not, matches are taken in reading order of their position
+    // and any which overlap an earlier one are dropped.
+    pub fn with_overlapping(self, overlapping: bool) -> Self {
+        Self {
+            overlapping,
+            ..self
+        }
+    }
+
+    // The set tiles covered by a match
+    pub fn tiles_at(&self, found: &PatternMatch) -> Vec<Point> {
+        let (set, _) = self.orient(found.orientation);
+        set.into_iter().map(|p| p + found.position).collect()
+    }
+
+    // Turns both parts of the pattern together, then moves them so the top left of their combined range is
+    // at the origin
+    fn orient(&self, orientation: Orientation) -> (Vec<Point>, Vec<Point>) {
+        let set: Vec<Point> = self.set.iter().map(|&p| orientation.apply(p)).collect();
+        let clear: Vec<Point> = self.clear.iter().map(|&p| orientation.apply(p)).collect();
+        let min = Point::get_range(set.iter().chain(clear.iter()))
+            .unwrap()
+            .min;
+        (
+            set.into_iter().map(|p| p - min).collect(),
+            clear.into_iter().map(|p| p - min).collect(),
+        )
+    }
+
+    // Orientations which give the same shape, such as every rotation of a square, are only searched once
+    fn find_in(&self, tiles: &TileSet) -> Vec<PatternMatch> {
+        let mut seen: Vec<(Vec<Point>, Vec<Point>)> = Vec::new();
+        let mut matches = Vec::new();
+        for orientation in Orientation::all() {
+            let (mut set, mut clear) = self.orient(orientation);
+            set.sort_unstable();
+            clear.sort_unstable();
+            if seen.iter().any(|(s, c)| s == &set && c == &clear) {
+                continue;
+            }
+
+            // The first set tile of the pattern has to line up with a tile, which narrows down where to look
+            for anchor in tiles.iter() {
+                let position = *anchor - set[0];
+                let found = set.iter().all(|&p| tiles.contains(&(p + position)))
+                    && clear
+                        .iter()
+                        .all(|&p| tiles.contains(&(p + position)) == false);
+                if found == true {
+                    matches.push(PatternMatch {
+                        position,
+                        orientation,
+                    });
+                }
+            }
+            seen.push((set, clear));
+        }
+        // Reading order, top to bottom and then left to right, whatever order Point sorts in
+        matches.sort_unstable_by(|a, b| {
+            Point::cmp_y_x(&a.position, &b.position).then(a.orientation.cmp(&b.orientation))
+        });
+
+        if self.overlapping == false {
+            let mut used = HashSet::new();
+            matches.retain(|found| {
+                let covered = self.tiles_at(found);
+                if covered.iter().any(|p| used.contains(p)) {
+                    false
+                } else {
+                    used.extend(covered);
+                    true
+                }
+            });
+        }
+        matches
+    }
+}
+
+impl TileSet {
+    pub fn find_pattern(&self, pattern: &Pattern) -> Vec<PatternMatch> {
+        pattern.find_in(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_find_pattern() {
+        let tiles = TileSet::from_string(
+            "\
+##...#
+#...##
+......
+.#....
+.##...",
+            '#',
+        );
+
+        // An L shape turns up three times, in different orientations. It's symmetric, so the one in the top
+        // right is found rotated rather than flipped.
+        let corner = Pattern::from_string("#\n##");
+        let found = tiles.find_pattern(&corner);
+        assert_eq!(
+            found,
+            vec![
+                PatternMatch {
+                    position: (0, 0).into(),
+                    orientation: Orientation::new(1, false),
+                },
+                PatternMatch {
+                    position: (4, 0).into(),
+                    orientation: Orientation::new(3, false),
+                },
+                PatternMatch {
+                    position: (1, 3).into(),
+                    orientation: Orientation::new(0, false),
+                },
+            ]
+        );
+        let mut covered = corner.tiles_at(&found[1]);
+        covered.sort_unstable();
+        assert_eq!(covered, vec![(5, 0).into(), (4, 1).into(), (5, 1).into()]);
+
+        // Each L is in an otherwise empty box, so requiring its corner to be empty still finds them all
+        let strict = Pattern::from_string("#.\n##");
+        assert_eq!(tiles.find_pattern(&strict).len(), 3);
+        let square = TileSet::from_string("##\n##", '#');
+        assert_eq!(tiles.find_pattern(&Pattern::new(&square)), vec![]);
+    }
+
+    #[test]
+    fn test_overlapping() {
+        // Three in a row fits a line of five in three places, or only once without overlaps
+        let tiles = TileSet::from_string("#####", '#');
+        let line = Pattern::from_string("###");
+        let positions = |pattern: &Pattern| -> Vec<Point> {
+            tiles
+                .find_pattern(pattern)
+                .iter()
+                .map(|m| m.position)
+                .collect()
+        };
+        assert_eq!(
+            positions(&line),
+            vec![(0, 0).into(), (1, 0).into(), (2, 0).into()]
+        );
+        assert_eq!(
+            positions(&line.clone().with_overlapping(false)),
+            vec![(0, 0).into()]
+        );
+
+        // Requiring clear ends stops the line matching inside a longer one, unless they're masked off
+        let ends = TileSet::new().with_tiles(&[(0, 0).into(), (4, 0).into()]);
+        let bounded = Pattern::from_string(".###.");
+        assert_eq!(positions(&bounded), vec![]);
+        assert_eq!(positions(&bounded.with_dont_care(&ends)), positions(&line));
+    }
+}
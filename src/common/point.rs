@@ -1,4 +1,4 @@
-use crate::common::{signed, Cardinal};
+use crate::common::{signed, Cardinal, Rect, RectN};
 use auto_ops::*;
 use nom::{
     character::complete::{char, space0},
@@ -69,42 +69,30 @@ impl Point {
     }
 
     const ORTHOGONALS: [(i32, i32); 4] = [(0, -1), (0, 1), (1, 0), (-1, 0)];
-    pub fn orthogonals(&self) -> impl Iterator<Item = Self> + '_ {
-        Self::ORTHOGONALS.iter().map(move |p| *self + *p)
+    pub fn orthogonals(&self) -> impl Iterator<Item = Self> {
+        let p = *self;
+        Self::ORTHOGONALS.iter().map(move |&d| p + d)
     }
 
     const DIAGONALS: [(i32, i32); 4] = [(-1, -1), (1, -1), (1, 1), (-1, 1)];
-    pub fn diagonals(&self) -> impl Iterator<Item = Self> + '_ {
-        Self::DIAGONALS.iter().map(move |p| *self + *p)
+    pub fn diagonals(&self) -> impl Iterator<Item = Self> {
+        let p = *self;
+        Self::DIAGONALS.iter().map(move |&d| p + d)
     }
 
-    pub fn adjacents(&self) -> impl Iterator<Item = Self> + '_ {
+    pub fn adjacents(&self) -> impl Iterator<Item = Self> {
         self.orthogonals().chain(self.diagonals())
     }
 
-    pub fn get_range<'a, I>(values: I) -> Option<((i32, i32), (i32, i32))>
+    pub fn get_range<'a, I>(values: I) -> Option<Rect>
     where
         I: std::iter::IntoIterator<Item = &'a Point>, // Using IntoIterator instead of Iterator allows the user to pass either an iterator or something that can be turned into one
     {
-        let mut point_iter = values.into_iter(); // Note: if 'values' is an Iterator it will just return itself here
-        if let Some(point) = point_iter.next() {
-            let range = point_iter.fold(
-                ((point.x, point.x), (point.y, point.y)),
-                |(acc_x, acc_y), p| {
-                    (
-                        (acc_x.0.min(p.x), acc_x.1.max(p.x)),
-                        (acc_y.0.min(p.y), acc_y.1.max(p.y)),
-                    )
-                },
-            );
-            Some(range)
-        } else {
-            None
-        }
+        Rect::from_points(values)
     }
 
-    pub fn in_range(&self, range: ((i32, i32), (i32, i32))) -> bool {
-        range.0 .0 <= self.x && self.x <= range.0 .1 && range.1 .0 <= self.y && self.y <= range.1 .1
+    pub fn in_range(&self, range: Rect) -> bool {
+        range.contains(self)
     }
 }
 
@@ -182,6 +170,195 @@ impl Ord for Point {
     }
 }
 
+impl From<PointN<2>> for Point {
+    fn from(point: PointN<2>) -> Self {
+        Self {
+            x: point[0],
+            y: point[1],
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PointN<const N: usize> {
+    pub coords: [i32; N],
+}
+
+impl<const N: usize> PointN<N> {
+    pub const fn new(coords: [i32; N]) -> Self {
+        Self { coords }
+    }
+
+    pub const fn origin() -> Self {
+        Self { coords: [0; N] }
+    }
+
+    // Places a 2D point in the first two dimensions, with all others at 0
+    pub fn from_2d(point: Point) -> Self {
+        assert!(N >= 2, "Can't fit a 2D point into {} dimensions", N);
+        let mut coords = [0; N];
+        coords[0] = point.x;
+        coords[1] = point.y;
+        Self { coords }
+    }
+
+    pub fn manhattan(a: Self, b: Self) -> u32 {
+        let delta = a - b;
+        delta.coords.iter().map(|c| c.unsigned_abs()).sum()
+    }
+
+    // Every combination of -1, 0 and 1 in each dimension except all 0, i.e. 3^N - 1 offsets. They are
+    // generated by counting in base 3.
+    pub fn neighbor_offsets() -> impl Iterator<Item = Self> {
+        let count = 3usize.pow(N as u32);
+        (0..count).filter(move |&i| i != count / 2).map(|mut i| {
+            let mut coords = [0; N];
+            for c in coords.iter_mut().rev() {
+                *c = (i % 3) as i32 - 1;
+                i /= 3;
+            }
+            Self { coords }
+        })
+    }
+
+    pub fn neighbors(&self) -> impl Iterator<Item = Self> + '_ {
+        Self::neighbor_offsets().map(move |offset| *self + offset)
+    }
+
+    pub fn orthogonals(&self) -> impl Iterator<Item = Self> + '_ {
+        (0..N).flat_map(move |dim| {
+            [-1, 1].iter().map(move |delta| {
+                let mut point = *self;
+                point.coords[dim] += delta;
+                point
+            })
+        })
+    }
+
+    pub fn get_range<'a, I>(values: I) -> Option<RectN<N>>
+    where
+        I: std::iter::IntoIterator<Item = &'a Self>,
+    {
+        RectN::from_points(values)
+    }
+
+    pub fn in_range(&self, range: RectN<N>) -> bool {
+        range.contains(self)
+    }
+}
+
+// Hashing the array would also hash its length, which is already known from the type. Skipping it makes a
+// noticeable difference to the HashMap heavy simulations.
+impl<const N: usize> std::hash::Hash for PointN<N> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        for c in self.coords.iter() {
+            c.hash(state);
+        }
+    }
+}
+
+impl<const N: usize> Default for PointN<N> {
+    fn default() -> Self {
+        Self::origin()
+    }
+}
+
+impl<const N: usize> From<[i32; N]> for PointN<N> {
+    fn from(coords: [i32; N]) -> Self {
+        Self { coords }
+    }
+}
+
+impl From<Point> for PointN<2> {
+    fn from(point: Point) -> Self {
+        Self::new([point.x, point.y])
+    }
+}
+
+impl<const N: usize> std::ops::Index<usize> for PointN<N> {
+    type Output = i32;
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.coords[index]
+    }
+}
+
+impl<const N: usize> std::ops::IndexMut<usize> for PointN<N> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.coords[index]
+    }
+}
+
+// auto_ops can't declare generic parameters, so this generates the same set of owned and borrowed
+// operator impls that impl_op_ex! would, for any N
+macro_rules! impl_point_n_op {
+    ($op_trait:ident, $op_fn:ident, $assign_trait:ident, $assign_fn:ident, $op:tt) => {
+        impl<const N: usize> std::ops::$op_trait<&PointN<N>> for &PointN<N> {
+            type Output = PointN<N>;
+            fn $op_fn(self, other: &PointN<N>) -> PointN<N> {
+                PointN {
+                    coords: std::array::from_fn(|i| self.coords[i] $op other.coords[i]),
+                }
+            }
+        }
+
+        impl<const N: usize> std::ops::$op_trait<PointN<N>> for &PointN<N> {
+            type Output = PointN<N>;
+            fn $op_fn(self, other: PointN<N>) -> PointN<N> {
+                self $op &other
+            }
+        }
+
+        impl<const N: usize> std::ops::$op_trait<&PointN<N>> for PointN<N> {
+            type Output = PointN<N>;
+            fn $op_fn(self, other: &PointN<N>) -> PointN<N> {
+                &self $op other
+            }
+        }
+
+        impl<const N: usize> std::ops::$op_trait<PointN<N>> for PointN<N> {
+            type Output = PointN<N>;
+            fn $op_fn(self, other: PointN<N>) -> PointN<N> {
+                &self $op &other
+            }
+        }
+
+        impl<const N: usize> std::ops::$assign_trait<&PointN<N>> for PointN<N> {
+            fn $assign_fn(&mut self, other: &PointN<N>) {
+                *self = &*self $op other;
+            }
+        }
+
+        impl<const N: usize> std::ops::$assign_trait<PointN<N>> for PointN<N> {
+            fn $assign_fn(&mut self, other: PointN<N>) {
+                *self = &*self $op &other;
+            }
+        }
+    };
+}
+
+impl_point_n_op!(Add, add, AddAssign, add_assign, +);
+impl_point_n_op!(Sub, sub, SubAssign, sub_assign, -);
+
+impl<const N: usize> std::ops::Neg for PointN<N> {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self::origin() - self
+    }
+}
+
+impl<const N: usize> std::fmt::Display for PointN<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "(")?;
+        for (i, c) in self.coords.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", c)?;
+        }
+        write!(f, ")")
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -356,7 +533,7 @@ mod test {
 
         let points = vec![Point { x: 0, y: 0 }];
         let range = Point::get_range(&points);
-        assert_eq!(range, Some(((0, 0), (0, 0))));
+        assert_eq!(range, Some(Rect::from_ranges((0, 0), (0, 0))));
 
         let points = vec![
             Point { x: -5, y: 0 },
@@ -364,7 +541,7 @@ mod test {
             Point { x: 4, y: 4 },
         ];
         let range = Point::get_range(&points);
-        assert_eq!(range, Some(((-5, 4), (0, 7))));
+        assert_eq!(range, Some(Rect::from_ranges((-5, 4), (0, 7))));
 
         let points = vec![
             Point { x: 24, y: -86 },
@@ -378,7 +555,7 @@ mod test {
             Point { x: 2, y: 22 },
         ];
         let range = Point::get_range(&points);
-        assert_eq!(range, Some(((-97, 73), (-86, 69))));
+        assert_eq!(range, Some(Rect::from_ranges((-97, 73), (-86, 69))));
 
         let points = vec![
             Point {
@@ -419,6 +596,86 @@ mod test {
             },
         ];
         let range = Point::get_range(&points);
-        assert_eq!(range, Some(((132960, 979388), (180916, 742648))));
+        assert_eq!(
+            range,
+            Some(Rect::from_ranges((132960, 979388), (180916, 742648)))
+        );
+    }
+
+    #[test]
+    fn test_point_n_ops() {
+        let a = PointN::new([1, 2, 3]);
+        let b = PointN::new([-1, 5, 0]);
+        assert_eq!(a + b, PointN::new([0, 7, 3]));
+        assert_eq!(a - b, PointN::new([2, -3, 3]));
+        assert_eq!(-a, PointN::new([-1, -2, -3]));
+
+        let mut c = a;
+        c += &b;
+        c -= PointN::new([0, 0, 3]);
+        assert_eq!(c, PointN::new([0, 7, 0]));
+        assert_eq!(c[1], 7);
+        assert_eq!(c.to_string(), "(0, 7, 0)");
+    }
+
+    #[test]
+    fn test_point_n_manhattan() {
+        let a = PointN::new([-1, -2, 3, 0]);
+        let b = PointN::new([3, 4, 3, -2]);
+        assert_eq!(PointN::manhattan(a, b), 12);
+        assert_eq!(PointN::manhattan(a, a), 0);
+    }
+
+    #[test]
+    fn test_point_n_neighbors() {
+        assert_eq!(PointN::<1>::neighbor_offsets().count(), 2);
+        assert_eq!(PointN::<2>::neighbor_offsets().count(), 8);
+        assert_eq!(PointN::<3>::neighbor_offsets().count(), 26);
+        assert_eq!(PointN::<4>::neighbor_offsets().count(), 80);
+        assert_eq!(PointN::<6>::neighbor_offsets().count(), 728);
+
+        let origin = PointN::<4>::origin();
+        assert!(PointN::<4>::neighbor_offsets().all(|p| p != origin));
+        assert!(PointN::<4>::neighbor_offsets().all(|p| p.coords.iter().all(|c| c.abs() <= 1)));
+
+        // The N=2 case matches Point's adjacents
+        let p = Point { x: 3, y: -4 };
+        let mut expected: Vec<Point> = p.adjacents().collect();
+        let mut actual: Vec<Point> = PointN::from(p).neighbors().map(Point::from).collect();
+        expected.sort();
+        actual.sort();
+        assert_eq!(actual, expected);
+
+        let mut expected: Vec<Point> = p.orthogonals().collect();
+        let mut actual: Vec<Point> = PointN::from(p).orthogonals().map(Point::from).collect();
+        expected.sort();
+        actual.sort();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_point_n_get_range() {
+        let points: Vec<PointN<3>> = Vec::new();
+        assert_eq!(PointN::get_range(&points), None);
+
+        let points = vec![
+            PointN::new([-5, 0, 2]),
+            PointN::new([0, 7, -1]),
+            PointN::new([4, 4, 0]),
+        ];
+        let range = PointN::get_range(&points).unwrap();
+        assert_eq!(
+            range,
+            RectN::new(PointN::new([-5, 0, -1]), PointN::new([4, 7, 2]))
+        );
+        assert!(points.iter().all(|p| p.in_range(range)));
+        assert!(PointN::new([5, 0, 0]).in_range(range) == false);
+    }
+
+    #[test]
+    fn test_point_n_from_2d() {
+        let p = Point { x: 3, y: -4 };
+        assert_eq!(PointN::<4>::from_2d(p), PointN::new([3, -4, 0, 0]));
+        assert_eq!(Point::from(PointN::from(p)), p);
     }
 }
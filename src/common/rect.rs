@@ -0,0 +1,296 @@
+use crate::common::{Point, PointN};
+
+// An axis aligned rectangle, including both its min and max corners. It always covers at least one point.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Rect {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Rect {
+    pub fn new(min: Point, max: Point) -> Self {
+        assert!(min.x <= max.x && min.y <= max.y, "Invalid rect");
+        Self { min, max }
+    }
+
+    pub fn from_ranges(x_range: (i32, i32), y_range: (i32, i32)) -> Self {
+        Self::new(
+            Point {
+                x: x_range.0,
+                y: y_range.0,
+            },
+            Point {
+                x: x_range.1,
+                y: y_range.1,
+            },
+        )
+    }
+
+    // The smallest rect which contains every point, if there are any
+    pub fn from_points<'a, I>(values: I) -> Option<Self>
+    where
+        I: std::iter::IntoIterator<Item = &'a Point>,
+    {
+        let mut point_iter = values.into_iter();
+        let first = *point_iter.next()?;
+        Some(point_iter.fold(Self::new(first, first), |acc, p| {
+            acc.union(&Self::new(*p, *p))
+        }))
+    }
+
+    pub fn width(&self) -> usize {
+        (self.max.x - self.min.x + 1) as usize
+    }
+
+    pub fn height(&self) -> usize {
+        (self.max.y - self.min.y + 1) as usize
+    }
+
+    pub fn area(&self) -> usize {
+        self.width() * self.height()
+    }
+
+    pub fn x_range(&self) -> std::ops::RangeInclusive<i32> {
+        self.min.x..=self.max.x
+    }
+
+    pub fn y_range(&self) -> std::ops::RangeInclusive<i32> {
+        self.min.y..=self.max.y
+    }
+
+    pub fn contains(&self, p: &Point) -> bool {
+        self.min.x <= p.x && p.x <= self.max.x && self.min.y <= p.y && p.y <= self.max.y
+    }
+
+    pub fn contains_rect(&self, other: &Self) -> bool {
+        self.contains(&other.min) && self.contains(&other.max)
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        Self {
+            min: Point {
+                x: self.min.x.min(other.min.x),
+                y: self.min.y.min(other.min.y),
+            },
+            max: Point {
+                x: self.max.x.max(other.max.x),
+                y: self.max.y.max(other.max.y),
+            },
+        }
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let min = Point {
+            x: self.min.x.max(other.min.x),
+            y: self.min.y.max(other.min.y),
+        };
+        let max = Point {
+            x: self.max.x.min(other.max.x),
+            y: self.max.y.min(other.max.y),
+        };
+        if min.x <= max.x && min.y <= max.y {
+            Some(Self { min, max })
+        } else {
+            None
+        }
+    }
+
+    // Moves every side outwards by the given amount
+    pub fn grow(&self, amount: i32) -> Self {
+        Self::new(self.min - (amount, amount), self.max + (amount, amount))
+    }
+
+    // Moves every side inwards by the given amount, unless that would leave nothing
+    pub fn shrink(&self, amount: i32) -> Option<Self> {
+        let min = self.min + (amount, amount);
+        let max = self.max - (amount, amount);
+        if min.x <= max.x && min.y <= max.y {
+            Some(Self { min, max })
+        } else {
+            None
+        }
+    }
+
+    // Every point in the rect, row by row
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let x_range = self.x_range();
+        self.y_range()
+            .flat_map(move |y| x_range.clone().map(move |x| Point { x, y }))
+    }
+}
+
+impl std::fmt::Display for Rect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} to {}", self.min, self.max)
+    }
+}
+
+// The same as Rect for any number of dimensions
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct RectN<const N: usize> {
+    pub min: PointN<N>,
+    pub max: PointN<N>,
+}
+
+impl<const N: usize> RectN<N> {
+    pub fn new(min: PointN<N>, max: PointN<N>) -> Self {
+        assert!((0..N).all(|d| min[d] <= max[d]), "Invalid rect");
+        Self { min, max }
+    }
+
+    pub fn from_points<'a, I>(values: I) -> Option<Self>
+    where
+        I: std::iter::IntoIterator<Item = &'a PointN<N>>,
+    {
+        let mut point_iter = values.into_iter();
+        let first = *point_iter.next()?;
+        Some(point_iter.fold(Self::new(first, first), |acc, p| {
+            acc.union(&Self::new(*p, *p))
+        }))
+    }
+
+    pub fn len(&self, dim: usize) -> usize {
+        (self.max[dim] - self.min[dim] + 1) as usize
+    }
+
+    pub fn volume(&self) -> usize {
+        (0..N).map(|d| self.len(d)).product()
+    }
+
+    pub fn range(&self, dim: usize) -> std::ops::RangeInclusive<i32> {
+        self.min[dim]..=self.max[dim]
+    }
+
+    pub fn contains(&self, p: &PointN<N>) -> bool {
+        (0..N).all(|d| self.min[d] <= p[d] && p[d] <= self.max[d])
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        Self {
+            min: PointN::new(std::array::from_fn(|d| self.min[d].min(other.min[d]))),
+            max: PointN::new(std::array::from_fn(|d| self.max[d].max(other.max[d]))),
+        }
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let min = PointN::new(std::array::from_fn(|d| self.min[d].max(other.min[d])));
+        let max = PointN::new(std::array::from_fn(|d| self.max[d].min(other.max[d])));
+        if (0..N).all(|d| min[d] <= max[d]) {
+            Some(Self { min, max })
+        } else {
+            None
+        }
+    }
+
+    pub fn grow(&self, amount: i32) -> Self {
+        let delta = PointN::new([amount; N]);
+        Self::new(self.min - delta, self.max + delta)
+    }
+
+    pub fn shrink(&self, amount: i32) -> Option<Self> {
+        let delta = PointN::new([amount; N]);
+        let min = self.min + delta;
+        let max = self.max - delta;
+        if (0..N).all(|d| min[d] <= max[d]) {
+            Some(Self { min, max })
+        } else {
+            None
+        }
+    }
+
+    // Every point in the rect, with the first dimension changing fastest
+    pub fn points(&self) -> impl Iterator<Item = PointN<N>> {
+        let rect = *self;
+        (0..self.volume()).map(move |mut i| {
+            let mut p = rect.min;
+            for d in 0..N {
+                let len = rect.len(d);
+                p[d] += (i % len) as i32;
+                i /= len;
+            }
+            p
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rect_size() {
+        let rect = Rect::from_ranges((-1, 2), (3, 3));
+        assert_eq!(rect.width(), 4);
+        assert_eq!(rect.height(), 1);
+        assert_eq!(rect.area(), 4);
+        assert_eq!(rect.to_string(), "(-1, 3) to (2, 3)");
+    }
+
+    #[test]
+    fn test_rect_union_intersection() {
+        let a = Rect::from_ranges((0, 4), (0, 4));
+        let b = Rect::from_ranges((3, 6), (-2, 1));
+        assert_eq!(a.union(&b), Rect::from_ranges((0, 6), (-2, 4)));
+        assert_eq!(a.intersection(&b), Some(Rect::from_ranges((3, 4), (0, 1))));
+
+        let c = Rect::from_ranges((5, 6), (0, 4));
+        assert_eq!(a.intersection(&c), None);
+        assert_eq!(a.union(&c).contains_rect(&a), true);
+        assert_eq!(a.contains_rect(&b), false);
+    }
+
+    #[test]
+    fn test_rect_grow_shrink() {
+        let rect = Rect::from_ranges((0, 4), (0, 2));
+        assert_eq!(rect.grow(1), Rect::from_ranges((-1, 5), (-1, 3)));
+        assert_eq!(rect.shrink(1), Some(Rect::from_ranges((1, 3), (1, 1))));
+        assert_eq!(rect.shrink(2), None);
+        assert_eq!(rect.grow(3).shrink(3), Some(rect));
+    }
+
+    #[test]
+    fn test_rect_points() {
+        let rect = Rect::from_ranges((1, 2), (5, 6));
+        let points: Vec<Point> = rect.points().collect();
+        assert_eq!(
+            points,
+            vec![
+                Point { x: 1, y: 5 },
+                Point { x: 2, y: 5 },
+                Point { x: 1, y: 6 },
+                Point { x: 2, y: 6 },
+            ]
+        );
+        assert!(points.iter().all(|p| rect.contains(p)));
+        assert_eq!(rect.contains(&Point { x: 3, y: 5 }), false);
+        assert_eq!(Rect::from_points(&points), Some(rect));
+        assert_eq!(Rect::from_points(&[]), None);
+    }
+
+    #[test]
+    fn test_rect_n() {
+        let a = RectN::new(PointN::new([0, 0, 0]), PointN::new([2, 1, 0]));
+        assert_eq!(a.volume(), 6);
+        assert_eq!(a.points().count(), 6);
+        assert!(a.points().all(|p| a.contains(&p)));
+        assert_eq!(a.points().nth(1), Some(PointN::new([1, 0, 0])));
+
+        let b = RectN::new(PointN::new([1, -1, -1]), PointN::new([5, 0, 1]));
+        assert_eq!(
+            a.intersection(&b),
+            Some(RectN::new(PointN::new([1, 0, 0]), PointN::new([2, 0, 0])))
+        );
+        assert_eq!(
+            a.union(&b),
+            RectN::new(PointN::new([0, -1, -1]), PointN::new([5, 1, 1]))
+        );
+        assert_eq!(a.grow(1).volume(), 5 * 4 * 3);
+        assert_eq!(a.shrink(1), None);
+
+        let points = vec![PointN::new([3, -1]), PointN::new([0, 4])];
+        assert_eq!(
+            RectN::from_points(&points),
+            Some(RectN::new(PointN::new([0, -1]), PointN::new([3, 4])))
+        );
+    }
+}
@@ -1,7 +1,63 @@
-use crate::common::Point;
-use nom::IResult;
+use crate::common::{modulo, Orientation, Point, Rect};
+use nom::{
+    error::{Error, ErrorKind},
+    Err, IResult,
+};
 use std::collections::{HashMap, HashSet};
 
+// Rotations, flips and moves of a set of tiles, built on moving each point. Rotations, flips and
+// transposes all happen about the origin, so use normalize afterwards to bring the tiles back to it
+pub trait Transform: Clone {
+    fn bounds(&self) -> Option<Rect>;
+
+    fn map_points<F: Fn(Point) -> Point>(&self, f: F) -> Self;
+
+    fn rotate90(&self) -> Self {
+        self.map_points(|p| Point { x: -p.y, y: p.x })
+    }
+
+    fn rotate180(&self) -> Self {
+        self.map_points(|p| Point { x: -p.x, y: -p.y })
+    }
+
+    fn rotate270(&self) -> Self {
+        self.map_points(|p| Point { x: p.y, y: -p.x })
+    }
+
+    fn flip_horizontal(&self) -> Self {
+        self.map_points(|p| Point { x: -p.x, y: p.y })
+    }
+
+    fn flip_vertical(&self) -> Self {
+        self.map_points(|p| Point { x: p.x, y: -p.y })
+    }
+
+    fn transpose(&self) -> Self {
+        self.map_points(|p| Point { x: p.y, y: p.x })
+    }
+
+    fn orient(&self, orientation: Orientation) -> Self {
+        self.map_points(|p| orientation.apply(p))
+    }
+
+    // The tiles in all eight orientations, each normalized
+    fn orientations(&self) -> impl Iterator<Item = (Orientation, Self)> + '_ {
+        Orientation::all().map(move |o| (o, self.orient(o).normalize()))
+    }
+
+    fn translate(&self, offset: Point) -> Self {
+        self.map_points(|p| p + offset)
+    }
+
+    // Moves the tiles so the top left of their range is at the origin
+    fn normalize(&self) -> Self {
+        match self.bounds() {
+            Some(range) => self.translate(Point::origin() - range.min),
+            None => self.clone(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct TileSet {
     tiles: HashSet<Point>,
@@ -59,16 +115,45 @@ impl TileSet {
         }
     }
 
-    pub fn get_range(&self) -> Option<((i32, i32), (i32, i32))> {
+    pub fn active_char(&self) -> char {
+        self.active_char
+    }
+
+    pub fn get_range(&self) -> Option<Rect> {
         Point::get_range(&self.tiles)
     }
+
+    pub fn crop(&self, bounds: Rect) -> Self {
+        Self {
+            tiles: self
+                .tiles
+                .iter()
+                .copied()
+                .filter(|p| bounds.contains(p))
+                .collect(),
+            active_char: self.active_char,
+        }
+    }
+}
+
+impl Transform for TileSet {
+    fn bounds(&self) -> Option<Rect> {
+        self.get_range()
+    }
+
+    fn map_points<F: Fn(Point) -> Point>(&self, f: F) -> Self {
+        Self {
+            tiles: self.tiles.iter().map(|&p| f(p)).collect(),
+            active_char: self.active_char,
+        }
+    }
 }
 
 impl std::fmt::Display for TileSet {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let (x_range, y_range) = self.get_range().unwrap();
-        for y in y_range.0..=y_range.1 {
-            for x in x_range.0..=x_range.1 {
+        let range = self.get_range().unwrap();
+        for y in range.y_range() {
+            for x in range.x_range() {
                 if self.tiles.contains(&Point { x, y }) {
                     write!(f, "{}", self.active_char)?;
                 } else {
@@ -94,6 +179,8 @@ impl std::ops::DerefMut for TileSet {
     }
 }
 
+pub type Rgb = [u8; 3];
+
 pub trait TileChar
 where
     Self: Sized,
@@ -101,6 +188,18 @@ where
     fn to_char(&self) -> char;
     fn from_char(c: char) -> Option<Self>;
     fn all_chars() -> Vec<char>;
+
+    // By default tiles are shades of grey, from black for the first of all_chars to white for the last
+    fn color(&self) -> Rgb {
+        let chars = Self::all_chars();
+        let idx = chars.iter().position(|&c| c == self.to_char()).unwrap_or(0);
+        let shade = if chars.len() > 1 {
+            (idx * 255 / (chars.len() - 1)) as u8
+        } else {
+            255
+        };
+        [shade; 3]
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -147,16 +246,41 @@ impl<T: TileChar> TileMap<T> {
         Ok((input, Self { tiles }))
     }
 
-    pub fn get_range(&self) -> Option<((i32, i32), (i32, i32))> {
+    pub fn get_range(&self) -> Option<Rect> {
         Point::get_range(self.tiles.keys())
     }
 }
 
+impl<T: TileChar + Clone> TileMap<T> {
+    pub fn crop(&self, bounds: Rect) -> Self {
+        Self {
+            tiles: self
+                .tiles
+                .iter()
+                .filter(|(p, _)| bounds.contains(p))
+                .map(|(&p, t)| (p, t.clone()))
+                .collect(),
+        }
+    }
+}
+
+impl<T: TileChar + Clone> Transform for TileMap<T> {
+    fn bounds(&self) -> Option<Rect> {
+        self.get_range()
+    }
+
+    fn map_points<F: Fn(Point) -> Point>(&self, f: F) -> Self {
+        Self {
+            tiles: self.tiles.iter().map(|(&p, t)| (f(p), t.clone())).collect(),
+        }
+    }
+}
+
 impl<T: TileChar> std::fmt::Display for TileMap<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let (x_range, y_range) = self.get_range().unwrap();
-        for y in y_range.0..=y_range.1 {
-            for x in x_range.0..=x_range.1 {
+        let range = self.get_range().unwrap();
+        for y in range.y_range() {
+            for x in range.x_range() {
                 if let Some(t) = self.tiles.get(&Point { x, y }) {
                     write!(f, "{}", t.to_char())?;
                 } else {
@@ -182,6 +306,252 @@ impl<T: TileChar> std::ops::DerefMut for TileMap<T> {
     }
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub struct Grid<T> {
+    tiles: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            tiles: vec![fill; width * height],
+            width,
+            height,
+        }
+    }
+
+    pub fn from_vec(width: usize, height: usize, tiles: Vec<T>) -> Self {
+        assert_eq!(tiles.len(), width * height, "Grid size doesn't match");
+        Self {
+            tiles,
+            width,
+            height,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn in_bounds(&self, p: &Point) -> bool {
+        p.x >= 0 && p.y >= 0 && (p.x as usize) < self.width && (p.y as usize) < self.height
+    }
+
+    pub fn point_to_idx(&self, p: &Point) -> Option<usize> {
+        if self.in_bounds(p) {
+            Some(p.y as usize * self.width + p.x as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn idx_to_point(&self, idx: usize) -> Point {
+        Point {
+            x: (idx % self.width) as i32,
+            y: (idx / self.width) as i32,
+        }
+    }
+
+    pub fn get(&self, p: &Point) -> Option<&T> {
+        self.point_to_idx(p).map(|idx| &self.tiles[idx])
+    }
+
+    pub fn get_mut(&mut self, p: &Point) -> Option<&mut T> {
+        self.point_to_idx(p).map(move |idx| &mut self.tiles[idx])
+    }
+
+    // Treats the grid as repeating infinitely in every direction. An empty grid has nothing to repeat
+    pub fn get_wrapping(&self, p: &Point) -> Option<&T> {
+        self.wrap(p).map(|p| &self[p])
+    }
+
+    pub fn wrap(&self, p: &Point) -> Option<Point> {
+        if self.tiles.is_empty() {
+            return None;
+        }
+        Some(Point {
+            x: modulo(p.x, self.width as i32),
+            y: modulo(p.y, self.height as i32),
+        })
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = Point> + '_ {
+        (0..self.tiles.len()).map(move |idx| self.idx_to_point(idx))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.points().zip(self.tiles.iter())
+    }
+
+    pub fn tiles(&self) -> &[T] {
+        &self.tiles
+    }
+
+    pub fn tiles_mut(&mut self) -> &mut [T] {
+        &mut self.tiles
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.tiles[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(move |y| self.row(y))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(x < self.width, "Column out of bounds");
+        self.tiles.iter().skip(x).step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |x| self.column(x))
+    }
+
+    pub fn orthogonals(&self, p: &Point) -> impl Iterator<Item = Point> + '_ {
+        p.orthogonals().filter(move |n| self.in_bounds(n))
+    }
+
+    pub fn adjacents(&self, p: &Point) -> impl Iterator<Item = Point> + '_ {
+        p.adjacents().filter(move |n| self.in_bounds(n))
+    }
+
+    pub fn orthogonals_wrapping(&self, p: &Point) -> impl Iterator<Item = Point> + '_ {
+        p.orthogonals().filter_map(move |n| self.wrap(&n))
+    }
+
+    pub fn adjacents_wrapping(&self, p: &Point) -> impl Iterator<Item = Point> + '_ {
+        p.adjacents().filter_map(move |n| self.wrap(&n))
+    }
+
+    // The grid covers the tilemap's range, with its top left corner moved to the origin
+    pub fn from_tilemap(tilemap: &TileMap<T>, fill: T) -> Self
+    where
+        T: TileChar + Clone,
+    {
+        let range = match tilemap.get_range() {
+            Some(range) => range,
+            None => return Self::from_vec(0, 0, Vec::new()),
+        };
+        let mut grid = Self::new(range.width(), range.height(), fill);
+        for (p, t) in tilemap.iter() {
+            grid[*p - range.min] = t.clone();
+        }
+        grid
+    }
+
+    pub fn to_tilemap(&self) -> TileMap<T>
+    where
+        T: TileChar + Clone,
+    {
+        TileMap {
+            tiles: self.iter().map(|(p, t)| (p, t.clone())).collect(),
+        }
+    }
+}
+
+impl<T: TileChar> Grid<T> {
+    pub fn from_string(input: &str) -> Self {
+        Self::parser(input).unwrap().1
+    }
+
+    // Every character must be a tile and every row must be the same length. Like TileMap, parsing stops
+    // at a blank line.
+    pub fn parser(input: &str) -> IResult<&str, Self> {
+        let (pixels, input) = if let Some(idx) = input.find("\n\n") {
+            input.split_at(idx)
+        } else {
+            (input, "")
+        };
+
+        let mut tiles = Vec::new();
+        let mut width = None;
+        let mut height = 0;
+        for line in pixels
+            .lines()
+            .map(str::trim_end)
+            .filter(|l| l.is_empty() == false)
+        {
+            let mut row_len = 0;
+            for (i, c) in line.char_indices() {
+                let t = T::from_char(c)
+                    .ok_or_else(|| Err::Error(Error::new(&line[i..], ErrorKind::Char)))?;
+                tiles.push(t);
+                row_len += 1;
+            }
+
+            match width {
+                Some(w) if w != row_len => {
+                    let location = line.char_indices().nth(w).map_or(line.len(), |(i, _)| i);
+                    return Err(Err::Error(Error::new(&line[location..], ErrorKind::Verify)));
+                }
+                Some(_) => (),
+                None => width = Some(row_len),
+            }
+            height += 1;
+        }
+
+        let width = width.ok_or_else(|| Err::Error(Error::new(pixels, ErrorKind::Eof)))?;
+        Ok((input, Self::from_vec(width, height, tiles)))
+    }
+}
+
+impl Grid<bool> {
+    // The grid covers the tileset's range, with its top left corner moved to the origin
+    pub fn from_tileset(tileset: &TileSet) -> Self {
+        let range = match tileset.get_range() {
+            Some(range) => range,
+            None => return Self::from_vec(0, 0, Vec::new()),
+        };
+        let mut grid = Self::new(range.width(), range.height(), false);
+        for p in tileset.iter() {
+            grid[*p - range.min] = true;
+        }
+        grid
+    }
+
+    pub fn to_tileset(&self) -> TileSet {
+        let tiles: Vec<Point> = self.iter().filter(|(_p, &t)| t).map(|(p, _t)| p).collect();
+        TileSet::new().with_tiles(&tiles)
+    }
+}
+
+impl<T> std::ops::Index<Point> for Grid<T> {
+    type Output = T;
+    fn index(&self, p: Point) -> &Self::Output {
+        let idx = self.point_to_idx(&p).expect("Point out of bounds");
+        &self.tiles[idx]
+    }
+}
+
+impl<T> std::ops::IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, p: Point) -> &mut Self::Output {
+        let idx = self.point_to_idx(&p).expect("Point out of bounds");
+        &mut self.tiles[idx]
+    }
+}
+
+impl<T: TileChar> std::fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.rows() {
+            for t in row {
+                write!(f, "{}", t.to_char())?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -297,14 +667,17 @@ X..
 #.#
 ###";
         let tileset = TileSet::from_string(input, '#');
-        assert_eq!(tileset.get_range(), Some(((0, 2), (0, 2))));
+        assert_eq!(tileset.get_range(), Some(Rect::from_ranges((0, 2), (0, 2))));
 
         let input = "#.#.#.#.#.#.#.#.#";
         let tileset = TileSet::from_string(input, '#');
-        assert_eq!(tileset.get_range(), Some(((0, 16), (0, 0))));
+        assert_eq!(
+            tileset.get_range(),
+            Some(Rect::from_ranges((0, 16), (0, 0)))
+        );
     }
 
-    #[derive(Debug, PartialEq)]
+    #[derive(Clone, Debug, PartialEq)]
     enum MyTile {
         A,
         B,
@@ -334,6 +707,84 @@ X..
         }
     }
 
+    #[test]
+    fn test_tileset_transforms() {
+        let input = "\
+##.
+#.#";
+        let tileset = TileSet::from_string(input, '#');
+        let rotated = tileset.rotate90();
+        assert_eq!(
+            rotated.get_range(),
+            Some(Rect::from_ranges((-1, 0), (0, 2)))
+        );
+        assert_eq!(rotated.normalize().to_string(), "##\n.#\n#.\n");
+        assert_eq!(tileset.rotate180().normalize().to_string(), "#.#\n.##\n");
+        assert_eq!(tileset.rotate270().normalize().to_string(), ".#\n#.\n##\n");
+        assert_eq!(
+            tileset.flip_horizontal().normalize().to_string(),
+            ".##\n#.#\n"
+        );
+        assert_eq!(
+            tileset.flip_vertical().normalize().to_string(),
+            "#.#\n##.\n"
+        );
+        assert_eq!(tileset.transpose().to_string(), "##\n#.\n.#\n");
+
+        // Undoing each transform gets back to the start
+        assert_eq!(tileset.rotate90().rotate270(), tileset);
+        assert_eq!(tileset.rotate180().rotate180(), tileset);
+        assert_eq!(tileset.rotate90().rotate90().rotate90().rotate90(), tileset);
+        assert_eq!(tileset.flip_horizontal().flip_horizontal(), tileset);
+        assert_eq!(tileset.transpose().transpose(), tileset);
+        assert_eq!(tileset.transpose(), tileset.flip_horizontal().rotate270());
+
+        // Orientations match the named transforms
+        assert_eq!(
+            tileset.orient(Orientation::new(1, false)),
+            tileset.rotate90()
+        );
+        assert_eq!(
+            tileset.orient(Orientation::new(3, false)),
+            tileset.rotate270()
+        );
+        assert_eq!(
+            tileset.orient(Orientation::new(0, true)),
+            tileset.flip_horizontal()
+        );
+        assert_eq!(
+            tileset.orient(Orientation::new(2, true)),
+            tileset.flip_vertical()
+        );
+        let orientations: Vec<(Orientation, TileSet)> = tileset.orientations().collect();
+        assert_eq!(orientations.len(), 8);
+        for (i, (orientation, oriented)) in orientations.iter().enumerate() {
+            assert_eq!(oriented, &tileset.orient(*orientation).normalize());
+            assert!(orientations[..i].iter().all(|(_, other)| other != oriented));
+        }
+    }
+
+    #[test]
+    fn test_tileset_crop_translate() {
+        let input = "\
+#.#
+.#.
+#.#";
+        let tileset = TileSet::from_string(input, '#');
+        let cropped = tileset.crop(Rect::from_ranges((1, 2), (0, 1)));
+        assert_eq!(cropped.to_string(), ".#\n#.\n");
+        assert_eq!(cropped.get_range(), Some(Rect::from_ranges((1, 2), (0, 1))));
+        assert_eq!(
+            cropped.normalize().get_range(),
+            Some(Rect::from_ranges((0, 1), (0, 1)))
+        );
+
+        let moved = tileset.translate(Point { x: -5, y: 3 });
+        assert_eq!(moved.get_range(), Some(Rect::from_ranges((-5, -3), (3, 5))));
+        assert_eq!(moved.normalize(), tileset);
+        assert_eq!(TileSet::new().normalize(), TileSet::new());
+    }
+
     #[test]
     fn test_tilemap_from_string() {
         let input = ".";
@@ -426,10 +877,160 @@ ABC
 A.A
 CBA";
         let tilemap: TileMap<MyTile> = TileMap::from_string(input);
-        assert_eq!(tilemap.get_range(), Some(((0, 2), (0, 2))));
+        assert_eq!(tilemap.get_range(), Some(Rect::from_ranges((0, 2), (0, 2))));
 
         let input = "ABCABCABCABCABC";
         let tilemap: TileMap<MyTile> = TileMap::from_string(input);
-        assert_eq!(tilemap.get_range(), Some(((0, 14), (0, 0))));
+        assert_eq!(
+            tilemap.get_range(),
+            Some(Rect::from_ranges((0, 14), (0, 0)))
+        );
+    }
+
+    #[test]
+    fn test_tilemap_transforms() {
+        let input = "\
+AB
+C.";
+        let tilemap: TileMap<MyTile> = TileMap::from_string(input);
+        assert_eq!(tilemap.rotate90().normalize().to_string(), "CA\n.B\n");
+        assert_eq!(tilemap.rotate180().normalize().to_string(), ".C\nBA\n");
+        assert_eq!(tilemap.rotate270().normalize().to_string(), "B.\nAC\n");
+        assert_eq!(
+            tilemap.flip_horizontal().normalize().to_string(),
+            "BA\n.C\n"
+        );
+        assert_eq!(tilemap.flip_vertical().normalize().to_string(), "C.\nAB\n");
+        assert_eq!(tilemap.transpose().to_string(), "AC\nB.\n");
+        assert_eq!(tilemap.orientations().count(), 8);
+
+        let cropped = tilemap.crop(Rect::from_ranges((1, 1), (0, 1)));
+        assert_eq!(cropped.to_string(), "B\n");
+        assert_eq!(
+            cropped.translate(Point { x: 2, y: 2 }).get_range(),
+            Some(Rect::from_ranges((3, 3), (2, 2)))
+        );
+        assert_eq!(
+            cropped.normalize().get_range(),
+            Some(Rect::from_ranges((0, 0), (0, 0)))
+        );
+    }
+
+    #[test]
+    fn test_grid_from_string() {
+        let input = "\
+ABC
+ACA
+CBA";
+        let grid: Grid<MyTile> = Grid::parser(input).unwrap().1;
+        assert_eq!(grid.width(), 3);
+        assert_eq!(grid.height(), 3);
+        assert_eq!(grid[Point { x: 1, y: 0 }], MyTile::B);
+        assert_eq!(grid.get(&Point { x: 2, y: 2 }), Some(&MyTile::A));
+        assert_eq!(grid.get(&Point { x: 3, y: 0 }), None);
+        assert_eq!(grid.get(&Point { x: 0, y: -1 }), None);
+
+        let input = "\
+AB
+BA
+
+CC";
+        let (remaining, grid) = Grid::<MyTile>::parser(input).unwrap();
+        assert_eq!(remaining, "\n\nCC");
+        assert_eq!(grid.height(), 2);
+
+        let input = "ABC\nAXC";
+        assert_eq!(
+            Grid::<MyTile>::parser(input),
+            Err(Err::Error(Error::new("XC", ErrorKind::Char)))
+        );
+
+        let input = "ABC\nABCA";
+        assert_eq!(
+            Grid::<MyTile>::parser(input),
+            Err(Err::Error(Error::new("A", ErrorKind::Verify)))
+        );
+    }
+
+    #[test]
+    fn test_grid_display() {
+        let input = "\
+ABC
+ACA
+CBA";
+        let grid: Grid<MyTile> = Grid::from_string(input);
+        assert_eq!(grid.to_string().trim(), input);
+    }
+
+    #[test]
+    fn test_grid_rows_columns() {
+        let grid = Grid::from_vec(3, 2, vec![1, 2, 3, 4, 5, 6]);
+        assert_eq!(
+            grid.rows().collect::<Vec<_>>(),
+            vec![&[1, 2, 3], &[4, 5, 6]]
+        );
+        assert_eq!(grid.column(1).copied().collect::<Vec<_>>(), vec![2, 5]);
+        assert_eq!(
+            grid.columns()
+                .map(|c| c.copied().collect())
+                .collect::<Vec<Vec<_>>>(),
+            vec![vec![1, 4], vec![2, 5], vec![3, 6]]
+        );
+        assert_eq!(
+            grid.iter().map(|(p, &t)| (p, t)).nth(4),
+            Some((Point { x: 1, y: 1 }, 5))
+        );
+    }
+
+    #[test]
+    fn test_grid_neighbors() {
+        let mut grid = Grid::new(3, 3, 0);
+        grid[Point { x: 1, y: 1 }] = 5;
+        *grid.get_mut(&Point { x: 2, y: 0 }).unwrap() = 7;
+
+        let corner = Point { x: 0, y: 0 };
+        assert_eq!(grid.orthogonals(&corner).count(), 2);
+        assert_eq!(grid.adjacents(&corner).count(), 3);
+        assert_eq!(grid.adjacents(&Point { x: 1, y: 1 }).count(), 8);
+        assert_eq!(grid.adjacents_wrapping(&corner).count(), 8);
+        assert_eq!(grid.adjacents(&corner).map(|p| grid[p]).sum::<i32>(), 5);
+        assert_eq!(
+            grid.adjacents_wrapping(&corner)
+                .map(|p| grid[p])
+                .sum::<i32>(),
+            12
+        );
+        assert_eq!(
+            grid.orthogonals_wrapping(&corner)
+                .collect::<Vec<_>>()
+                .contains(&Point { x: 2, y: 0 }),
+            true
+        );
+        assert_eq!(grid.get_wrapping(&Point { x: -1, y: 3 }), Some(&7));
+
+        let empty: Grid<i32> = Grid::from_vec(0, 0, Vec::new());
+        assert_eq!(empty.get_wrapping(&corner), None);
+        assert_eq!(empty.adjacents_wrapping(&corner).count(), 0);
+    }
+
+    #[test]
+    fn test_grid_conversions() {
+        let input = "\
+A..
+.B.
+..C";
+        let tilemap: TileMap<MyTile> = TileMap::from_string(input);
+        let grid = Grid::from_tilemap(&tilemap, MyTile::A);
+        assert_eq!(grid.to_string().trim(), "AAA\nABA\nAAC");
+        assert_eq!(grid.to_tilemap().len(), 9);
+
+        let grid: Grid<MyTile> = Grid::from_string("AB\nCA");
+        assert_eq!(Grid::from_tilemap(&grid.to_tilemap(), MyTile::C), grid);
+
+        let tileset = TileSet::from_string("..#\n#..", '#');
+        let grid = Grid::from_tileset(&tileset);
+        assert_eq!(grid.width(), 3);
+        assert_eq!(grid.row(1), &[true, false, false]);
+        assert_eq!(grid.to_tileset(), tileset);
     }
 }
@@ -0,0 +1,214 @@
+use crate::common::ParseError;
+use crate::console::{Instruction, InstructionSet, Mnemonic, Operand, Register};
+use std::collections::{BTreeSet, HashMap};
+
+// Turns assembly source into programs and back. Each line holds at most one instruction, optionally after a
+// "label:", and anything after a '#' or ';' is a comment. Operands are separated by spaces or commas, and
+// jumps take either a relative offset or a label.
+pub struct Assembler {
+    instruction_set: InstructionSet,
+}
+
+impl Assembler {
+    pub fn new(instruction_set: InstructionSet) -> Self {
+        Self { instruction_set }
+    }
+
+    pub fn assemble(&self, source: &str) -> Result<Vec<Instruction>, ParseError> {
+        let error = |location: &str, expected: &str| ParseError::at(8, source, location, expected);
+
+        // Labels can be used before they're defined, so find them all first
+        let mut labels = HashMap::new();
+        let mut lines = Vec::new();
+        for line in source.lines() {
+            let line = line.split(['#', ';']).next().unwrap();
+            let line = match line.find(':') {
+                Some(colon) => {
+                    let label = line[..colon].trim();
+                    let valid = label.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                        && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+                    if valid == false {
+                        return Err(error(line.trim_start(), "a label"));
+                    }
+                    if labels.insert(label, lines.len()).is_some() {
+                        return Err(error(label, "a label which hasn't already been used"));
+                    }
+                    &line[colon + 1..]
+                }
+                None => line,
+            };
+
+            let words: Vec<&str> = line
+                .split(|c: char| c.is_whitespace() || c == ',')
+                .filter(|word| word.is_empty() == false)
+                .collect();
+            if words.is_empty() == false {
+                lines.push(words);
+            }
+        }
+
+        let mut program = Vec::new();
+        for (ip, words) in lines.iter().enumerate() {
+            let mnemonic = Mnemonic::from_str(words[0])
+                .filter(|&mnemonic| self.instruction_set.contains(mnemonic))
+                .ok_or_else(|| error(words[0], &self.instruction_set.describe()))?;
+
+            let expected = match mnemonic {
+                Mnemonic::Jmp | Mnemonic::Nop => "a jump offset or label",
+                Mnemonic::Acc => "a signed argument",
+                Mnemonic::Set | Mnemonic::Add | Mnemonic::Mul => "a register and a value",
+                Mnemonic::Jz | Mnemonic::Jnz => "a register and a jump offset or label",
+                Mnemonic::Out => "a value",
+                Mnemonic::Hlt => "no operands",
+            };
+            let operand_error = || error(words[0], &format!("{} {}", mnemonic.name(), expected));
+            let operands = &words[1..];
+            let register = |word: &str| Register::from_str(word).ok_or_else(operand_error);
+            let value = |word: &str| Operand::from_str(word).ok_or_else(operand_error);
+            let offset = |word: &str| match labels.get(word) {
+                Some(&target) => Ok(target as i32 - ip as i32),
+                None => word.parse::<i32>().map_err(|_| operand_error()),
+            };
+
+            let instruction = match (mnemonic, operands) {
+                (Mnemonic::Jmp, [x]) => Instruction::Jmp(offset(x)?),
+                (Mnemonic::Acc, [x]) => {
+                    Instruction::Acc(x.parse::<i32>().map_err(|_| operand_error())?)
+                }
+                (Mnemonic::Nop, [x]) => Instruction::Nop(offset(x)?),
+                (Mnemonic::Set, [r, x]) => Instruction::Set(register(r)?, value(x)?),
+                (Mnemonic::Add, [r, x]) => Instruction::Add(register(r)?, value(x)?),
+                (Mnemonic::Mul, [r, x]) => Instruction::Mul(register(r)?, value(x)?),
+                (Mnemonic::Jz, [r, x]) => Instruction::Jz(register(r)?, offset(x)?),
+                (Mnemonic::Jnz, [r, x]) => Instruction::Jnz(register(r)?, offset(x)?),
+                (Mnemonic::Out, [x]) => Instruction::Out(value(x)?),
+                (Mnemonic::Hlt, []) => Instruction::Hlt,
+                _ => return Err(operand_error()),
+            };
+            program.push(instruction);
+        }
+
+        if program.is_empty() {
+            return Err(error(source, &self.instruction_set.describe()));
+        }
+        Ok(program)
+    }
+
+    // Every jump target within the program gets a label, named after the instruction it points at, and the
+    // end of the program gets one too if anything jumps there. Jumps before the start keep their offset.
+    pub fn disassemble(&self, program: &[Instruction]) -> String {
+        let target = |ip: usize, instruction: &Instruction| {
+            instruction
+                .offset()
+                .map(|x| ip as isize + x as isize)
+                .filter(|&target| target >= 0 && target <= program.len() as isize)
+                .map(|target| target as usize)
+        };
+        let targets: BTreeSet<usize> = program
+            .iter()
+            .enumerate()
+            .filter_map(|(ip, instruction)| target(ip, instruction))
+            .collect();
+
+        let mut lines = Vec::new();
+        for (ip, instruction) in program.iter().enumerate() {
+            if targets.contains(&ip) == true {
+                lines.push(format!("l{}:", ip));
+            }
+            let line = match (instruction, target(ip, instruction)) {
+                (Instruction::Jmp(_), Some(target)) => format!("jmp l{}", target),
+                (Instruction::Nop(_), Some(target)) => format!("nop l{}", target),
+                (Instruction::Jz(r, _), Some(target)) => format!("jz {} l{}", r, target),
+                (Instruction::Jnz(r, _), Some(target)) => format!("jnz {} l{}", r, target),
+                _ => instruction.to_string(),
+            };
+            lines.push(format!("    {}", line));
+        }
+        if targets.contains(&program.len()) == true {
+            lines.push(format!("l{}:", program.len()));
+        }
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::console::test::{COUNTDOWN, EXAMPLE_PROGRAM};
+    use crate::console::GameConsole;
+
+    #[test]
+    fn test_assemble() {
+        let program = Assembler::new(InstructionSet::extended())
+            .assemble(COUNTDOWN)
+            .unwrap();
+        let a = Register::General(0);
+        assert_eq!(
+            program,
+            vec![
+                Instruction::Set(a, Operand::Value(3)),
+                Instruction::Out(Operand::Register(a)),
+                Instruction::Add(Register::Accumulator, Operand::Register(a)),
+                Instruction::Add(a, Operand::Value(-1)),
+                Instruction::Jnz(a, -3),
+                Instruction::Hlt,
+                Instruction::Out(Operand::Value(99)),
+            ]
+        );
+
+        let mut console = GameConsole::new(program);
+        assert_eq!(console.execute(), Ok((false, 6)));
+        assert_eq!(console.output(), [3, 2, 1]);
+        assert_eq!(console.register(a), 0);
+        assert_eq!(console.ip, 5);
+    }
+
+    #[test]
+    fn test_assemble_invalid() {
+        let boot = Assembler::new(InstructionSet::boot());
+        let error = boot.assemble("acc +1\n  mul acc 2").err().unwrap();
+        assert_eq!((error.day, error.line, error.column), (8, 2, 3));
+        assert_eq!(error.expected, "an instruction (jmp, acc, nop)");
+
+        let error = boot.assemble("jmp nowhere").err().unwrap();
+        assert_eq!(error.expected, "jmp a jump offset or label");
+        let error = boot.assemble("x:\nx: nop +0").err().unwrap();
+        assert_eq!((error.line, error.column), (2, 1));
+        assert!(boot.assemble("# nothing here").is_err());
+
+        let extended = Assembler::new(InstructionSet::extended().without_mnemonic(Mnemonic::Mul));
+        assert!(extended.assemble("set i 2").is_err());
+        assert!(extended.assemble("hlt 1").is_err());
+        assert!(extended.assemble("mul a 2").is_err());
+        assert!(extended.assemble("add a 2").is_ok());
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let assembler = Assembler::new(InstructionSet::extended());
+        let program = assembler.assemble(EXAMPLE_PROGRAM).unwrap();
+        let source = assembler.disassemble(&program);
+        let expected = "\
+l0:
+    nop l0
+l1:
+    acc +1
+    jmp l6
+l3:
+    acc +3
+    jmp l1
+    acc -99
+l6:
+    acc +1
+    jmp l3
+    acc +6";
+        assert_eq!(source, expected);
+        assert_eq!(assembler.assemble(&source).unwrap(), program);
+
+        let program = assembler.assemble(COUNTDOWN).unwrap();
+        let program = [program, vec![Instruction::Jz(Register::General(7), 1)]].concat();
+        let source = assembler.disassemble(&program);
+        assert!(source.ends_with("    jz h l8\nl8:"));
+        assert_eq!(assembler.assemble(&source).unwrap(), program);
+    }
+}
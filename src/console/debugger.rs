@@ -0,0 +1,482 @@
+use crate::console::{
+    Assembler, Crash, GameConsole, Instruction, InstructionSet, LoopDetector, Register, REGISTERS,
+};
+use std::collections::BTreeSet;
+use std::io::{self, BufRead, Write};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Watchpoint {
+    // Any instruction which changes the accumulator
+    Change,
+    // The accumulator becoming the given value
+    Equals(i32),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StopReason {
+    Step,
+    Breakpoint(usize),
+    Watchpoint { old: i32, new: i32 },
+    // The program is about to go round a loop it can never leave
+    Loop(usize),
+    Terminated,
+    Crashed(Crash),
+}
+
+// Enough to undo one step
+#[derive(Clone, Copy)]
+struct HistoryEntry {
+    ip: usize,
+    accumulator: i32,
+    registers: [i32; REGISTERS],
+    output_len: usize,
+    first_run: bool,
+}
+
+// Runs a console a step at a time, remembering every step so they can be undone
+pub struct Debugger {
+    console: GameConsole,
+    breakpoints: BTreeSet<usize>,
+    watchpoints: Vec<Watchpoint>,
+    history: Vec<HistoryEntry>,
+}
+
+impl Debugger {
+    pub fn new(console: GameConsole) -> Self {
+        Self {
+            console,
+            breakpoints: BTreeSet::new(),
+            watchpoints: Vec::new(),
+            history: Vec::new(),
+        }
+    }
+
+    pub fn ip(&self) -> usize {
+        self.console.ip
+    }
+
+    pub fn accumulator(&self) -> i32 {
+        self.console.accumulator
+    }
+
+    pub fn executed(&self) -> &[bool] {
+        &self.console.executed
+    }
+
+    pub fn program(&self) -> &[Instruction] {
+        &self.console.program
+    }
+
+    pub fn current_instruction(&self) -> Option<&Instruction> {
+        self.console.program.get(self.console.ip)
+    }
+
+    pub fn register(&self, register: Register) -> i32 {
+        self.console.register(register)
+    }
+
+    pub fn output(&self) -> &[i32] {
+        self.console.output()
+    }
+
+    pub fn is_terminated(&self) -> bool {
+        self.console.is_terminated()
+    }
+
+    pub fn steps(&self) -> usize {
+        self.history.len()
+    }
+
+    pub fn breakpoints(&self) -> impl Iterator<Item = usize> + '_ {
+        self.breakpoints.iter().copied()
+    }
+
+    pub fn add_breakpoint(&mut self, ip: usize) {
+        self.breakpoints.insert(ip);
+    }
+
+    pub fn remove_breakpoint(&mut self, ip: usize) -> bool {
+        self.breakpoints.remove(&ip)
+    }
+
+    pub fn watchpoints(&self) -> &[Watchpoint] {
+        &self.watchpoints
+    }
+
+    pub fn add_watchpoint(&mut self, watchpoint: Watchpoint) {
+        if self.watchpoints.contains(&watchpoint) == false {
+            self.watchpoints.push(watchpoint);
+        }
+    }
+
+    pub fn clear_watchpoints(&mut self) {
+        self.watchpoints.clear();
+    }
+
+    // Runs one instruction, even if it has run before
+    pub fn step(&mut self) -> StopReason {
+        if let Some(crash) = self.console.crash {
+            return StopReason::Crashed(crash);
+        }
+        if self.is_terminated() == true {
+            return StopReason::Terminated;
+        }
+
+        let old = self.console.accumulator;
+        self.history.push(HistoryEntry {
+            ip: self.console.ip,
+            accumulator: old,
+            registers: self.console.registers,
+            output_len: self.console.output.len(),
+            first_run: self.console.executed[self.console.ip] == false,
+        });
+        self.console.executed[self.console.ip] = true;
+        self.console.execute_next_instruction();
+
+        let new = self.console.accumulator;
+        let triggered = self.watchpoints.iter().any(|w| match w {
+            Watchpoint::Change => new != old,
+            Watchpoint::Equals(value) => new == *value && old != *value,
+        });
+        if triggered == true {
+            StopReason::Watchpoint { old, new }
+        } else if let Some(crash) = self.console.crash {
+            StopReason::Crashed(crash)
+        } else if self.is_terminated() == true {
+            StopReason::Terminated
+        } else {
+            StopReason::Step
+        }
+    }
+
+    // Undoes the last step, returning false if there's nothing left to undo
+    pub fn step_back(&mut self) -> bool {
+        match self.history.pop() {
+            Some(entry) => {
+                self.console.ip = entry.ip;
+                self.console.accumulator = entry.accumulator;
+                self.console.registers = entry.registers;
+                self.console.output.truncate(entry.output_len);
+                self.console.halted = false;
+                self.console.crash = None;
+                if entry.first_run == true {
+                    self.console.executed[entry.ip] = false;
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Keeps stepping until the program ends, a watchpoint triggers, or it's about to run a breakpoint or go
+    // round an endless loop. The current instruction always runs and loops are only looked for from where
+    // this run started, so calling this again carries on past whatever stopped it last time, going round
+    // a loop once more.
+    pub fn run(&mut self) -> StopReason {
+        let mut detector = LoopDetector::new(&self.console);
+        if self.is_terminated() == false && self.console.crash.is_none() {
+            detector.repeats(&self.console);
+        }
+        loop {
+            match self.step() {
+                StopReason::Step => (),
+                reason => return reason,
+            }
+
+            let ip = self.console.ip;
+            if self.breakpoints.contains(&ip) == true {
+                return StopReason::Breakpoint(ip);
+            }
+            if detector.repeats(&self.console) == true {
+                return StopReason::Loop(ip);
+            }
+        }
+    }
+}
+
+const DEBUG_HELP: &str = "\
+Commands:
+    s, step [N]         Run the next N instructions (default 1)
+    b, back [N]         Undo the last N instructions (default 1)
+    c, continue         Run until a breakpoint, watchpoint, loop, crash or the end of the program
+    break IP            Stop before running the instruction at IP
+    delete IP           Remove the breakpoint at IP
+    watch [VALUE]       Stop when the accumulator changes, or becomes VALUE
+    unwatch             Remove all watchpoints
+    p, print            Show the instruction pointer, accumulator and next instruction
+    registers           Show the registers and everything output so far
+    executed            List the instructions which have run
+    l, list [N]         Show the N instructions around the instruction pointer (default 7)
+    h, help             Show this message
+    q, quit             Exit the debugger";
+
+fn describe(debugger: &Debugger, reason: StopReason) -> String {
+    let location = match debugger.current_instruction() {
+        Some(instruction) => format!("ip {}: {}", debugger.ip(), instruction),
+        None => format!("ip {}: end of program", debugger.ip()),
+    };
+    match reason {
+        StopReason::Step => location,
+        StopReason::Breakpoint(ip) => format!("Breakpoint at {}\n{}", ip, location),
+        StopReason::Watchpoint { old, new } => {
+            format!("Accumulator changed from {} to {}\n{}", old, new, location)
+        }
+        StopReason::Loop(ip) => format!("Loop detected at {}\n{}", ip, location),
+        StopReason::Terminated => format!(
+            "Program terminated with accumulator {}",
+            debugger.accumulator()
+        ),
+        StopReason::Crashed(crash) => {
+            format!("{} with accumulator {}", crash, debugger.accumulator())
+        }
+    }
+}
+
+fn debug_command(debugger: &mut Debugger, line: &str) -> Option<String> {
+    let mut words = line.split_whitespace();
+    let command = words.next()?;
+    let argument = words.next();
+    let count = match argument.map(str::parse::<usize>) {
+        None => Ok(1),
+        Some(Ok(n)) => Ok(n),
+        Some(Err(_)) => Err(format!("Invalid number '{}'", argument.unwrap())),
+    };
+    let ip = match argument.map(str::parse::<usize>) {
+        Some(Ok(ip)) if ip < debugger.program().len() => Ok(ip),
+        Some(_) => Err(format!("Invalid instruction '{}'", argument.unwrap())),
+        None => Err("Missing instruction".to_string()),
+    };
+
+    let output = match command {
+        "s" | "step" => match count {
+            Ok(n) => {
+                let mut reason = StopReason::Step;
+                for _ in 0..n {
+                    reason = debugger.step();
+                    if reason != StopReason::Step {
+                        break;
+                    }
+                }
+                describe(debugger, reason)
+            }
+            Err(e) => e,
+        },
+        "b" | "back" => match count {
+            Ok(n) => {
+                let undone = (0..n).take_while(|_| debugger.step_back()).count();
+                if undone < n {
+                    format!(
+                        "At the start of the history\n{}",
+                        describe(debugger, StopReason::Step)
+                    )
+                } else {
+                    describe(debugger, StopReason::Step)
+                }
+            }
+            Err(e) => e,
+        },
+        "c" | "continue" => {
+            let reason = debugger.run();
+            describe(debugger, reason)
+        }
+        "break" => match ip {
+            Ok(ip) => {
+                debugger.add_breakpoint(ip);
+                format!("Breakpoint at {}", ip)
+            }
+            Err(e) => e,
+        },
+        "delete" => match ip {
+            Ok(ip) if debugger.remove_breakpoint(ip) == true => {
+                format!("Deleted breakpoint at {}", ip)
+            }
+            Ok(ip) => format!("No breakpoint at {}", ip),
+            Err(e) => e,
+        },
+        "watch" => match argument.map(str::parse::<i32>) {
+            None => {
+                debugger.add_watchpoint(Watchpoint::Change);
+                "Watching for accumulator changes".to_string()
+            }
+            Some(Ok(value)) => {
+                debugger.add_watchpoint(Watchpoint::Equals(value));
+                format!("Watching for accumulator {}", value)
+            }
+            Some(Err(_)) => format!("Invalid value '{}'", argument.unwrap()),
+        },
+        "unwatch" => {
+            debugger.clear_watchpoints();
+            "Removed all watchpoints".to_string()
+        }
+        "p" | "print" => format!(
+            "{}\naccumulator {}, {} steps",
+            describe(debugger, StopReason::Step),
+            debugger.accumulator(),
+            debugger.steps()
+        ),
+        "registers" => {
+            let registers: Vec<String> = (0..REGISTERS)
+                .map(|r| {
+                    let register = Register::General(r);
+                    format!("{}={}", register, debugger.register(register))
+                })
+                .collect();
+            let output: Vec<String> = debugger.output().iter().map(i32::to_string).collect();
+            format!(
+                "acc={} {}\noutput: {}",
+                debugger.accumulator(),
+                registers.join(" "),
+                output.join(", ")
+            )
+        }
+        "executed" => {
+            let executed: Vec<String> = (0..debugger.program().len())
+                .filter(|&ip| debugger.executed()[ip] == true)
+                .map(|ip| ip.to_string())
+                .collect();
+            format!("{} executed: {}", executed.len(), executed.join(", "))
+        }
+        "l" | "list" => {
+            let n = match argument {
+                Some(_) => count.unwrap_or(7),
+                None => 7,
+            };
+            let start = debugger.ip().saturating_sub(n / 2);
+            let end = (start + n).min(debugger.program().len());
+            let lines: Vec<String> = (start..end)
+                .map(|ip| {
+                    let marker = if ip == debugger.ip() { '>' } else { ' ' };
+                    let breakpoint = if debugger.breakpoints.contains(&ip) {
+                        '*'
+                    } else {
+                        ' '
+                    };
+                    format!(
+                        "{}{}{:>5}  {}",
+                        marker,
+                        breakpoint,
+                        ip,
+                        debugger.program()[ip]
+                    )
+                })
+                .collect();
+            lines.join("\n")
+        }
+        "h" | "help" => DEBUG_HELP.to_string(),
+        _ => format!("Unknown command '{}', try 'help'", command),
+    };
+    Some(output)
+}
+
+// Reads debugger commands a line at a time until quit or the end of the input. The program can be boot code
+// or assembly using any of the extended instructions.
+pub fn debug<R: BufRead, W: Write>(source: &str, commands: R, mut output: W) -> Result<(), String> {
+    let program = Assembler::new(InstructionSet::extended())
+        .assemble(source)
+        .map_err(|e| e.to_string())?;
+    let mut debugger = Debugger::new(GameConsole::new(program));
+    let io_error = |e: io::Error| e.to_string();
+
+    writeln!(output, "{}", describe(&debugger, StopReason::Step)).map_err(io_error)?;
+    write!(output, "(debug) ").map_err(io_error)?;
+    output.flush().map_err(io_error)?;
+    for line in commands.lines() {
+        let line = line.map_err(io_error)?;
+        if matches!(line.trim(), "q" | "quit") {
+            break;
+        }
+        if let Some(response) = debug_command(&mut debugger, &line) {
+            writeln!(output, "{}", response).map_err(io_error)?;
+        }
+        write!(output, "(debug) ").map_err(io_error)?;
+        output.flush().map_err(io_error)?;
+    }
+    writeln!(output).map_err(io_error)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::console::test::{boot_code, EXAMPLE_PROGRAM};
+
+    #[test]
+    fn test_debugger_step() {
+        let mut debugger = Debugger::new(boot_code(EXAMPLE_PROGRAM));
+        assert_eq!(debugger.current_instruction(), Some(&Instruction::Nop(0)));
+        assert_eq!(debugger.step(), StopReason::Step);
+        assert_eq!(debugger.step(), StopReason::Step);
+        assert_eq!((debugger.ip(), debugger.accumulator()), (2, 1));
+        assert_eq!(debugger.executed()[..3], [true, true, false]);
+
+        assert_eq!(debugger.step_back(), true);
+        assert_eq!((debugger.ip(), debugger.accumulator()), (1, 0));
+        assert_eq!(debugger.executed()[..3], [true, false, false]);
+        assert_eq!(debugger.step_back(), true);
+        assert_eq!(debugger.step_back(), false);
+        assert_eq!(debugger.steps(), 0);
+    }
+
+    #[test]
+    fn test_debugger_run() {
+        let mut debugger = Debugger::new(boot_code(EXAMPLE_PROGRAM));
+        debugger.add_breakpoint(4);
+        assert_eq!(debugger.run(), StopReason::Breakpoint(4));
+        assert_eq!(debugger.accumulator(), 5);
+        assert_eq!(debugger.run(), StopReason::Breakpoint(4));
+        assert_eq!(debugger.accumulator(), 10);
+
+        // Each run only stops for a loop once it gets back to somewhere it's been during that run
+        assert_eq!(debugger.remove_breakpoint(4), true);
+        assert_eq!(debugger.run(), StopReason::Loop(4));
+        assert_eq!(debugger.accumulator(), 15);
+        assert_eq!(debugger.run(), StopReason::Loop(4));
+        assert_eq!(debugger.accumulator(), 20);
+
+        // Undoing the loop back to the start runs the same path again
+        while debugger.step_back() == true {}
+        assert_eq!(debugger.executed().iter().any(|&e| e), false);
+        assert_eq!(debugger.run(), StopReason::Loop(1));
+    }
+
+    #[test]
+    fn test_debugger_watchpoints() {
+        let mut debugger = Debugger::new(boot_code(EXAMPLE_PROGRAM));
+        debugger.add_watchpoint(Watchpoint::Equals(7));
+        assert_eq!(debugger.run(), StopReason::Loop(1));
+
+        while debugger.step_back() == true {}
+        debugger.clear_watchpoints();
+        debugger.add_watchpoint(Watchpoint::Change);
+        assert_eq!(debugger.run(), StopReason::Watchpoint { old: 0, new: 1 });
+        assert_eq!(debugger.run(), StopReason::Watchpoint { old: 1, new: 2 });
+        assert_eq!(debugger.ip(), 7);
+
+        let mut fixed = boot_code(EXAMPLE_PROGRAM);
+        fixed.program[7] = Instruction::Nop(-4);
+        let mut debugger = Debugger::new(fixed);
+        debugger.add_watchpoint(Watchpoint::Equals(8));
+        assert_eq!(debugger.run(), StopReason::Watchpoint { old: 2, new: 8 });
+        assert_eq!(debugger.is_terminated(), true);
+        assert_eq!(debugger.step(), StopReason::Terminated);
+    }
+
+    #[test]
+    fn test_debug_repl() {
+        let commands = "break 4\ncontinue\nback 2\nprint\nbreak 9\nstep x\nquit\nstep\n";
+        let mut output = Vec::new();
+        debug(EXAMPLE_PROGRAM, commands.as_bytes(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let expected = "\
+ip 0: nop +0
+(debug) Breakpoint at 4
+(debug) Breakpoint at 4
+ip 4: jmp -3
+(debug) ip 7: jmp -4
+(debug) ip 7: jmp -4
+accumulator 2, 4 steps
+(debug) Invalid instruction '9'
+(debug) Invalid number 'x'
+(debug) \n";
+        assert_eq!(output, expected);
+    }
+}
@@ -0,0 +1,666 @@
+mod assembler;
+mod debugger;
+mod repair;
+mod trace;
+
+pub use assembler::*;
+pub use debugger::*;
+pub use repair::*;
+pub use trace::*;
+
+use crate::common::{signed, trim_start};
+use nom::{
+    branch::alt, bytes::complete::tag, combinator::map, multi::many1, sequence::preceded, IResult,
+};
+use std::collections::HashSet;
+
+// General purpose registers a to h, alongside the accumulator
+pub const REGISTERS: usize = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Register {
+    Accumulator,
+    General(usize),
+}
+
+impl Register {
+    pub fn from_str(s: &str) -> Option<Self> {
+        let mut chars = s.chars();
+        match (s, chars.next(), chars.next()) {
+            ("acc", _, _) => Some(Self::Accumulator),
+            (_, Some(c @ 'a'..='h'), None) => Some(Self::General(c as usize - 'a' as usize)),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Register {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Accumulator => write!(f, "acc"),
+            Self::General(r) => write!(f, "{}", (b'a' + *r as u8) as char),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Operand {
+    Value(i32),
+    Register(Register),
+}
+
+impl Operand {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match Register::from_str(s) {
+            Some(register) => Some(Self::Register(register)),
+            None => s.parse().ok().map(Self::Value),
+        }
+    }
+}
+
+impl std::fmt::Display for Operand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Value(x) => write!(f, "{}", x),
+            Self::Register(register) => write!(f, "{}", register),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Mnemonic {
+    Jmp,
+    Acc,
+    Nop,
+    Set,
+    Add,
+    Mul,
+    Jz,
+    Jnz,
+    Out,
+    Hlt,
+}
+
+impl Mnemonic {
+    pub const ALL: [Self; 10] = [
+        Self::Jmp,
+        Self::Acc,
+        Self::Nop,
+        Self::Set,
+        Self::Add,
+        Self::Mul,
+        Self::Jz,
+        Self::Jnz,
+        Self::Out,
+        Self::Hlt,
+    ];
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|mnemonic| mnemonic.name() == s)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Jmp => "jmp",
+            Self::Acc => "acc",
+            Self::Nop => "nop",
+            Self::Set => "set",
+            Self::Add => "add",
+            Self::Mul => "mul",
+            Self::Jz => "jz",
+            Self::Jnz => "jnz",
+            Self::Out => "out",
+            Self::Hlt => "hlt",
+        }
+    }
+}
+
+// Jump offsets are relative to the instruction doing the jumping. The jump of a Nop is never taken, but is
+// kept so that it can be flipped into a Jmp.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Instruction {
+    Jmp(i32),
+    Acc(i32),
+    Nop(i32),
+    Set(Register, Operand),
+    Add(Register, Operand),
+    Mul(Register, Operand),
+    Jz(Register, i32),
+    Jnz(Register, i32),
+    Out(Operand),
+    Hlt,
+}
+
+impl Instruction {
+    fn parser(input: &str) -> IResult<&str, Self> {
+        trim_start(alt((
+            map(preceded(tag("jmp "), signed), Self::Jmp),
+            map(preceded(tag("acc "), signed), Self::Acc),
+            map(preceded(tag("nop "), signed), Self::Nop),
+        )))(input)
+    }
+
+    pub fn mnemonic(&self) -> Mnemonic {
+        match self {
+            Self::Jmp(_) => Mnemonic::Jmp,
+            Self::Acc(_) => Mnemonic::Acc,
+            Self::Nop(_) => Mnemonic::Nop,
+            Self::Set(_, _) => Mnemonic::Set,
+            Self::Add(_, _) => Mnemonic::Add,
+            Self::Mul(_, _) => Mnemonic::Mul,
+            Self::Jz(_, _) => Mnemonic::Jz,
+            Self::Jnz(_, _) => Mnemonic::Jnz,
+            Self::Out(_) => Mnemonic::Out,
+            Self::Hlt => Mnemonic::Hlt,
+        }
+    }
+
+    // The relative jump an instruction can make, if any
+    fn offset(&self) -> Option<i32> {
+        match *self {
+            Self::Jmp(x) | Self::Nop(x) | Self::Jz(_, x) | Self::Jnz(_, x) => Some(x),
+            _ => None,
+        }
+    }
+
+    fn is_conditional(&self) -> bool {
+        matches!(self, Self::Jz(_, _) | Self::Jnz(_, _))
+    }
+
+    fn transform(&self) -> Self {
+        match *self {
+            Self::Jmp(x) => Self::Nop(x),
+            Self::Nop(x) => Self::Jmp(x),
+            _ => self.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Jmp(x) => write!(f, "jmp {:+}", x),
+            Self::Acc(x) => write!(f, "acc {:+}", x),
+            Self::Nop(x) => write!(f, "nop {:+}", x),
+            Self::Set(r, x) => write!(f, "set {} {}", r, x),
+            Self::Add(r, x) => write!(f, "add {} {}", r, x),
+            Self::Mul(r, x) => write!(f, "mul {} {}", r, x),
+            Self::Jz(r, x) => write!(f, "jz {} {:+}", r, x),
+            Self::Jnz(r, x) => write!(f, "jnz {} {:+}", r, x),
+            Self::Out(x) => write!(f, "out {}", x),
+            Self::Hlt => write!(f, "hlt"),
+        }
+    }
+}
+
+// Runs one instruction on the console, leaving the instruction pointer on the next one to run
+pub type Handler = fn(&mut GameConsole, &Instruction);
+
+// The instructions a console can run and how it runs each of them. The assembler only accepts these, and
+// the console crashes on any other. Swapping an instruction's handler changes what it does.
+#[derive(Clone)]
+pub struct InstructionSet {
+    handlers: [Option<Handler>; Mnemonic::ALL.len()],
+}
+
+impl InstructionSet {
+    // Just what the handheld's boot code uses
+    pub fn boot() -> Self {
+        Self::empty()
+            .with_mnemonic(Mnemonic::Jmp)
+            .with_mnemonic(Mnemonic::Acc)
+            .with_mnemonic(Mnemonic::Nop)
+    }
+
+    pub fn extended() -> Self {
+        Mnemonic::ALL
+            .iter()
+            .fold(Self::empty(), |set, &mnemonic| set.with_mnemonic(mnemonic))
+    }
+
+    fn empty() -> Self {
+        Self {
+            handlers: [None; Mnemonic::ALL.len()],
+        }
+    }
+
+    // Adds the mnemonic with its usual behavior
+    pub fn with_mnemonic(self, mnemonic: Mnemonic) -> Self {
+        self.with_handler(mnemonic, GameConsole::execute_instruction)
+    }
+
+    pub fn with_handler(mut self, mnemonic: Mnemonic, handler: Handler) -> Self {
+        self.handlers[mnemonic as usize] = Some(handler);
+        self
+    }
+
+    pub fn without_mnemonic(mut self, mnemonic: Mnemonic) -> Self {
+        self.handlers[mnemonic as usize] = None;
+        self
+    }
+
+    pub fn contains(&self, mnemonic: Mnemonic) -> bool {
+        self.handlers[mnemonic as usize].is_some()
+    }
+
+    fn describe(&self) -> String {
+        let names: Vec<&str> = Mnemonic::ALL
+            .iter()
+            .filter(|&&mnemonic| self.contains(mnemonic))
+            .map(Mnemonic::name)
+            .collect();
+        format!("an instruction ({})", names.join(", "))
+    }
+}
+
+type State = (usize, i32, [i32; REGISTERS]);
+
+#[derive(Clone)]
+pub struct GameConsole {
+    program: Vec<Instruction>,
+    ip: usize,
+    accumulator: i32,
+    registers: [i32; REGISTERS],
+    output: Vec<i32>,
+    halted: bool,
+    // Leaves the instruction pointer on the instruction which crashed
+    crash: Option<Crash>,
+    executed: Vec<bool>,
+    instruction_set: InstructionSet,
+}
+
+impl GameConsole {
+    // The console runs the extended instruction set unless it's given another
+    pub fn new(program: Vec<Instruction>) -> Self {
+        let program_len = program.len();
+        Self {
+            program,
+            ip: 0,
+            accumulator: 0,
+            registers: [0; REGISTERS],
+            output: Vec::new(),
+            halted: false,
+            crash: None,
+            executed: vec![false; program_len],
+            instruction_set: InstructionSet::extended(),
+        }
+    }
+
+    pub fn with_instruction_set(self, instruction_set: InstructionSet) -> Self {
+        Self {
+            instruction_set,
+            ..self
+        }
+    }
+
+    // Boot code, which only has jmp, acc and nop
+    pub fn parser(input: &str) -> IResult<&str, Self> {
+        let (input, program) = many1(Instruction::parser)(input)?;
+        Ok((
+            input,
+            Self::new(program).with_instruction_set(InstructionSet::boot()),
+        ))
+    }
+
+    pub fn program(&self) -> &[Instruction] {
+        &self.program
+    }
+
+    pub fn register(&self, register: Register) -> i32 {
+        match register {
+            Register::Accumulator => self.accumulator,
+            Register::General(r) => self.registers[r],
+        }
+    }
+
+    pub fn set_register(&mut self, register: Register, value: i32) {
+        *self.register_mut(register) = value;
+    }
+
+    fn register_mut(&mut self, register: Register) -> &mut i32 {
+        match register {
+            Register::Accumulator => &mut self.accumulator,
+            Register::General(r) => &mut self.registers[r],
+        }
+    }
+
+    pub fn value(&self, operand: Operand) -> i32 {
+        match operand {
+            Operand::Value(x) => x,
+            Operand::Register(register) => self.register(register),
+        }
+    }
+
+    pub fn output(&self) -> &[i32] {
+        &self.output
+    }
+
+    pub fn is_terminated(&self) -> bool {
+        self.halted == true || self.ip >= self.program.len()
+    }
+
+    pub fn crash(&self) -> Option<Crash> {
+        self.crash
+    }
+
+    fn state(&self) -> State {
+        (self.ip, self.accumulator, self.registers)
+    }
+
+    // Moves on to the next instruction
+    pub fn advance(&mut self) {
+        self.ip += 1;
+    }
+
+    // Jumps relative to the current instruction
+    pub fn jump(&mut self, x: i32) {
+        match self.ip.checked_add_signed(x as isize) {
+            Some(ip) => self.ip = ip,
+            None => self.fail(Fault::JumpedBeforeStart),
+        }
+    }
+
+    fn fail(&mut self, fault: Fault) {
+        self.crash = Some(Crash { ip: self.ip, fault });
+    }
+
+    fn execute_next_instruction(&mut self) {
+        let instruction = self.program[self.ip].clone();
+        match self.instruction_set.handlers[instruction.mnemonic() as usize] {
+            Some(handler) => handler(self, &instruction),
+            None => self.fail(Fault::IllegalInstruction(instruction.mnemonic())),
+        }
+    }
+
+    // The usual behavior of every instruction
+    fn execute_instruction(&mut self, instruction: &Instruction) {
+        match *instruction {
+            Instruction::Jmp(x) => self.jump(x),
+            Instruction::Acc(x) => {
+                self.accumulator = self.accumulator.wrapping_add(x);
+                self.ip += 1;
+            }
+            Instruction::Nop(_) => self.ip += 1,
+            Instruction::Set(r, x) => {
+                *self.register_mut(r) = self.value(x);
+                self.ip += 1;
+            }
+            Instruction::Add(r, x) => {
+                let value = self.value(x);
+                let register = self.register_mut(r);
+                *register = register.wrapping_add(value);
+                self.ip += 1;
+            }
+            Instruction::Mul(r, x) => {
+                let value = self.value(x);
+                let register = self.register_mut(r);
+                *register = register.wrapping_mul(value);
+                self.ip += 1;
+            }
+            Instruction::Jz(r, x) => match self.register(r) {
+                0 => self.jump(x),
+                _ => self.ip += 1,
+            },
+            Instruction::Jnz(r, x) => match self.register(r) {
+                0 => self.ip += 1,
+                _ => self.jump(x),
+            },
+            Instruction::Out(x) => {
+                self.output.push(self.value(x));
+                self.ip += 1;
+            }
+            // The instruction pointer stays on the hlt, like a real processor
+            Instruction::Hlt => self.halted = true,
+        }
+    }
+
+    // Runs until the program terminates or is about to loop forever. A program without conditional jumps
+    // always takes the same path, so that's as soon as an instruction is about to run a second time.
+    // Otherwise it has to wait for the whole state to repeat, and a program which keeps counting without
+    // ever terminating will run forever. Jumping before the start of the program is an error.
+    pub fn execute(&mut self) -> Result<(bool, i32), Crash> {
+        // Far more steps than could ever be run
+        self.execute_for(usize::MAX).unwrap()
+    }
+
+    // The same as execute, but gives up with None once max_steps instructions have run
+    fn execute_for(&mut self, max_steps: usize) -> Option<Result<(bool, i32), Crash>> {
+        let mut detector = LoopDetector::new(self);
+        let mut steps = 0;
+        loop {
+            if let Some(crash) = self.crash {
+                return Some(Err(crash));
+            }
+            if self.is_terminated() == true {
+                return Some(Ok((false, self.accumulator)));
+            }
+            if detector.repeats(self) == true {
+                return Some(Ok((true, self.accumulator)));
+            }
+            if steps == max_steps {
+                return None;
+            }
+
+            steps += 1;
+            self.executed[self.ip] = true;
+            self.execute_next_instruction();
+        }
+    }
+}
+
+// Spots a console returning to somewhere it's already been since the detector was made
+enum LoopDetector {
+    Visited(Vec<bool>),
+    // Only needed when the path can depend on the registers
+    States(HashSet<State>),
+}
+
+impl LoopDetector {
+    fn new(console: &GameConsole) -> Self {
+        if console.program.iter().any(Instruction::is_conditional) {
+            LoopDetector::States(HashSet::new())
+        } else {
+            LoopDetector::Visited(vec![false; console.program.len()])
+        }
+    }
+
+    fn repeats(&mut self, console: &GameConsole) -> bool {
+        match self {
+            LoopDetector::Visited(visited) => std::mem::replace(&mut visited[console.ip], true),
+            LoopDetector::States(states) => states.insert(console.state()) == false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Fault {
+    JumpedBeforeStart,
+    // An instruction which isn't in the console's instruction set
+    IllegalInstruction(Mnemonic),
+}
+
+// Why the program stopped at the given instruction
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Crash {
+    pub ip: usize,
+    pub fault: Fault,
+}
+
+impl std::fmt::Display for Crash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.fault {
+            Fault::JumpedBeforeStart => {
+                write!(f, "Jumped before the start of the program from {}", self.ip)
+            }
+            Fault::IllegalInstruction(mnemonic) => {
+                write!(f, "Illegal instruction {} at {}", mnemonic.name(), self.ip)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // The example boot code from day 8
+    pub static EXAMPLE_PROGRAM: &str = "\
+nop +0
+acc +1
+jmp +4
+acc +3
+jmp -3
+acc -99
+acc +1
+jmp -4
+acc +6";
+
+    pub static COUNTDOWN: &str = "\
+# Outputs 3, 2, 1 and leaves 6 in the accumulator
+    set a 3
+loop:
+    out a           ; count down
+    add acc a
+    add a -1
+    jnz a, loop
+    hlt
+    out 99";
+
+    // Boot code, run on a console which only has the boot code's instructions
+    pub fn boot_code(source: &str) -> GameConsole {
+        let boot = InstructionSet::boot();
+        let program = Assembler::new(boot.clone()).assemble(source).unwrap();
+        GameConsole::new(program).with_instruction_set(boot)
+    }
+
+    #[test]
+    fn test_multiple_repairs() {
+        let console = boot_code("nop +4\nacc +1\njmp -2\nacc +3\nacc +5");
+        let repairs = console.repairs();
+        let indices: Vec<usize> = repairs.iter().map(|repair| repair.index).collect();
+        assert_eq!(indices, [0, 2]);
+        assert_eq!(repairs[0].accumulator, 5);
+        assert_eq!(repairs[1].accumulator, 9);
+
+        for repair in repairs {
+            let mut repaired = console.clone();
+            repaired.program[repair.index] = repair.original.transform();
+            assert_eq!(repaired.execute(), Ok((false, repair.accumulator)));
+        }
+    }
+
+    #[test]
+    fn test_conditional_loops() {
+        // Goes round the loop three times before leaving, which only looks like an endless loop if
+        // instructions running twice is all that's checked
+        let assembler = Assembler::new(InstructionSet::extended());
+        let mut console = GameConsole::new(assembler.assemble(COUNTDOWN).unwrap());
+        let mut debugger = Debugger::new(console.clone());
+        assert_eq!(debugger.run(), StopReason::Terminated);
+        assert_eq!(debugger.output(), [3, 2, 1]);
+        while debugger.step_back() == true {}
+        assert_eq!((debugger.ip(), debugger.output()), (0, &[][..]));
+
+        // Once the counter stops changing it's stuck forever, which is spotted as soon as a trip round the
+        // loop leaves everything the same
+        console.program[2] = Instruction::Add(Register::Accumulator, Operand::Value(0));
+        console.program[3] = Instruction::Add(Register::General(0), Operand::Value(0));
+        assert_eq!(console.execute(), Ok((true, 0)));
+        assert_eq!(console.output(), [3]);
+    }
+
+    #[test]
+    fn test_extended_repairs() {
+        let assembler = Assembler::new(InstructionSet::extended());
+        let console = GameConsole::new(
+            assembler
+                .assemble("set a 2\nadd acc a\nnop end\njmp -2\nend: mul acc 10")
+                .unwrap(),
+        );
+        let repairs = console.repairs();
+        assert_eq!(repairs.len(), 2);
+        assert_eq!((repairs[0].index, repairs[0].accumulator), (2, 20));
+        assert_eq!((repairs[1].index, repairs[1].accumulator), (3, 20));
+
+        // Either flip sends it off counting for billions of steps, which it gives up on
+        let console = GameConsole::new(
+            assembler
+                .assemble("nop count\njmp end\ncount: add a 1\njnz a count\nend: hlt")
+                .unwrap(),
+        );
+        assert_eq!(console.repairs(), []);
+    }
+
+    #[test]
+    fn test_crash() {
+        let before_start = |ip| Crash {
+            ip,
+            fault: Fault::JumpedBeforeStart,
+        };
+        let mut console = boot_code("jmp -1");
+        assert_eq!(console.execute(), Err(before_start(0)));
+
+        let console = boot_code("nop +0\njmp -5");
+        assert_eq!(console.clone().execute(), Err(before_start(1)));
+        assert_eq!(
+            console.clone().trace(100).stop,
+            TraceStop::Crashed(before_start(1))
+        );
+        let repairs = console.repairs();
+        assert_eq!(repairs.len(), 1);
+        assert_eq!((repairs[0].index, repairs[0].accumulator), (1, 0));
+
+        // The crash sticks until it's undone
+        let mut debugger = Debugger::new(console);
+        assert_eq!(debugger.run(), StopReason::Crashed(before_start(1)));
+        assert_eq!(debugger.step(), StopReason::Crashed(before_start(1)));
+        assert_eq!(debugger.step_back(), true);
+        assert_eq!(debugger.step(), StopReason::Crashed(before_start(1)));
+    }
+
+    #[test]
+    fn test_instruction_set() {
+        let program = Assembler::new(InstructionSet::extended())
+            .assemble("acc +2\nmul acc 3\nout acc")
+            .unwrap();
+
+        // The boot code's console doesn't have mul
+        let mut boot =
+            GameConsole::new(program.clone()).with_instruction_set(InstructionSet::boot());
+        let crash = boot.execute().unwrap_err();
+        assert_eq!(crash.fault, Fault::IllegalInstruction(Mnemonic::Mul));
+        assert_eq!(crash.to_string(), "Illegal instruction mul at 1");
+        assert_eq!(boot.register(Register::Accumulator), 2);
+
+        let mut extended = GameConsole::new(program.clone());
+        assert_eq!(extended.execute(), Ok((false, 6)));
+        assert_eq!(extended.output(), [6]);
+
+        // Swapping a handler changes what the instruction does
+        let double_output = |console: &mut GameConsole, instruction: &Instruction| {
+            if let Instruction::Out(x) = *instruction {
+                let value = console.value(x);
+                console.output.extend([value, value]);
+            }
+            console.advance();
+        };
+        let set = InstructionSet::extended().with_handler(Mnemonic::Out, double_output);
+        let mut console = GameConsole::new(program).with_instruction_set(set);
+        assert_eq!(console.execute(), Ok((false, 6)));
+        assert_eq!(console.output(), [6, 6]);
+    }
+
+    #[test]
+    fn test_overflow() {
+        let assembler = Assembler::new(InstructionSet::extended());
+        let mut console = GameConsole::new(
+            assembler
+                .assemble("set acc 2147483647\nadd acc 1\nacc +1\nmul acc 2")
+                .unwrap(),
+        );
+        assert_eq!(console.execute(), Ok((false, 2)));
+    }
+}
@@ -0,0 +1,217 @@
+use crate::console::{GameConsole, Instruction, LoopDetector};
+
+// The most instructions repairs will run for any one flip when it can't work the answer out from the
+// control flow graph
+const REPAIR_STEP_LIMIT: usize = 100_000;
+
+impl GameConsole {
+    // Every Jmp/Nop which makes the program terminate when flipped, in the order they're first reached. For
+    // boot code only instructions on the looping path matter, and flipping one terminates exactly when the
+    // new successor can already reach the end, so this needs a single pass over the graph rather than a run
+    // per flip. Anything using registers or conditional jumps falls back to running each flip, which is
+    // quadratic, and gives up on any run longer than REPAIR_STEP_LIMIT so a program which counts forever
+    // can't hang it.
+    pub fn repairs(&self) -> Vec<Repair> {
+        let graph = ControlFlowGraph::new(&self.program);
+        let accumulator_to_end = graph.accumulator_to_end(&self.program);
+
+        let mut repairs = Vec::new();
+        let mut console = self.clone();
+        let mut detector = LoopDetector::new(&console);
+        let mut visited = vec![false; self.program.len()];
+        let mut steps = 0;
+        while console.is_terminated() == false
+            && console.crash.is_none()
+            && detector.repeats(&console) == false
+            && steps < REPAIR_STEP_LIMIT
+        {
+            let ip = console.ip;
+            let instruction = &console.program[ip];
+            let flipped = instruction.transform();
+            if visited[ip] == false && flipped != *instruction {
+                let accumulator = match &accumulator_to_end {
+                    Some(accumulator_to_end) => successors(ip, &flipped, self.program.len())
+                        .first()
+                        .and_then(|&next| accumulator_to_end[next])
+                        .map(|remaining| console.accumulator.wrapping_add(remaining)),
+                    None => {
+                        let mut repaired = self.clone();
+                        repaired.program[ip] = flipped;
+                        match repaired.execute_for(REPAIR_STEP_LIMIT) {
+                            Some(Ok((false, accumulator))) => Some(accumulator),
+                            _ => None,
+                        }
+                    }
+                };
+                if let Some(accumulator) = accumulator {
+                    repairs.push(Repair {
+                        index: ip,
+                        original: instruction.clone(),
+                        accumulator,
+                    });
+                }
+            }
+
+            visited[ip] = true;
+            steps += 1;
+            console.executed[ip] = true;
+            console.execute_next_instruction();
+        }
+        repairs
+    }
+}
+
+// Where execution can go after the instruction at ip, with every ip past the end merged into a single end node
+// at len. Jumping before the start of the program goes nowhere.
+fn successors(ip: usize, instruction: &Instruction, len: usize) -> Vec<usize> {
+    let offset_ip = |x: i32| {
+        let next = ip as isize + x as isize;
+        if next < 0 {
+            None
+        } else {
+            Some((next as usize).min(len))
+        }
+    };
+    let mut successors: Vec<usize> = match instruction {
+        Instruction::Jmp(x) => offset_ip(*x).into_iter().collect(),
+        Instruction::Jz(_, x) | Instruction::Jnz(_, x) => {
+            offset_ip(1).into_iter().chain(offset_ip(*x)).collect()
+        }
+        Instruction::Hlt => vec![len],
+        _ => offset_ip(1).into_iter().collect(),
+    };
+    successors.dedup();
+    successors
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Repair {
+    pub index: usize,
+    pub original: Instruction,
+    // The accumulator once the repaired program terminates
+    pub accumulator: i32,
+}
+
+// One node per instruction, plus the end node
+pub struct ControlFlowGraph {
+    successors: Vec<Vec<usize>>,
+    predecessors: Vec<Vec<usize>>,
+}
+
+impl ControlFlowGraph {
+    pub fn new(program: &[Instruction]) -> Self {
+        let successors: Vec<Vec<usize>> = program
+            .iter()
+            .enumerate()
+            .map(|(ip, instruction)| successors(ip, instruction, program.len()))
+            .collect();
+
+        let mut predecessors = vec![Vec::new(); program.len() + 1];
+        for (ip, nexts) in successors.iter().enumerate() {
+            for &next in nexts {
+                predecessors[next].push(ip);
+            }
+        }
+
+        Self {
+            successors,
+            predecessors,
+        }
+    }
+
+    pub fn end(&self) -> usize {
+        self.successors.len()
+    }
+
+    pub fn successors(&self, ip: usize) -> &[usize] {
+        self.successors.get(ip).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn predecessors(&self, ip: usize) -> &[usize] {
+        &self.predecessors[ip]
+    }
+
+    // Walks backwards from the end node, so every node is visited after one of its successors
+    fn reverse_order(&self) -> Vec<usize> {
+        let mut visited = vec![false; self.end() + 1];
+        visited[self.end()] = true;
+        let mut order = vec![self.end()];
+        let mut i = 0;
+        while i < order.len() {
+            for &ip in &self.predecessors[order[i]] {
+                if visited[ip] == false {
+                    visited[ip] = true;
+                    order.push(ip);
+                }
+            }
+            i += 1;
+        }
+        order
+    }
+
+    // Which nodes can reach the end, including the end node itself. With conditional jumps that's only a
+    // possibility, without them it's certain.
+    pub fn terminating(&self) -> Vec<bool> {
+        let mut terminating = vec![false; self.end() + 1];
+        for ip in self.reverse_order() {
+            terminating[ip] = true;
+        }
+        terminating
+    }
+
+    // How much the accumulator changes between each node and the end, or None if it never gets there. Only
+    // known ahead of time for programs which don't touch the accumulator apart from acc and can only take
+    // one path, so jmp, acc, nop, out and hlt. This is what keeps repairs linear, and for anything else it
+    // returns None and repairs has to run every flip instead.
+    fn accumulator_to_end(&self, program: &[Instruction]) -> Option<Vec<Option<i32>>> {
+        let known = program.iter().all(|instruction| {
+            matches!(
+                instruction,
+                Instruction::Jmp(_)
+                    | Instruction::Acc(_)
+                    | Instruction::Nop(_)
+                    | Instruction::Out(_)
+                    | Instruction::Hlt
+            )
+        });
+        if known == false {
+            return None;
+        }
+
+        let mut accumulator = vec![None; self.end() + 1];
+        for ip in self.reverse_order() {
+            accumulator[ip] = Some(match (program.get(ip), self.successors(ip)) {
+                (Some(Instruction::Acc(x)), [next]) => x.wrapping_add(accumulator[*next].unwrap()),
+                (Some(_), [next]) => accumulator[*next].unwrap(),
+                _ => 0,
+            });
+        }
+        Some(accumulator)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::console::test::{boot_code, EXAMPLE_PROGRAM};
+
+    #[test]
+    fn test_control_flow_graph() {
+        let console = boot_code(EXAMPLE_PROGRAM);
+        let graph = ControlFlowGraph::new(&console.program);
+        assert_eq!(graph.end(), 9);
+        assert_eq!(graph.successors(2), [6]);
+        assert_eq!(graph.predecessors(1), [0, 4]);
+        assert_eq!(graph.predecessors(9), [8]);
+
+        let terminating: Vec<usize> = (0..=graph.end())
+            .filter(|&ip| graph.terminating()[ip] == true)
+            .collect();
+        assert_eq!(terminating, [8, 9]);
+
+        // Jumping before the start has no successor, and past the end goes to the end node
+        let graph = ControlFlowGraph::new(&[Instruction::Jmp(-1), Instruction::Jmp(5)]);
+        assert_eq!(graph.successors(0), []);
+        assert_eq!(graph.successors(1), [2]);
+    }
+}
@@ -0,0 +1,363 @@
+use crate::console::{Assembler, Crash, GameConsole, Instruction, InstructionSet, LoopDetector};
+use std::io::{self, Write};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TraceFormat {
+    Csv,
+    JsonLines,
+}
+
+impl std::str::FromStr for TraceFormat {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(Self::Csv),
+            "jsonl" => Ok(Self::JsonLines),
+            _ => Err(format!("invalid format '{}', expected csv or jsonl", s)),
+        }
+    }
+}
+
+const TRACE_CSV_HEADER: &str = "step,ip,instruction,accumulator_before,accumulator_after";
+
+// Enough for any program which doesn't count forever, at about 50 bytes a step
+pub const TRACE_STEP_LIMIT: usize = 1_000_000;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TraceStep {
+    pub step: usize,
+    pub ip: usize,
+    pub instruction: Instruction,
+    pub accumulator_before: i32,
+    pub accumulator_after: i32,
+}
+
+// Why a trace came to an end
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TraceStop {
+    Terminated,
+    // The program was about to go round a loop it can never leave
+    Loop(usize),
+    Crashed(Crash),
+    // The program was still running when the trace reached its step limit
+    StepLimit,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Trace {
+    pub steps: Vec<TraceStep>,
+    pub stop: TraceStop,
+    program_len: usize,
+}
+
+impl Trace {
+    // Flushes the output at the end, so an error writing the tail of a buffered file isn't lost when it's
+    // dropped
+    pub fn write<W: Write>(&self, format: TraceFormat, mut output: W) -> io::Result<()> {
+        let columns: Vec<&str> = TRACE_CSV_HEADER.split(',').collect();
+        if format == TraceFormat::Csv {
+            writeln!(output, "{}", TRACE_CSV_HEADER)?;
+        }
+        for step in &self.steps {
+            let values = [
+                step.step.to_string(),
+                step.ip.to_string(),
+                step.instruction.to_string(),
+                step.accumulator_before.to_string(),
+                step.accumulator_after.to_string(),
+            ];
+            match format {
+                TraceFormat::Csv => writeln!(output, "{}", values.join(","))?,
+                TraceFormat::JsonLines => {
+                    let fields: Vec<String> = columns
+                        .iter()
+                        .zip(values.iter())
+                        .map(|(k, v)| match *k {
+                            "instruction" => format!("\"{}\": \"{}\"", k, v),
+                            _ => format!("\"{}\": {}", k, v),
+                        })
+                        .collect();
+                    writeln!(output, "{{{}}}", fields.join(", "))?;
+                }
+            }
+        }
+        output.flush()
+    }
+
+    // How many times each instruction ran
+    pub fn counts(&self) -> Vec<usize> {
+        let mut counts = vec![0; self.program_len];
+        for step in &self.steps {
+            counts[step.ip] += 1;
+        }
+        counts
+    }
+
+    pub fn summary(&self, hottest: usize) -> TraceSummary {
+        let counts = self.counts();
+        let mut ranked: Vec<(usize, usize)> = counts
+            .iter()
+            .enumerate()
+            .filter(|(_, &count)| count > 0)
+            .map(|(ip, &count)| (ip, count))
+            .collect();
+        ranked.sort_by_key(|&(ip, count)| (std::cmp::Reverse(count), ip));
+        ranked.truncate(hottest);
+
+        let instruction = |ip: usize| {
+            self.steps
+                .iter()
+                .find(|step| step.ip == ip)
+                .map(|step| step.instruction.clone())
+        };
+        TraceSummary {
+            steps: self.steps.len(),
+            accumulator: self.steps.last().map_or(0, |step| step.accumulator_after),
+            covered: counts.iter().filter(|&&count| count > 0).count(),
+            total: self.program_len,
+            hottest: ranked
+                .into_iter()
+                .map(|(ip, count)| (ip, instruction(ip).unwrap(), count))
+                .collect(),
+            loop_entry: match self.stop {
+                TraceStop::Loop(ip) => Some((ip, instruction(ip).unwrap())),
+                _ => None,
+            },
+            crash: match self.stop {
+                TraceStop::Crashed(crash) => Some(crash),
+                _ => None,
+            },
+            step_limit: self.stop == TraceStop::StepLimit,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TraceSummary {
+    pub steps: usize,
+    pub accumulator: i32,
+    pub covered: usize,
+    pub total: usize,
+    // (ip, instruction, times run), most often run first
+    pub hottest: Vec<(usize, Instruction, usize)>,
+    // Where the program would have started going round the same loop forever
+    pub loop_entry: Option<(usize, Instruction)>,
+    pub crash: Option<Crash>,
+    pub step_limit: bool,
+}
+
+impl std::fmt::Display for TraceSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.loop_entry, &self.crash) {
+            (Some((ip, instruction)), _) => writeln!(
+                f,
+                "Infinite loop after {} steps with accumulator {}, entering at {}: {}",
+                self.steps, self.accumulator, ip, instruction
+            )?,
+            (None, Some(crash)) => writeln!(
+                f,
+                "{} after {} steps with accumulator {}",
+                crash, self.steps, self.accumulator
+            )?,
+            (None, None) if self.step_limit == true => writeln!(
+                f,
+                "Still running after {} steps with accumulator {}",
+                self.steps, self.accumulator
+            )?,
+            (None, None) => writeln!(
+                f,
+                "Terminated after {} steps with accumulator {}",
+                self.steps, self.accumulator
+            )?,
+        }
+        writeln!(
+            f,
+            "Coverage: {}/{} instructions ({:.1}%)",
+            self.covered,
+            self.total,
+            100.0 * self.covered as f64 / self.total.max(1) as f64
+        )?;
+        write!(f, "Hottest instructions:")?;
+        let instructions: Vec<String> = self
+            .hottest
+            .iter()
+            .map(|(_, instruction, _)| instruction.to_string())
+            .collect();
+        let width = instructions.iter().map(String::len).max().unwrap_or(0);
+        for ((ip, _, count), instruction) in self.hottest.iter().zip(instructions.iter()) {
+            let runs = if *count == 1 { "run" } else { "runs" };
+            write!(
+                f,
+                "\n{:>7}  {:<width$}{:>8} {}",
+                ip,
+                instruction,
+                count,
+                runs,
+                width = width
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl GameConsole {
+    // The same as execute, but keeps a record of every step along the way. A program which counts forever
+    // never repeats a state, so the trace stops after max_steps steps instead of running out of memory.
+    pub fn trace(&mut self, max_steps: usize) -> Trace {
+        let mut detector = LoopDetector::new(self);
+        let mut steps = Vec::new();
+        let stop = loop {
+            if let Some(crash) = self.crash {
+                break TraceStop::Crashed(crash);
+            }
+            if self.is_terminated() == true {
+                break TraceStop::Terminated;
+            }
+            if detector.repeats(self) == true {
+                break TraceStop::Loop(self.ip);
+            }
+            if steps.len() == max_steps {
+                break TraceStop::StepLimit;
+            }
+
+            let ip = self.ip;
+            let accumulator_before = self.accumulator;
+            self.executed[ip] = true;
+            self.execute_next_instruction();
+            steps.push(TraceStep {
+                step: steps.len() + 1,
+                ip,
+                instruction: self.program[ip].clone(),
+                accumulator_before,
+                accumulator_after: self.accumulator,
+            });
+        };
+
+        Trace {
+            steps,
+            stop,
+            program_len: self.program.len(),
+        }
+    }
+}
+
+// Runs an assembly program for at most max_steps steps, writing a step by step trace to output and
+// returning a summary of the run
+pub fn trace<W: Write>(
+    source: &str,
+    format: TraceFormat,
+    max_steps: usize,
+    output: W,
+) -> Result<TraceSummary, String> {
+    let program = Assembler::new(InstructionSet::extended())
+        .assemble(source)
+        .map_err(|e| e.to_string())?;
+    let trace = GameConsole::new(program).trace(max_steps);
+    trace.write(format, output).map_err(|e| e.to_string())?;
+    Ok(trace.summary(5))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::console::test::{boot_code, COUNTDOWN, EXAMPLE_PROGRAM};
+
+    #[test]
+    fn test_trace() {
+        let mut console = boot_code(EXAMPLE_PROGRAM);
+        let trace = console.trace(100);
+        assert_eq!(trace.stop, TraceStop::Loop(1));
+        assert_eq!(trace.steps.len(), 7);
+        assert_eq!(
+            trace.steps[5],
+            TraceStep {
+                step: 6,
+                ip: 3,
+                instruction: Instruction::Acc(3),
+                accumulator_before: 2,
+                accumulator_after: 5,
+            }
+        );
+        assert_eq!(trace.counts(), [1, 1, 1, 1, 1, 0, 1, 1, 0]);
+
+        let mut csv = Vec::new();
+        trace.write(TraceFormat::Csv, &mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some(TRACE_CSV_HEADER));
+        assert_eq!(lines.next(), Some("1,0,nop +0,0,0"));
+        assert_eq!(lines.count(), 6);
+
+        let mut json = Vec::new();
+        trace.write(TraceFormat::JsonLines, &mut json).unwrap();
+        let json = String::from_utf8(json).unwrap();
+        assert_eq!(
+            json.lines().nth(1),
+            Some(
+                "{\"step\": 2, \"ip\": 1, \"instruction\": \"acc +1\", \
+                 \"accumulator_before\": 0, \"accumulator_after\": 1}"
+            )
+        );
+    }
+
+    #[test]
+    fn test_trace_summary() {
+        let summary = boot_code(EXAMPLE_PROGRAM).trace(100).summary(2);
+        assert_eq!((summary.steps, summary.accumulator), (7, 5));
+        assert_eq!((summary.covered, summary.total), (7, 9));
+        assert_eq!(summary.loop_entry, Some((1, Instruction::Acc(1))));
+        assert_eq!(
+            summary.to_string(),
+            "\
+Infinite loop after 7 steps with accumulator 5, entering at 1: acc +1
+Coverage: 7/9 instructions (77.8%)
+Hottest instructions:
+      0  nop +0       1 run
+      1  acc +1       1 run"
+        );
+
+        let mut output = Vec::new();
+        let summary = trace(COUNTDOWN, TraceFormat::Csv, 100, &mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap().lines().count(), 15);
+        assert_eq!(summary.loop_entry, None);
+        assert_eq!((summary.covered, summary.total), (6, 7));
+        let hottest: Vec<(usize, usize)> = summary
+            .hottest
+            .iter()
+            .map(|&(ip, _, count)| (ip, count))
+            .collect();
+        assert_eq!(hottest, [(1, 3), (2, 3), (3, 3), (4, 3), (0, 1)]);
+        assert_eq!(
+            summary.to_string().lines().skip(2).collect::<Vec<_>>(),
+            [
+                "Hottest instructions:",
+                "      1  out a           3 runs",
+                "      2  add acc a       3 runs",
+                "      3  add a -1        3 runs",
+                "      4  jnz a -3        3 runs",
+                "      0  set a 3         1 run",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_trace_step_limit() {
+        // Counting up never repeats a state, so only the step limit stops it
+        let counter = "count: add a 1\njnz a count";
+        let mut output = Vec::new();
+        let summary = trace(counter, TraceFormat::Csv, 50, &mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap().lines().count(), 51);
+        assert_eq!(summary.step_limit, true);
+        assert_eq!(
+            summary.to_string().lines().next(),
+            Some("Still running after 50 steps with accumulator 0")
+        );
+
+        let program = Assembler::new(InstructionSet::extended())
+            .assemble(counter)
+            .unwrap();
+        let trace = GameConsole::new(program).trace(10);
+        assert_eq!(trace.stop, TraceStop::StepLimit);
+        assert_eq!(trace.steps.len(), 10);
+    }
+}
@@ -32,6 +32,9 @@
     In your expense report, what is the product of the three entries that sum to 2020?
 */
 
+use crate::common::{parse_lines, trim, unsigned, ParseError};
+use crate::Solution;
+
 fn find_sum2_2020(list: &[u32]) -> (u32, u32) {
     for &a in list {
         for &b in list {
@@ -57,11 +60,8 @@ fn find_sum3_2020(list: &[u32]) -> (u32, u32, u32) {
 }
 
 #[aoc_generator(day1)]
-pub fn input_generator(input: &str) -> Vec<u32> {
-    input
-        .lines()
-        .map(|line| line.trim().parse::<u32>().unwrap())
-        .collect()
+pub fn input_generator(input: &str) -> Result<Vec<u32>, ParseError> {
+    parse_lines(1, input, "an unsigned number", trim(unsigned))
 }
 
 #[aoc(day1, part1)]
@@ -80,6 +80,28 @@ pub fn part2(input: &[u32]) -> u32 {
     product
 }
 
+pub struct Day01;
+
+impl Solution for Day01 {
+    const DAY: u32 = 1;
+
+    type Input = Vec<u32>;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -94,7 +116,7 @@ mod test {
 
     #[test]
     fn test_find_sum2_2020() {
-        let expenses = input_generator(EXAMPLE_INPUT);
+        let expenses = input_generator(EXAMPLE_INPUT).unwrap();
         let (a, b) = find_sum2_2020(&expenses);
         assert_eq!(a, 1721);
         assert_eq!(b, 299);
@@ -104,7 +126,7 @@ mod test {
 
     #[test]
     fn test_find_sum3_2020() {
-        let expenses = input_generator(EXAMPLE_INPUT);
+        let expenses = input_generator(EXAMPLE_INPUT).unwrap();
         let (a, b, c) = find_sum3_2020(&expenses);
         assert_eq!(a, 979);
         assert_eq!(b, 366);
@@ -34,7 +34,8 @@
     How many passwords are valid according to the new interpretation of the policies?
 */
 
-use crate::common::{to_owned, trim_start, unsigned};
+use crate::common::{parse_input, to_owned, trim_start, unsigned, ParseError};
+use crate::Solution;
 use nom::{
     bytes::complete::tag,
     character::{
@@ -123,8 +124,13 @@ fn count_valid_passwords2(entries: &[Entry]) -> usize {
 }
 
 #[aoc_generator(day2)]
-pub fn input_generator(input: &str) -> Vec<Entry> {
-    many1(Entry::parser)(input).unwrap().1
+pub fn input_generator(input: &str) -> Result<Vec<Entry>, ParseError> {
+    parse_input(
+        2,
+        input,
+        "a password entry like '1-3 a: abcde'",
+        many1(Entry::parser),
+    )
 }
 
 #[aoc(day2, part1)]
@@ -141,6 +147,28 @@ pub fn part2(input: &[Entry]) -> usize {
     valid_count
 }
 
+pub struct Day02;
+
+impl Solution for Day02 {
+    const DAY: u32 = 2;
+
+    type Input = Vec<Entry>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -152,28 +180,28 @@ mod test {
 
     #[test]
     fn test_is_password_valid_1() {
-        let entries = input_generator(EXAMPLE_INPUT);
+        let entries = input_generator(EXAMPLE_INPUT).unwrap();
         let valid: Vec<bool> = entries.iter().map(Entry::is_password_valid1).collect();
         assert_eq!(valid, [true, false, true]);
     }
 
     #[test]
     fn test_count_valid_passwords1() {
-        let entries = input_generator(EXAMPLE_INPUT);
+        let entries = input_generator(EXAMPLE_INPUT).unwrap();
         let valid_count = count_valid_passwords1(&entries);
         assert_eq!(valid_count, 2);
     }
 
     #[test]
     fn test_is_password_valid_2() {
-        let entries = input_generator(EXAMPLE_INPUT);
+        let entries = input_generator(EXAMPLE_INPUT).unwrap();
         let valid: Vec<bool> = entries.iter().map(Entry::is_password_valid2).collect();
         assert_eq!(valid, [true, false, false]);
     }
 
     #[test]
     fn test_count_valid_passwords2() {
-        let entries = input_generator(EXAMPLE_INPUT);
+        let entries = input_generator(EXAMPLE_INPUT).unwrap();
         let valid_count = count_valid_passwords2(&entries);
         assert_eq!(valid_count, 1);
     }
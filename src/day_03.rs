@@ -66,23 +66,44 @@
     What do you get if you multiply together the number of trees encountered on each of the listed slopes?
 */
 
-use crate::common::{modulo, Point, TileSet};
+use crate::common::{parse_input, Grid, ParseError, Point, TileChar};
+use crate::Solution;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Square {
+    Open,
+    Tree,
+}
+
+impl TileChar for Square {
+    fn to_char(&self) -> char {
+        match self {
+            Self::Open => '.',
+            Self::Tree => '#',
+        }
+    }
+
+    fn from_char(c: char) -> Option<Self> {
+        Some(match c {
+            '.' => Self::Open,
+            '#' => Self::Tree,
+            _ => return None,
+        })
+    }
+
+    fn all_chars() -> Vec<char> {
+        vec!['.', '#']
+    }
+}
 
 pub struct Map {
-    tileset: TileSet,
-    x_max: i32,
-    y_max: i32,
+    grid: Grid<Square>,
 }
 
 impl Map {
     fn from_string(input: &str) -> Self {
-        let tileset = TileSet::from_string(input, '#');
-        let range = tileset.get_range().unwrap();
-
         Self {
-            tileset,
-            x_max: range.0 .1,
-            y_max: range.1 .1,
+            grid: Grid::from_string(input),
         }
     }
 
@@ -90,12 +111,13 @@ impl Map {
         let mut p = Point::origin();
         let mut tree_count = 0;
 
-        while p.y <= self.y_max {
-            if self.tileset.contains(&p) {
+        // The map repeats to the right, so wrap around horizontally until reaching the bottom
+        while p.y < self.grid.height() as i32 {
+            if self.grid.get_wrapping(&p) == Some(&Square::Tree) {
                 tree_count += 1;
             }
 
-            p.x = modulo(p.x + x_delta, self.x_max + 1);
+            p.x += x_delta;
             p.y += y_delta;
         }
 
@@ -112,8 +134,14 @@ impl Map {
 }
 
 #[aoc_generator(day3)]
-pub fn input_generator(input: &str) -> Map {
-    Map::from_string(input)
+pub fn input_generator(input: &str) -> Result<Map, ParseError> {
+    let grid = parse_input(
+        3,
+        input,
+        "a rectangle of open squares '.' and trees '#'",
+        Grid::parser,
+    )?;
+    Ok(Map { grid })
 }
 
 #[aoc(day3, part1)]
@@ -130,6 +158,28 @@ pub fn part2(input: &Map) -> usize {
     tree_product
 }
 
+pub struct Day03;
+
+impl Solution for Day03 {
+    const DAY: u32 = 3;
+
+    type Input = Map;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -109,10 +109,11 @@
     Count the number of valid passports - those that have all required fields and valid values. Continue to treat cid as optional. In your batch file, how many passports are valid?
 */
 
-use crate::common::{to_owned, trim_start, unsigned};
+use crate::common::{parse_input, to_owned, trim_start, unsigned, ParseError};
+use crate::Solution;
 use nom::{
     branch::alt,
-    bytes::complete::{is_not, take_while_m_n},
+    bytes::complete::{is_not, tag, take_while_m_n},
     character::{
         complete::{alpha1, char, line_ending, space1},
         is_digit, is_hex_digit,
@@ -130,8 +131,18 @@ struct Field {
 
 impl Field {
     fn parser(input: &str) -> IResult<&str, Self> {
+        let key_parser = alt((
+            tag("byr"),
+            tag("iyr"),
+            tag("eyr"),
+            tag("hgt"),
+            tag("hcl"),
+            tag("ecl"),
+            tag("pid"),
+            tag("cid"),
+        ));
         let (input, (key, value)) =
-            separated_pair(to_owned(alpha1), char(':'), to_owned(is_not(" \t\r\n")))(input)?;
+            separated_pair(to_owned(key_parser), char(':'), to_owned(is_not(" \t\r\n")))(input)?;
 
         Ok((input, Self { key, value }))
     }
@@ -178,7 +189,7 @@ impl Passport {
                 "ecl" => pass.eye_color = Some(field.value),
                 "pid" => pass.pass_id = Some(field.value),
                 "cid" => pass.country_id = Some(field.value),
-                _ => panic!("Unknown field found: {}", field.value),
+                _ => unreachable!(),
             }
         }
 
@@ -301,8 +312,13 @@ impl Passport {
 }
 
 #[aoc_generator(day4)]
-pub fn input_generator(input: &str) -> Vec<Passport> {
-    many1(Passport::parser)(input).unwrap().1
+pub fn input_generator(input: &str) -> Result<Vec<Passport>, ParseError> {
+    parse_input(
+        4,
+        input,
+        "a passport field (byr, iyr, eyr, hgt, hcl, ecl, pid or cid) like 'byr:1937'",
+        many1(Passport::parser),
+    )
 }
 
 #[aoc(day4, part1)]
@@ -319,6 +335,28 @@ pub fn part2(input: &[Passport]) -> usize {
     count
 }
 
+pub struct Day04;
+
+impl Solution for Day04 {
+    const DAY: u32 = 4;
+
+    type Input = Vec<Passport>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -369,7 +407,7 @@ iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719";
 
     #[test]
     fn test_passport_parser() {
-        let passports = input_generator(EXAMPLE_INPUT);
+        let passports = input_generator(EXAMPLE_INPUT).unwrap();
         assert_eq!(
             passports,
             [
@@ -417,20 +455,28 @@ iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719";
         );
     }
 
+    #[test]
+    fn test_passport_parser_invalid() {
+        let input = "ecl:gry pid:860033327\nbyr:1937 xyz:147 hgt:183cm";
+        let error = input_generator(input).err().unwrap();
+        assert_eq!((error.day, error.line, error.column), (4, 2, 10));
+        assert_eq!(error.found, "xyz:147 hgt:183cm");
+    }
+
     #[test]
     fn test_passport_is_valid1() {
-        let passports = input_generator(EXAMPLE_INPUT);
+        let passports = input_generator(EXAMPLE_INPUT).unwrap();
         let valid: Vec<bool> = passports.iter().map(Passport::is_valid1).collect();
         assert_eq!(valid, [true, false, true, false]);
     }
 
     #[test]
     fn test_passport_is_valid2() {
-        let passports = input_generator(EXAMPLE_INPUT_INVALID);
+        let passports = input_generator(EXAMPLE_INPUT_INVALID).unwrap();
         let valid: Vec<bool> = passports.iter().map(Passport::is_valid2).collect();
         assert_eq!(valid, [false, false, false, false]);
 
-        let passports = input_generator(EXAMPLE_INPUT_VALID);
+        let passports = input_generator(EXAMPLE_INPUT_VALID).unwrap();
         let valid: Vec<bool> = passports.iter().map(Passport::is_valid2).collect();
         assert_eq!(valid, [true, true, true, true]);
     }
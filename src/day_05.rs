@@ -47,15 +47,32 @@
     What is the ID of your seat?
 */
 
+use crate::common::{parse_input, to_owned, trim_start, ParseError};
+use crate::Solution;
+use nom::{
+    character::complete::one_of,
+    combinator::recognize,
+    multi::{count, many1},
+    sequence::pair,
+    IResult,
+};
+
 pub struct BoardingPass {
     seat_bsp: String,
 }
 
 impl BoardingPass {
     fn from_string(input: &str) -> Self {
-        Self {
-            seat_bsp: input[..10].to_owned(),
-        }
+        Self::parser(input).unwrap().1
+    }
+
+    fn parser(input: &str) -> IResult<&str, Self> {
+        let (input, seat_bsp) = trim_start(to_owned(recognize(pair(
+            count(one_of("FB"), 7),
+            count(one_of("LR"), 3),
+        ))))(input)?;
+
+        Ok((input, Self { seat_bsp }))
     }
 
     fn row(&self) -> u32 {
@@ -107,8 +124,13 @@ fn find_my_seat_id(passes: &[BoardingPass]) -> u32 {
 }
 
 #[aoc_generator(day5)]
-pub fn input_generator(input: &str) -> Vec<BoardingPass> {
-    input.lines().map(BoardingPass::from_string).collect()
+pub fn input_generator(input: &str) -> Result<Vec<BoardingPass>, ParseError> {
+    parse_input(
+        5,
+        input,
+        "a boarding pass like 'FBFBBFFRLR'",
+        many1(BoardingPass::parser),
+    )
 }
 
 #[aoc(day5, part1)]
@@ -125,6 +147,28 @@ pub fn part2(input: &[BoardingPass]) -> u32 {
     my_seat_id
 }
 
+pub struct Day05;
+
+impl Solution for Day05 {
+    const DAY: u32 = 5;
+
+    type Input = Vec<BoardingPass>;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
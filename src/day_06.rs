@@ -73,7 +73,8 @@
     For each group, count the number of questions to which everyone answered "yes". What is the sum of those counts?
 */
 
-use crate::common::{to_owned, trim_start};
+use crate::common::{parse_input, to_owned, trim_start, ParseError};
+use crate::Solution;
 use nom::{
     character::complete::{alpha1, line_ending},
     multi::{many1, separated_list1},
@@ -114,8 +115,13 @@ impl Group {
 }
 
 #[aoc_generator(day6)]
-pub fn input_generator(input: &str) -> Vec<Group> {
-    many1(Group::parser)(input).unwrap().1
+pub fn input_generator(input: &str) -> Result<Vec<Group>, ParseError> {
+    parse_input(
+        6,
+        input,
+        "a group of answers (letters a-z)",
+        many1(Group::parser),
+    )
 }
 
 #[aoc(day6, part1)]
@@ -132,6 +138,28 @@ pub fn part2(input: &[Group]) -> usize {
     count_sum
 }
 
+pub struct Day06;
+
+impl Solution for Day06 {
+    const DAY: u32 = 6;
+
+    type Input = Vec<Group>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -155,7 +183,7 @@ b";
 
     #[test]
     fn test_input_generator() {
-        let groups = input_generator(EXAMPLE_INPUT);
+        let groups = input_generator(EXAMPLE_INPUT).unwrap();
         assert_eq!(
             groups,
             [
@@ -180,7 +208,7 @@ b";
 
     #[test]
     fn test_group_answers1() {
-        let groups = input_generator(EXAMPLE_INPUT);
+        let groups = input_generator(EXAMPLE_INPUT).unwrap();
         let answers: Vec<String> = groups.iter().map(Group::answers1).collect();
         assert_eq!(answers, ["abc", "abc", "abc", "a", "b"]);
         let answers_count: Vec<usize> = answers.iter().map(|s| s.len()).collect();
@@ -189,7 +217,7 @@ b";
 
     #[test]
     fn test_group_answers2() {
-        let groups = input_generator(EXAMPLE_INPUT);
+        let groups = input_generator(EXAMPLE_INPUT).unwrap();
         let answers: Vec<String> = groups.iter().map(Group::answers2).collect();
         assert_eq!(answers, ["abc", "", "a", "a", "b"]);
         let answers_count: Vec<usize> = answers.iter().map(|s| s.len()).collect();
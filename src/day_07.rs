@@ -56,7 +56,8 @@
     How many individual bags are required inside your single shiny gold bag?
 */
 
-use crate::common::{to_owned, trim_start, unsigned};
+use crate::common::{parse_input, to_owned, trim_start, unsigned, ParseError};
+use crate::Solution;
 use nom::{
     branch::alt,
     bytes::complete::tag,
@@ -129,9 +130,7 @@ impl BagCookBook {
         let mut reverse_lookup: HashMap<String, Vec<String>> = HashMap::new();
         for (k, v) in self.recipes_map.iter() {
             for ingredient in v {
-                let e = reverse_lookup
-                    .entry(ingredient.color.clone())
-                    .or_insert_with(Vec::new);
+                let e = reverse_lookup.entry(ingredient.color.clone()).or_default();
                 e.push(k.clone());
             }
         }
@@ -187,8 +186,13 @@ impl BagCookBook {
 }
 
 #[aoc_generator(day7)]
-pub fn input_generator(input: &str) -> BagCookBook {
-    BagCookBook::parser(input).unwrap().1
+pub fn input_generator(input: &str) -> Result<BagCookBook, ParseError> {
+    parse_input(
+        7,
+        input,
+        "a bag rule like 'light red bags contain 1 bright white bag.'",
+        BagCookBook::parser,
+    )
 }
 
 #[aoc(day7, part1)]
@@ -205,6 +209,28 @@ pub fn part2(input: &BagCookBook) -> u32 {
     count
 }
 
+pub struct Day07;
+
+impl Solution for Day07 {
+    const DAY: u32 = 7;
+
+    type Input = BagCookBook;
+    type Answer1 = usize;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -231,7 +257,7 @@ dark violet bags contain no other bags.";
 
     #[test]
     fn test_input_generator() {
-        let cookbook = input_generator(EXAMPLE_INPUT1);
+        let cookbook = input_generator(EXAMPLE_INPUT1).unwrap();
         let expected: Vec<(String, Vec<Ingredient>)> = vec![
             (
                 "light red".into(),
@@ -326,7 +352,7 @@ dark violet bags contain no other bags.";
 
     #[test]
     fn test_gen_reverse_lookup() {
-        let cookbook = input_generator(EXAMPLE_INPUT1);
+        let cookbook = input_generator(EXAMPLE_INPUT1).unwrap();
         let reverse_lookup = cookbook.gen_reverse_lookup();
         let expected: Vec<(String, Vec<String>)> = vec![
             (
@@ -361,16 +387,16 @@ dark violet bags contain no other bags.";
 
     #[test]
     fn test_count_contains_gold() {
-        let cookbook = input_generator(EXAMPLE_INPUT1);
+        let cookbook = input_generator(EXAMPLE_INPUT1).unwrap();
         assert_eq!(cookbook.count_contains_gold(), 4);
     }
 
     #[test]
     fn test_count_bags_in_gold() {
-        let cookbook = input_generator(EXAMPLE_INPUT1);
+        let cookbook = input_generator(EXAMPLE_INPUT1).unwrap();
         assert_eq!(cookbook.count_bags_in_gold(), 32);
 
-        let cookbook = input_generator(EXAMPLE_INPUT2);
+        let cookbook = input_generator(EXAMPLE_INPUT2).unwrap();
         assert_eq!(cookbook.count_bags_in_gold(), 126);
     }
 }
@@ -2,136 +2,35 @@
 
 */
 
-use crate::common::{signed, trim_start};
-use nom::{
-    character::complete::{alpha1, char},
-    multi::many1,
-    sequence::separated_pair,
-    IResult,
-};
-
-#[derive(Clone)]
-pub enum Instruction {
-    Jmp(i32),
-    Acc(i32),
-    Nop(i32),
-}
-
-impl Instruction {
-    fn parser(input: &str) -> IResult<&str, Self> {
-        let (input, (op, arg)) = separated_pair(trim_start(alpha1), char(' '), signed)(input)?;
-
-        let instruction = match op {
-            "jmp" => Self::Jmp(arg),
-            "acc" => Self::Acc(arg),
-            "nop" => Self::Nop(arg),
-            _ => panic!("Unknown operation"),
-        };
-
-        Ok((input, instruction))
+use crate::common::{parse_input, ParseError};
+use crate::console::{GameConsole, Repair};
+use crate::Solution;
+
+// The first repair the console would reach, which is the one the puzzle expects
+fn fix_corruption(console: &GameConsole) -> Repair {
+    if console.is_terminated() == true {
+        panic!("Program terminated normally!");
     }
-
-    fn transform(&self) -> Self {
-        match *self {
-            Self::Jmp(x) => Self::Nop(x),
-            Self::Acc(x) => Self::Acc(x),
-            Self::Nop(x) => Self::Jmp(x),
-        }
-    }
-}
-
-#[derive(Clone)]
-pub struct GameConsole {
-    program: Vec<Instruction>,
-    ip: usize,
-    accumulator: i32,
-    executed: Vec<bool>,
-}
-
-impl GameConsole {
-    fn parser(input: &str) -> IResult<&str, Self> {
-        let (input, program) = many1(Instruction::parser)(input)?;
-
-        let program_len = program.len();
-        Ok((
-            input,
-            Self {
-                program,
-                ip: 0,
-                accumulator: 0,
-                executed: vec![false; program_len],
-            },
-        ))
-    }
-
-    fn execute_next_instruction(&mut self) {
-        match self.program[self.ip] {
-            Instruction::Jmp(x) => {
-                if x >= 0 {
-                    self.ip += x as usize;
-                } else {
-                    self.ip -= (-x) as usize;
-                }
-            }
-            Instruction::Acc(x) => {
-                self.accumulator += x;
-                self.ip += 1;
-            }
-            Instruction::Nop(_) => self.ip += 1,
-        }
-    }
-
-    fn execute(&mut self) -> (bool, i32) {
-        loop {
-            if self.ip >= self.program.len() {
-                return (false, self.accumulator);
-            }
-            if self.executed[self.ip] == true {
-                return (true, self.accumulator);
-            }
-
-            self.executed[self.ip] = true;
-            self.execute_next_instruction();
-        }
-    }
-
-    fn fix_corruption(&mut self) -> i32 {
-        loop {
-            if self.ip >= self.program.len() {
-                panic!("Program terminated normally!");
-            }
-            if self.executed[self.ip] == true {
-                panic!("No corrupted instruction found!");
-            }
-
-            if matches!(
-                self.program[self.ip],
-                Instruction::Jmp(_) | Instruction::Nop(_)
-            ) {
-                let mut new_console = self.clone();
-                new_console.program[self.ip] = self.program[self.ip].transform();
-
-                let (is_infinite_loop, acc_value) = new_console.execute();
-                if is_infinite_loop == false {
-                    return acc_value;
-                }
-            }
-
-            self.executed[self.ip] = true;
-            self.execute_next_instruction();
-        }
+    match console.repairs().into_iter().next() {
+        Some(repair) => repair,
+        None => panic!("No corrupted instruction found!"),
     }
 }
 
 #[aoc_generator(day8)]
-pub fn input_generator(input: &str) -> GameConsole {
-    GameConsole::parser(input).unwrap().1
+pub fn input_generator(input: &str) -> Result<GameConsole, ParseError> {
+    parse_input(
+        8,
+        input,
+        "an instruction (acc, jmp or nop) with a signed argument",
+        GameConsole::parser,
+    )
 }
 
 #[aoc(day8, part1)]
 pub fn part1(input: &GameConsole) -> i32 {
     let mut console = input.clone(); // aoc-runner can only give an immutable reference, so to get mutable I have to clone
-    let (is_infinite_loop, acc_value) = console.execute();
+    let (is_infinite_loop, acc_value) = console.execute().expect("Boot code crashed!");
     assert_eq!(is_infinite_loop, true);
     assert_eq!(acc_value, 1939);
     acc_value
@@ -139,15 +38,37 @@ pub fn part1(input: &GameConsole) -> i32 {
 
 #[aoc(day8, part2)]
 pub fn part2(input: &GameConsole) -> i32 {
-    let mut console = input.clone(); // aoc-runner can only give an immutable reference, so to get mutable I have to clone
-    let acc_value = console.fix_corruption();
+    let acc_value = fix_corruption(input).accumulator;
     assert_eq!(acc_value, 2212);
     acc_value
 }
 
+pub struct Day08;
+
+impl Solution for Day08 {
+    const DAY: u32 = 8;
+
+    type Input = GameConsole;
+    type Answer1 = i32;
+    type Answer2 = i32;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::console::{Assembler, Instruction, InstructionSet};
 
     static EXAMPLE_INPUT: &str = "\
 nop +0
@@ -160,18 +81,45 @@ acc +1
 jmp -4
 acc +6";
 
+    #[test]
+    fn test_input_generator_invalid() {
+        let error = input_generator("nop +0\nacc +1\nmul +2\njmp -2")
+            .err()
+            .unwrap();
+        assert_eq!((error.day, error.line, error.column), (8, 3, 1));
+        assert_eq!(error.found, "mul +2");
+
+        let error = input_generator("nop +0\nacc x1").err().unwrap();
+        assert_eq!((error.day, error.line, error.column), (8, 2, 1));
+    }
+
+    #[test]
+    fn test_input_generator() {
+        // Boot code is also valid assembly
+        let console = input_generator(EXAMPLE_INPUT).unwrap();
+        let assembler = Assembler::new(InstructionSet::boot());
+        assert_eq!(
+            console.program(),
+            assembler.assemble(EXAMPLE_INPUT).unwrap()
+        );
+        assert_eq!(console.program()[7], Instruction::Jmp(-4));
+    }
+
     #[test]
     fn test_execute() {
-        let mut console = input_generator(EXAMPLE_INPUT);
-        let (is_infinite_loop, acc_value) = console.execute();
+        let mut console = input_generator(EXAMPLE_INPUT).unwrap();
+        let (is_infinite_loop, acc_value) = console.execute().unwrap();
         assert_eq!(is_infinite_loop, true);
         assert_eq!(acc_value, 5);
     }
 
     #[test]
     fn test_fix_corruption() {
-        let mut console = input_generator(EXAMPLE_INPUT);
-        let acc_value = console.fix_corruption();
-        assert_eq!(acc_value, 8);
+        let console = input_generator(EXAMPLE_INPUT).unwrap();
+        let repair = fix_corruption(&console);
+        assert_eq!(repair.index, 7);
+        assert_eq!(repair.original, Instruction::Jmp(-4));
+        assert_eq!(repair.accumulator, 8);
+        assert_eq!(console.repairs(), vec![repair]);
     }
 }
@@ -77,6 +77,8 @@
     What is the encryption weakness in your XMAS-encrypted list of numbers?
 */
 
+use crate::common::{parse_lines, unsigned, ParseError};
+use crate::Solution;
 use std::cmp::Ordering;
 
 fn sum_exists(list: &[u64], x: u64) -> bool {
@@ -125,11 +127,8 @@ fn find_contiguous_sum(list: &[u64], target: u64) -> u64 {
 }
 
 #[aoc_generator(day9)]
-pub fn input_generator(input: &str) -> Vec<u64> {
-    input
-        .lines()
-        .map(|line| line.parse::<u64>().unwrap())
-        .collect()
+pub fn input_generator(input: &str) -> Result<Vec<u64>, ParseError> {
+    parse_lines(9, input, "an unsigned number", unsigned)
 }
 
 #[aoc(day9, part1)]
@@ -147,6 +146,28 @@ pub fn part2(input: &[u64]) -> u64 {
     encryption_weakness
 }
 
+pub struct Day09;
+
+impl Solution for Day09 {
+    const DAY: u32 = 9;
+
+    type Input = Vec<u64>;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -175,14 +196,14 @@ mod test {
 
     #[test]
     fn test_find_first_non_sum() {
-        let values = input_generator(EXAMPLE_INPUT);
+        let values = input_generator(EXAMPLE_INPUT).unwrap();
         let non_sum = find_first_non_sum(&values, 5);
         assert_eq!(non_sum, 127);
     }
 
     #[test]
     fn test_find_contiguous_sum() {
-        let values = input_generator(EXAMPLE_INPUT);
+        let values = input_generator(EXAMPLE_INPUT).unwrap();
         let non_sum = find_first_non_sum(&values, 5);
         let encryption_weakness = find_contiguous_sum(&values, non_sum);
         assert_eq!(encryption_weakness, 62);
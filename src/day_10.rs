@@ -133,6 +133,9 @@
     What is the total number of distinct ways you can arrange the adapters to connect the charging outlet to your device?
 */
 
+use crate::common::{parse_lines, unsigned, ParseError};
+use crate::Solution;
+
 fn find_jolt_differences(values: &[u32]) -> (usize, usize, usize) {
     let mut sorted_values = values.to_vec();
     sorted_values.push(0); // Charging outlet starts at 0
@@ -180,11 +183,8 @@ fn count_arrangements(values: &[u32]) -> usize {
 }
 
 #[aoc_generator(day10)]
-pub fn input_generator(input: &str) -> Vec<u32> {
-    input
-        .lines()
-        .map(|line| line.parse::<u32>().unwrap())
-        .collect()
+pub fn input_generator(input: &str) -> Result<Vec<u32>, ParseError> {
+    parse_lines(10, input, "an unsigned number", unsigned)
 }
 
 #[aoc(day10, part1)]
@@ -202,6 +202,28 @@ pub fn part2(input: &[u32]) -> usize {
     arrangements
 }
 
+pub struct Day10;
+
+impl Solution for Day10 {
+    const DAY: u32 = 10;
+
+    type Input = Vec<u32>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -254,12 +276,12 @@ mod test {
 
     #[test]
     fn test_find_jolt_differences() {
-        let input = input_generator(EXAMPLE_INPUT1);
+        let input = input_generator(EXAMPLE_INPUT1).unwrap();
         let (one, _two, three) = find_jolt_differences(&input);
         assert_eq!(one, 7);
         assert_eq!(three, 5);
 
-        let input = input_generator(EXAMPLE_INPUT2);
+        let input = input_generator(EXAMPLE_INPUT2).unwrap();
         let (one, _two, three) = find_jolt_differences(&input);
         assert_eq!(one, 22);
         assert_eq!(three, 10);
@@ -267,11 +289,11 @@ mod test {
 
     #[test]
     fn test_count_arrangements() {
-        let input = input_generator(EXAMPLE_INPUT1);
+        let input = input_generator(EXAMPLE_INPUT1).unwrap();
         let arrangements = count_arrangements(&input);
         assert_eq!(arrangements, 8);
 
-        let input = input_generator(EXAMPLE_INPUT2);
+        let input = input_generator(EXAMPLE_INPUT2).unwrap();
         let arrangements = count_arrangements(&input);
         assert_eq!(arrangements, 19208);
     }
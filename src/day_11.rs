@@ -203,25 +203,22 @@
     Given the new visibility method and the rule change for occupied seats becoming empty, once equilibrium is reached, how many seats end up occupied?
 */
 
-use crate::common::{Mode, Point};
-
-#[derive(Clone, Copy, PartialEq)]
+use crate::common::{
+    parse_input, trim_start, Cycle, Frame, FrameWriter, Grid, GridAutomaton, GridTopology,
+    LifeLike, LifeRule, Neighborhood, ParseError, Point, Rgb, Rule, TileChar,
+};
+use crate::Solution;
+use std::io;
+
+#[derive(Clone, Copy, Default, Hash, PartialEq)]
 enum Tile {
+    #[default]
     Floor,
     Empty,
     Occupied,
 }
 
-impl Tile {
-    fn from_char(c: char) -> Self {
-        match c {
-            '.' => Self::Floor,
-            'L' => Self::Empty,
-            '#' => Self::Occupied,
-            _ => panic!("Unknown character: {}", c),
-        }
-    }
-
+impl TileChar for Tile {
     fn to_char(&self) -> char {
         match self {
             Self::Floor => '.',
@@ -229,172 +226,308 @@ impl Tile {
             Self::Occupied => '#',
         }
     }
-}
 
-fn get_tile_flat(p: &Point, x_size: usize, y_size: usize, tiles: &[Tile]) -> Tile {
-    if p.x >= 0 && p.x < x_size as i32 && p.y >= 0 && p.y < y_size as i32 {
-        let idx = point_to_idx(p, x_size);
-        tiles[idx]
-    } else {
-        Tile::Floor
+    fn from_char(c: char) -> Option<Self> {
+        Some(match c {
+            '.' => Self::Floor,
+            'L' => Self::Empty,
+            '#' => Self::Occupied,
+            _ => return None,
+        })
+    }
+
+    fn all_chars() -> Vec<char> {
+        vec!['.', 'L', '#']
     }
-}
 
-fn point_to_idx(p: &Point, x_size: usize) -> usize {
-    p.y as usize * x_size + p.x as usize
+    fn color(&self) -> Rgb {
+        match self {
+            Self::Floor => [40, 40, 40],
+            Self::Empty => [60, 160, 60],
+            Self::Occupied => [200, 50, 50],
+        }
+    }
 }
 
-fn idx_to_point(idx: usize, x_size: usize) -> Point {
-    let x = (idx % x_size) as i32;
-    let y = (idx / x_size) as i32;
-    Point { x, y }
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Neighbors {
+    // The eight tiles touching each seat
+    Direct,
+    // The seats seen looking out in each of the eight directions
+    LineOfSight,
 }
 
+// How people choose their seats. Empty seats are taken when no neighbors are occupied, and occupied seats
+// are left once at least tolerance neighbors are occupied, unless some other life-like rule is given.
 #[derive(Clone)]
-pub struct WaitingArea {
-    tiles: Vec<Tile>,
-    x_size: usize,
-    y_size: usize,
-    active: Vec<Point>,
-    neighbors_direct: Vec<Vec<Point>>,
-    neighbors_visible: Vec<Vec<Point>>,
+struct SeatingRules {
+    neighbors: Neighbors,
+    radius: Option<usize>, // How far line of sight reaches, or None for no limit
+    rule: LifeRule,        // Occupied seats are live
+    blocks_sight: Vec<Tile>, // Tiles which can be seen but hide anything behind them
 }
 
-impl WaitingArea {
-    fn from_string(input: &str) -> Self {
-        let mut tiles = Vec::new();
-        let mut x_size: Option<usize> = None;
-        for line in input.trim().lines() {
-            tiles.extend(line.chars().map(Tile::from_char));
-
-            if let Some(x) = x_size {
-                assert_eq!(x, line.len());
-            } else {
-                x_size = Some(line.len());
-            }
+impl SeatingRules {
+    fn direct() -> Self {
+        Self {
+            neighbors: Neighbors::Direct,
+            radius: None,
+            rule: Self::tolerance_rule(4),
+            blocks_sight: vec![Tile::Empty, Tile::Occupied],
         }
-        let x_size = x_size.unwrap();
-        let y_size = tiles.len() / x_size;
+    }
 
-        // Build list of which tiles are active
-        let active = tiles
-            .iter()
-            .enumerate()
-            .filter(|(_i, seat)| seat == &&Tile::Empty)
-            .map(|(i, _seat)| {
-                let x = (i % x_size) as i32;
-                let y = (i / x_size) as i32;
-                Point { x, y }
-            })
-            .collect();
+    fn line_of_sight() -> Self {
+        Self {
+            neighbors: Neighbors::LineOfSight,
+            rule: Self::tolerance_rule(5),
+            ..Self::direct()
+        }
+    }
 
-        // Build index of non-floor neighbors directly adjacent to each tile
-        let neighbors_direct = (0..tiles.len())
-            .map(|i| {
-                idx_to_point(i, x_size)
-                    .adjacents()
-                    .filter(|adj| get_tile_flat(adj, x_size, y_size, &tiles) == Tile::Empty)
-                    .collect()
-            })
-            .collect();
+    // B0/S0123 is part 1 and B0/S01234L is part 2. Only the default and line of sight neighborhoods make
+    // sense for seats.
+    fn from_life_like(life_like: &LifeLike) -> Option<Self> {
+        let rules = match life_like.neighborhood {
+            Neighborhood::Moore => Self::direct(),
+            Neighborhood::Sight => Self::line_of_sight(),
+            _ => return None,
+        };
+        Some(Self {
+            rule: life_like.rule.clone(),
+            ..rules
+        })
+    }
 
-        // Build index of non-floor neighbors visible from each tile
-        let mut neighbors_visible = Vec::new();
-        for i in 0..tiles.len() {
-            let mut visible_dirs: Vec<Point> = Vec::new();
-            for dir in Point::origin().adjacents() {
-                let mut walk = idx_to_point(i, x_size);
-                while walk.in_range(((0, x_size as i32 + 1), (0, y_size as i32 + 1))) {
-                    walk += dir;
-                    if get_tile_flat(&walk, x_size, y_size, &tiles) == Tile::Empty {
-                        visible_dirs.push(walk);
-                        break;
-                    }
-                }
-                // If we left the waiting area bounds, nothing is visible so don't save anything
-            }
-            neighbors_visible.push(visible_dirs);
+    fn with_radius(self, radius: usize) -> Self {
+        Self {
+            radius: Some(radius),
+            ..self
         }
+    }
 
+    fn with_tolerance(self, tolerance: usize) -> Self {
         Self {
-            tiles,
-            x_size,
-            y_size,
-            active,
-            neighbors_direct,
-            neighbors_visible,
+            rule: Self::tolerance_rule(tolerance),
+            ..self
         }
     }
 
-    fn get_tile(&self, p: &Point) -> Tile {
-        get_tile_flat(p, self.x_size, self.y_size, &self.tiles)
+    fn with_blocks_sight(self, blocks_sight: &[Tile]) -> Self {
+        Self {
+            blocks_sight: blocks_sight.to_vec(),
+            ..self
+        }
     }
 
-    fn set_tile(&mut self, p: &Point, tile: Tile) {
-        let idx = point_to_idx(p, self.x_size);
-        self.tiles[idx] = tile;
+    fn blocks(&self, tile: Tile) -> bool {
+        self.blocks_sight.contains(&tile)
     }
 
-    fn count_neighbors_direct(&self, p: &Point) -> usize {
-        let idx = point_to_idx(p, self.x_size);
-        self.neighbors_direct[idx]
-            .iter()
-            .filter(|adj| self.get_tile(&adj) == Tile::Occupied)
-            .count()
+    fn reach(&self) -> usize {
+        match self.neighbors {
+            Neighbors::Direct => 1,
+            Neighbors::LineOfSight => self.radius.unwrap_or(usize::MAX),
+        }
     }
 
-    fn count_neighbors_visible(&self, p: &Point) -> usize {
-        let idx = point_to_idx(p, self.x_size);
-        self.neighbors_visible[idx]
-            .iter()
-            .filter(|adj| self.get_tile(&adj) == Tile::Occupied)
-            .count()
+    // B0/S0123 for a tolerance of 4
+    fn tolerance_rule(tolerance: usize) -> LifeRule {
+        let survive: Vec<usize> = (0..tolerance).collect();
+        LifeRule::new(&[0], &survive)
     }
 
-    fn step(&mut self, mode: Mode) -> bool {
-        let mut changes: Vec<(Point, Tile)> = Vec::new();
-        for p in self.active.iter() {
-            match (mode, self.get_tile(&p)) {
-                (_, Tile::Floor) => (),
-                (Mode::M1, Tile::Empty) => {
-                    // Become occupied if there are no direct neighbors occupied
-                    if self.count_neighbors_direct(&p) == 0 {
-                        changes.push((*p, Tile::Occupied));
-                    }
-                }
-                (Mode::M1, Tile::Occupied) => {
-                    // Become empty if four or more direct neighbors are also occupied
-                    if self.count_neighbors_direct(&p) >= 4 {
-                        changes.push((*p, Tile::Empty));
-                    }
-                }
-                (Mode::M2, Tile::Empty) => {
-                    // Become occupied if there are no visible neighbors occupied
-                    if self.count_neighbors_visible(&p) == 0 {
-                        changes.push((*p, Tile::Occupied));
-                    }
-                }
-                (Mode::M2, Tile::Occupied) => {
-                    // Become empty if five or more visible neighbors are also occupied
-                    if self.count_neighbors_visible(&p) >= 5 {
-                        changes.push((*p, Tile::Empty));
-                    }
+    // Each seat's neighbors are the seats it could see in each of the eight directions, nearest first,
+    // stopping at the edge of the waiting area, the reach or anything which always blocks sight. Which of
+    // them can actually be seen past the seats which block sight is up to SeatRule each step.
+    fn topology(&self, grid: &Grid<Tile>) -> GridTopology {
+        GridTopology::new(grid.width(), grid.height(), |p| {
+            if grid[p] == Tile::Floor {
+                return Vec::new();
+            }
+            Point::origin()
+                .adjacents()
+                .map(|dir| self.look(grid, p, dir))
+                .collect()
+        })
+    }
+
+    fn look(&self, grid: &Grid<Tile>, p: Point, dir: Point) -> Vec<Point> {
+        let seats_block = self.blocks(Tile::Empty) && self.blocks(Tile::Occupied);
+        let mut line = Vec::new();
+        let mut walk = p + dir;
+        let mut distance = 1;
+        while let Some(&tile) = grid.get(&walk) {
+            if distance > self.reach() || (tile == Tile::Floor && self.blocks(tile)) {
+                break;
+            }
+            if tile != Tile::Floor {
+                line.push(walk);
+                if seats_block == true {
+                    break;
                 }
             }
+            walk += dir;
+            distance += 1;
+        }
+        line
+    }
+}
+
+// Occupied seats are live and floor never changes
+struct SeatRule {
+    life: LifeRule,
+    blocks_sight: Vec<Tile>,
+    // The next state of each tile for every count up to eight neighbors, which is most of them, to save
+    // looking through the rule's lists for every seat every step
+    table: Vec<[Tile; 3]>,
+}
+
+impl SeatRule {
+    fn new(rules: &SeatingRules) -> Self {
+        let mut rule = Self {
+            life: rules.rule.clone(),
+            blocks_sight: rules.blocks_sight.clone(),
+            table: Vec::new(),
+        };
+        rule.table = (0..=8)
+            .map(|n| [Tile::Floor, Tile::Empty, Tile::Occupied].map(|tile| rule.lookup(tile, n)))
+            .collect();
+        rule
+    }
+
+    fn lookup(&self, state: Tile, live_neighbors: usize) -> Tile {
+        let occupied = match state {
+            Tile::Floor => return Tile::Floor,
+            Tile::Empty => self.life.next(false, live_neighbors),
+            Tile::Occupied => self.life.next(true, live_neighbors),
+        };
+        if occupied == true {
+            Tile::Occupied
+        } else {
+            Tile::Empty
         }
+    }
+}
+
+impl Rule for SeatRule {
+    type State = Tile;
 
-        for change in &changes {
-            self.set_tile(&change.0, change.1);
+    fn is_live(&self, state: Tile) -> bool {
+        state == Tile::Occupied
+    }
+
+    fn next(&self, state: Tile, live_neighbors: usize) -> Tile {
+        match self.table.get(live_neighbors) {
+            Some(next) => next[state as usize],
+            None => self.lookup(state, live_neighbors),
         }
-        changes.is_empty()
     }
 
-    fn simulate(&mut self, mode: Mode) {
-        while self.step(mode) == false {}
+    fn blocks_sight(&self, state: Tile) -> bool {
+        self.blocks_sight.contains(&state)
+    }
+}
+
+#[derive(Clone)]
+pub struct WaitingArea {
+    grid: Grid<Tile>,
+}
+
+impl WaitingArea {
+    fn from_string(input: &str) -> Result<Self, ParseError> {
+        let grid: Grid<Tile> = parse_input(
+            11,
+            input,
+            "a rectangle of seats ('.', 'L' or '#')",
+            trim_start(Grid::parser),
+        )?;
+        Ok(Self { grid })
+    }
+
+    fn automaton(&self, rules: &SeatingRules) -> GridAutomaton<SeatRule> {
+        GridAutomaton::new(rules.topology(&self.grid), SeatRule::new(rules))
+            .with_grid(self.grid.clone())
+    }
+
+    // Runs until the seating is stable, returning how many rounds that took. Some rules never settle
+    // and keep going round the same seatings instead, in which case that cycle is the error.
+    fn simulate(&mut self, rules: &SeatingRules) -> Result<usize, Cycle> {
+        let mut automaton = self.automaton(rules);
+        // Only one earlier seating is kept to compare against, moving it up each time the distance to it
+        // doubles, so spotting a cycle costs next to nothing for the usual rules which settle
+        let mut saved = self.grid.tiles().to_vec();
+        let (mut saved_at, mut distance) = (0, 1);
+        let mut rounds = 0;
+        while automaton.step() == true {
+            rounds += 1;
+            if automaton.grid().tiles() == saved.as_slice() {
+                let length = rounds - saved_at;
+                return Err(Cycle {
+                    start: self.cycle_start(rules, length),
+                    length,
+                });
+            }
+            if rounds - saved_at == distance {
+                saved.copy_from_slice(automaton.grid().tiles());
+                saved_at = rounds;
+                distance *= 2;
+            }
+        }
+        self.grid = automaton.into_grid();
+        Ok(rounds)
+    }
+
+    // The first generation which comes round again, found by running a second copy of the seating a whole
+    // cycle ahead of the first until the two match
+    fn cycle_start(&self, rules: &SeatingRules, length: usize) -> usize {
+        let mut behind = self.automaton(rules);
+        let mut ahead = self.automaton(rules);
+        ahead.simulate(length);
+        while behind.grid() != ahead.grid() {
+            behind.step();
+            ahead.step();
+        }
+        behind.generation()
+    }
+
+    // Finds the seating after any number of generations, even far more than can be simulated, by
+    // detecting when it starts repeating. Returns false if it hasn't repeated within max_steps.
+    fn simulate_generations(
+        &mut self,
+        rules: &SeatingRules,
+        generation: usize,
+        max_steps: usize,
+    ) -> bool {
+        let mut automaton = self.automaton(rules);
+        let reached = automaton.simulate_to(generation, max_steps);
+        self.grid = automaton.into_grid();
+        reached
+    }
+
+    // Writes the seating as it is now and after each generation, stopping once it's stable or after the
+    // given number of generations
+    fn record(
+        &mut self,
+        rules: &SeatingRules,
+        generations: usize,
+        writer: &mut FrameWriter,
+    ) -> io::Result<()> {
+        let mut automaton = self.automaton(rules);
+        writer.write(&Frame::from_grid(automaton.grid()))?;
+        for _ in 0..generations {
+            if automaton.step() == false {
+                break;
+            }
+            writer.write(&Frame::from_grid(automaton.grid()))?;
+        }
+        self.grid = automaton.into_grid();
+        Ok(())
     }
 
     fn total_occupied(&self) -> usize {
-        self.tiles
+        self.grid
+            .tiles()
             .iter()
             .filter(|seat| seat == &&Tile::Occupied)
             .count()
@@ -403,26 +536,45 @@ impl WaitingArea {
 
 impl std::fmt::Display for WaitingArea {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for y in 0..self.y_size as i32 {
-            for x in 0..self.x_size as i32 {
-                let p = Point { x, y };
-                write!(f, "{}", self.get_tile(&p).to_char())?;
-            }
-            writeln!(f)?;
-        }
-        Ok(())
+        write!(f, "{}", self.grid)
     }
 }
 
 #[aoc_generator(day11)]
-pub fn input_generator(input: &str) -> WaitingArea {
+pub fn input_generator(input: &str) -> Result<WaitingArea, ParseError> {
     WaitingArea::from_string(input)
 }
 
+// Runs a life-like rule on the seats and returns how many are occupied at the end. Without a number of
+// steps it runs until the seating settles, which some rules never do.
+pub fn life(input: &str, life_like: &LifeLike, steps: Option<usize>) -> Result<usize, String> {
+    let rules = SeatingRules::from_life_like(life_like).ok_or(format!(
+        "day 11 only supports the default and line of sight (L) neighborhoods, not {}",
+        life_like
+    ))?;
+    let mut waiting_area = input_generator(input).map_err(|e| e.to_string())?;
+    match steps {
+        Some(steps) => {
+            waiting_area.simulate_generations(&rules, steps, steps);
+        }
+        None => {
+            waiting_area.simulate(&rules).map_err(|cycle| {
+                format!(
+                    "the seating never settles, repeating every {} rounds",
+                    cycle.length
+                )
+            })?;
+        }
+    }
+    Ok(waiting_area.total_occupied())
+}
+
 #[aoc(day11, part1)]
 pub fn part1(input: &WaitingArea) -> usize {
     let mut waiting_area = input.clone();
-    waiting_area.simulate(Mode::M1);
+    waiting_area
+        .simulate(&SeatingRules::direct())
+        .expect("the seating never settles");
     let occupied = waiting_area.total_occupied();
     assert_eq!(occupied, 2183);
     occupied
@@ -431,15 +583,40 @@ pub fn part1(input: &WaitingArea) -> usize {
 #[aoc(day11, part2)]
 pub fn part2(input: &WaitingArea) -> usize {
     let mut waiting_area = input.clone();
-    waiting_area.simulate(Mode::M2);
+    waiting_area
+        .simulate(&SeatingRules::line_of_sight())
+        .expect("the seating never settles");
     let occupied = waiting_area.total_occupied();
     assert_eq!(occupied, 1990);
     occupied
 }
 
+pub struct Day11;
+
+impl Solution for Day11 {
+    const DAY: u32 = 11;
+
+    type Input = WaitingArea;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::common::FrameFormat;
 
     static EXAMPLE_INPUT: &str = "\
 L.LL.LL.LL
@@ -453,10 +630,19 @@ LLLLLLLLLL
 L.LLLLLL.L
 L.LLLLL.LL";
 
+    #[test]
+    fn test_input_generator() {
+        assert!(input_generator("\n\nL.L\n#L#\n").is_ok());
+
+        // Lines are counted from the start of the input, including any leading blank lines
+        let error = input_generator("\n\nL.L\nLxL\n").err().unwrap();
+        assert_eq!((error.day, error.line, error.column), (11, 4, 2));
+    }
+
     #[test]
     fn test_simulate() {
-        let mut waiting_area = input_generator(EXAMPLE_INPUT);
-        waiting_area.simulate(Mode::M1);
+        let mut waiting_area = input_generator(EXAMPLE_INPUT).unwrap();
+        assert_eq!(waiting_area.simulate(&SeatingRules::direct()), Ok(5));
         let occupied = waiting_area.total_occupied();
         assert_eq!(occupied, 37);
         let expected = input_generator(
@@ -471,11 +657,12 @@ L.#.L..#..
 #L#L##L#L#
 #.LLLLLL.L
 #.#L#L#.##",
-        );
+        )
+        .unwrap();
         assert_eq!(waiting_area.to_string(), expected.to_string());
 
-        let mut waiting_area = input_generator(EXAMPLE_INPUT);
-        waiting_area.simulate(Mode::M2);
+        let mut waiting_area = input_generator(EXAMPLE_INPUT).unwrap();
+        assert_eq!(waiting_area.simulate(&SeatingRules::line_of_sight()), Ok(6));
         let occupied = waiting_area.total_occupied();
         assert_eq!(occupied, 26);
 
@@ -491,7 +678,160 @@ L.L#.LL.L#
 LLL###LLL#
 #.LLLLL#.L
 #.L#LL#.L#",
-        );
+        )
+        .unwrap();
         assert_eq!(waiting_area.to_string(), expected.to_string());
     }
+
+    #[test]
+    fn test_simulate_generations() {
+        let input = input_generator(EXAMPLE_INPUT).unwrap();
+        for rules in &[
+            SeatingRules::direct(),
+            SeatingRules::line_of_sight(),
+            SeatingRules::line_of_sight().with_blocks_sight(&[Tile::Occupied]),
+        ] {
+            let mut stable = input.clone();
+            stable.simulate(rules).unwrap();
+
+            let mut waiting_area = input.clone();
+            assert_eq!(
+                waiting_area.simulate_generations(rules, 1_000_000_000_000, 100),
+                true
+            );
+            assert_eq!(waiting_area.to_string(), stable.to_string());
+
+            // Part way there it hasn't settled yet
+            let mut waiting_area = input.clone();
+            assert_eq!(waiting_area.simulate_generations(rules, 2, 100), true);
+            assert_ne!(waiting_area.to_string(), stable.to_string());
+        }
+    }
+
+    #[test]
+    fn test_seating_rules() {
+        let input = input_generator(EXAMPLE_INPUT).unwrap();
+        let occupied = |rules: SeatingRules| {
+            let mut waiting_area = input.clone();
+            waiting_area.simulate(&rules).unwrap();
+            waiting_area.total_occupied()
+        };
+
+        // Line of sight which only reaches the next tile is the same as looking at direct neighbors
+        let direct = occupied(SeatingRules::direct());
+        assert_eq!(direct, 37);
+        assert_eq!(
+            occupied(
+                SeatingRules::line_of_sight()
+                    .with_radius(1)
+                    .with_tolerance(4)
+            ),
+            direct
+        );
+
+        // Or when floor blocks sight too
+        assert_eq!(
+            occupied(
+                SeatingRules::line_of_sight()
+                    .with_tolerance(4)
+                    .with_blocks_sight(&[Tile::Floor, Tile::Empty, Tile::Occupied])
+            ),
+            direct
+        );
+
+        // Nobody minds company any more than they do in part 2, so the same people sit down
+        let visible = occupied(SeatingRules::line_of_sight());
+        assert_eq!(visible, 26);
+        assert_eq!(
+            occupied(SeatingRules::line_of_sight().with_radius(100)),
+            visible
+        );
+
+        // Only occupied seats block sight, so people look past empty seats for someone sitting down
+        assert_eq!(
+            occupied(SeatingRules::line_of_sight().with_blocks_sight(&[Tile::Occupied])),
+            15
+        );
+
+        // Seeing every seat in a line, people never settle and keep swapping between two seatings
+        let see_through = SeatingRules::line_of_sight().with_blocks_sight(&[]);
+        let mut even = input.clone();
+        assert_eq!(
+            even.simulate_generations(&see_through, 1_000_000, 100),
+            true
+        );
+        let mut odd = input.clone();
+        assert_eq!(odd.simulate_generations(&see_through, 1_000_001, 100), true);
+        assert_ne!(even.to_string(), odd.to_string());
+        odd.simulate_generations(&see_through, 1, 100);
+        assert_eq!(even.to_string(), odd.to_string());
+
+        // So simulating until they settle gives up as soon as a seating comes round again
+        let mut waiting_area = input.clone();
+        let cycle = waiting_area.simulate(&see_through).unwrap_err();
+        assert_eq!(cycle.length, 2);
+        assert_eq!(cycle.start, 0);
+    }
+
+    #[test]
+    fn test_cycle_start() {
+        // Nobody can stay sat down, so a lone seat is filled and emptied from the very start
+        let nobody_stays = SeatingRules::direct().with_tolerance(0);
+        let mut waiting_area = WaitingArea::from_string("L").unwrap();
+        let cycle = waiting_area.simulate(&nobody_stays).unwrap_err();
+        assert_eq!(
+            cycle,
+            Cycle {
+                start: 0,
+                length: 2
+            }
+        );
+
+        // Both people leave at once, and the seating they started in never comes round again
+        let mut waiting_area = WaitingArea::from_string("##L").unwrap();
+        let cycle = waiting_area.simulate(&nobody_stays).unwrap_err();
+        assert_eq!(
+            cycle,
+            Cycle {
+                start: 1,
+                length: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_life() {
+        let part1: LifeLike = "B0/S0123".parse().unwrap();
+        let part2: LifeLike = "B0/S01234L".parse().unwrap();
+        assert_eq!(life(EXAMPLE_INPUT, &part1, None), Ok(37));
+        assert_eq!(life(EXAMPLE_INPUT, &part2, None), Ok(26));
+
+        // Everyone sits down in the first round
+        assert_eq!(life(EXAMPLE_INPUT, &part1, Some(1)), Ok(71));
+
+        // Nobody ever leaves, so everyone stays sat down
+        let stay: LifeLike = "B0/S012345678".parse().unwrap();
+        assert_eq!(life(EXAMPLE_INPUT, &stay, None), Ok(71));
+
+        let hex: LifeLike = "B2/S12H".parse().unwrap();
+        assert!(life(EXAMPLE_INPUT, &hex, None).is_err());
+    }
+
+    #[test]
+    fn test_record() {
+        let mut waiting_area = input_generator(EXAMPLE_INPUT).unwrap();
+        let path = std::env::temp_dir().join(format!("aoc_2020_day_11_{}", std::process::id()));
+        let mut writer = FrameWriter::create(&path, FrameFormat::Ascii).unwrap();
+        waiting_area
+            .record(&SeatingRules::direct(), 100, &mut writer)
+            .unwrap();
+
+        // The example settles after five rounds
+        assert_eq!(writer.frames(), 6);
+        writer.finish().unwrap();
+        let animation = std::fs::read_to_string(&path).unwrap();
+        let last_frame = animation.split("-- frame 5 --\n").nth(1).unwrap();
+        assert_eq!(last_frame, waiting_area.to_string());
+        std::fs::remove_file(&path).unwrap();
+    }
 }
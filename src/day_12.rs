@@ -59,7 +59,8 @@
     Figure out where the navigation instructions actually lead. What is the Manhattan distance between that location and the ship's starting position?
 */
 
-use crate::common::{trim_start, unsigned, Cardinal, Mode, Point, Turn};
+use crate::common::{parse_input, trim_start, unsigned, Cardinal, Mode, ParseError, Point, Turn};
+use crate::Solution;
 use nom::{
     branch::alt, character::complete::char, combinator::value, multi::many1, sequence::pair,
     IResult,
@@ -206,8 +207,13 @@ fn handle_instructions(instructions: &[Instruction], mode: Mode) -> u32 {
 }
 
 #[aoc_generator(day12)]
-pub fn input_generator(input: &str) -> Vec<Instruction> {
-    many1(Instruction::parser)(input).unwrap().1
+pub fn input_generator(input: &str) -> Result<Vec<Instruction>, ParseError> {
+    parse_input(
+        12,
+        input,
+        "a navigation instruction (N, S, E, W, L, R or F) with a value",
+        many1(Instruction::parser),
+    )
 }
 
 #[aoc(day12, part1)]
@@ -224,6 +230,28 @@ pub fn part2(input: &[Instruction]) -> u32 {
     distance
 }
 
+pub struct Day12;
+
+impl Solution for Day12 {
+    const DAY: u32 = 12;
+
+    type Input = Vec<Instruction>;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -238,7 +266,7 @@ F11";
     #[test]
     fn test_ship_execute1() {
         let mut ship = Ship::new();
-        let mut instr_iter = input_generator(EXAMPLE_INPUT).into_iter();
+        let mut instr_iter = input_generator(EXAMPLE_INPUT).unwrap().into_iter();
 
         ship.execute1(&instr_iter.next().unwrap());
         assert_eq!(ship.location, (10, 0).into());
@@ -264,7 +292,7 @@ F11";
     #[test]
     fn test_ship_execute2() {
         let mut ship = Ship::new();
-        let mut instr_iter = input_generator(EXAMPLE_INPUT).into_iter();
+        let mut instr_iter = input_generator(EXAMPLE_INPUT).unwrap().into_iter();
 
         ship.execute2(&instr_iter.next().unwrap());
         assert_eq!(ship.location, (100, -10).into());
@@ -289,11 +317,11 @@ F11";
 
     #[test]
     fn test_handle_instructions() {
-        let instructions = input_generator(EXAMPLE_INPUT);
+        let instructions = input_generator(EXAMPLE_INPUT).unwrap();
         let distance = handle_instructions(&instructions, Mode::M1);
         assert_eq!(distance, 25);
 
-        let instructions = input_generator(EXAMPLE_INPUT);
+        let instructions = input_generator(EXAMPLE_INPUT).unwrap();
         let distance = handle_instructions(&instructions, Mode::M2);
         assert_eq!(distance, 286);
     }
@@ -105,7 +105,8 @@
     What is the earliest timestamp such that all of the listed bus IDs depart at offsets matching their positions in the list?
 */
 
-use crate::common::{modulo, trim_start, unsigned};
+use crate::common::{modulo, parse_input, trim_start, unsigned, ParseError};
+use crate::Solution;
 use nom::{
     character::complete::{alphanumeric1, char},
     multi::separated_list1,
@@ -192,8 +193,13 @@ impl Schedule {
 }
 
 #[aoc_generator(day13)]
-pub fn input_generator(input: &str) -> Schedule {
-    Schedule::parser(input).unwrap().1
+pub fn input_generator(input: &str) -> Result<Schedule, ParseError> {
+    parse_input(
+        13,
+        input,
+        "an arrival time followed by a comma-separated list of bus IDs",
+        Schedule::parser,
+    )
 }
 
 #[aoc(day13, part1)]
@@ -211,6 +217,28 @@ pub fn part2(input: &Schedule) -> u64 {
     syzygy
 }
 
+pub struct Day13;
+
+impl Solution for Day13 {
+    const DAY: u32 = 13;
+
+    type Input = Schedule;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -249,7 +277,7 @@ mod test {
 
     #[test]
     fn test_find_earliest_bus() {
-        let schedule = input_generator(EXAMPLE_INPUT1);
+        let schedule = input_generator(EXAMPLE_INPUT1).unwrap();
         let (bus_id, wait_time) = schedule.find_earliest_bus();
         assert_eq!(bus_id, 59);
         assert_eq!(wait_time, 5);
@@ -257,27 +285,27 @@ mod test {
 
     #[test]
     fn test_find_earliest_syzygy() {
-        let schedule = input_generator(EXAMPLE_INPUT1);
+        let schedule = input_generator(EXAMPLE_INPUT1).unwrap();
         let syzygy = schedule.find_earliest_syzygy();
         assert_eq!(syzygy, 1068781);
 
-        let schedule = input_generator(EXAMPLE_INPUT2);
+        let schedule = input_generator(EXAMPLE_INPUT2).unwrap();
         let syzygy = schedule.find_earliest_syzygy();
         assert_eq!(syzygy, 3417);
 
-        let schedule = input_generator(EXAMPLE_INPUT3);
+        let schedule = input_generator(EXAMPLE_INPUT3).unwrap();
         let syzygy = schedule.find_earliest_syzygy();
         assert_eq!(syzygy, 754018);
 
-        let schedule = input_generator(EXAMPLE_INPUT4);
+        let schedule = input_generator(EXAMPLE_INPUT4).unwrap();
         let syzygy = schedule.find_earliest_syzygy();
         assert_eq!(syzygy, 779210);
 
-        let schedule = input_generator(EXAMPLE_INPUT5);
+        let schedule = input_generator(EXAMPLE_INPUT5).unwrap();
         let syzygy = schedule.find_earliest_syzygy();
         assert_eq!(syzygy, 1261476);
 
-        let schedule = input_generator(EXAMPLE_INPUT6);
+        let schedule = input_generator(EXAMPLE_INPUT6).unwrap();
         let syzygy = schedule.find_earliest_syzygy();
         assert_eq!(syzygy, 1202161486);
     }
@@ -84,7 +84,8 @@
     Execute the initialization program using an emulator for a version 2 decoder chip. What is the sum of all values left in memory after it completes?
 */
 
-use crate::common::{trim_start, unsigned, Mode};
+use crate::common::{parse_input, trim_start, unsigned, Mode, ParseError};
+use crate::Solution;
 use nom::{
     branch::alt,
     bytes::complete::tag,
@@ -148,13 +149,21 @@ impl Mask {
         tmp
     }
 
-    fn apply_address(&self, addr: usize) -> MaskAddrIterator {
+    fn apply_address(&self, addr: usize) -> MaskAddrIterator<'_> {
         MaskAddrIterator {
-            mask: &self,
+            mask: self,
             addr,
             count: 0,
         }
     }
+
+    // The same addresses as apply_address, without listing them
+    fn address_pattern(&self, addr: usize) -> AddressPattern {
+        AddressPattern {
+            fixed: (addr as u64 | self.set) & !self.floating,
+            floating: self.floating,
+        }
+    }
 }
 
 impl std::ops::Shl<u8> for Mask {
@@ -226,6 +235,101 @@ impl<'a> Iterator for MaskAddrIterator<'a> {
     }
 }
 
+// Every address which matches fixed on all of the bits which aren't floating. Floating bits are always
+// clear in fixed, so that equal patterns compare equal.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct AddressPattern {
+    fixed: u64,
+    floating: u64,
+}
+
+impl AddressPattern {
+    fn single(addr: usize) -> Self {
+        Self {
+            fixed: addr as u64,
+            floating: 0,
+        }
+    }
+
+    fn len(&self) -> u64 {
+        1 << self.floating.count_ones()
+    }
+
+    fn intersection(&self, other: &Self) -> Option<Self> {
+        let fixed_in_both = !self.floating & !other.floating;
+        if (self.fixed ^ other.fixed) & fixed_in_both != 0 {
+            return None;
+        }
+        Some(Self {
+            fixed: self.fixed | other.fixed,
+            floating: self.floating & other.floating,
+        })
+    }
+
+    // Splits off the addresses which aren't in other, one floating bit at a time. Each piece pins one more
+    // bit to the opposite of other's, so the pieces never overlap each other or other.
+    fn subtract(&self, other: &Self) -> Vec<Self> {
+        if self.intersection(other).is_none() {
+            return vec![*self];
+        }
+
+        let mut pieces = Vec::new();
+        let mut remaining = *self;
+        let mut split = self.floating & !other.floating;
+        while split != 0 {
+            let bit = split & split.wrapping_neg();
+            split &= !bit;
+            remaining.floating &= !bit;
+            pieces.push(Self {
+                fixed: remaining.fixed | (!other.fixed & bit),
+                floating: remaining.floating,
+            });
+            remaining.fixed |= other.fixed & bit;
+        }
+        // What's left is entirely inside other
+        pieces
+    }
+}
+
+// Keeps each write as the pattern of addresses it covers, rather than listing them, so the cost depends on
+// how the writes overlap instead of how many floating bits they have. Patterns are always disjoint, with
+// anything overwritten cut out of the older ones.
+#[derive(Default)]
+struct SymbolicMemory {
+    writes: Vec<(AddressPattern, u64)>,
+}
+
+impl SymbolicMemory {
+    fn write(&mut self, addresses: AddressPattern, value: u64) {
+        let mut pieces = Vec::new();
+        self.writes.retain(|(pattern, old)| {
+            if pattern.intersection(&addresses).is_none() {
+                return true;
+            }
+            pieces.extend(
+                pattern
+                    .subtract(&addresses)
+                    .into_iter()
+                    .map(|piece| (piece, *old)),
+            );
+            false
+        });
+        self.writes.extend(pieces);
+        // Zeroes are what memory starts with, so only need to clear what was there before
+        if value != 0 {
+            self.writes.push((addresses, value));
+        }
+    }
+
+    // Wide enough for every one of the 2^36 addresses holding the largest value
+    fn sum(&self) -> u128 {
+        self.writes
+            .iter()
+            .map(|(pattern, value)| pattern.len() as u128 * *value as u128)
+            .sum()
+    }
+}
+
 pub enum Instruction {
     UpdateMask(Mask),
     WriteMemory(usize, u64),
@@ -250,31 +354,50 @@ impl Instruction {
     }
 }
 
+enum Memory {
+    // Every address written to
+    Enumerated(HashMap<usize, u64>),
+    Symbolic(SymbolicMemory),
+}
+
 struct Computer {
     mask: Mask,
-    memory: HashMap<usize, u64>,
+    memory: Memory,
 }
 
 impl Computer {
     fn new() -> Self {
         Self {
             mask: Mask::new(),
-            memory: HashMap::new(),
+            memory: Memory::Enumerated(HashMap::new()),
+        }
+    }
+
+    fn symbolic() -> Self {
+        Self {
+            mask: Mask::new(),
+            memory: Memory::Symbolic(SymbolicMemory::default()),
         }
     }
 
     fn execute(&mut self, instruction: &Instruction, mode: Mode) {
         match instruction {
             Instruction::UpdateMask(mask) => self.mask = *mask,
-            Instruction::WriteMemory(addr, value) => match mode {
-                Mode::M1 => {
-                    self.memory.insert(*addr, self.mask.apply_value(*value));
+            Instruction::WriteMemory(addr, value) => match (&mut self.memory, mode) {
+                (Memory::Enumerated(memory), Mode::M1) => {
+                    memory.insert(*addr, self.mask.apply_value(*value));
                 }
-                Mode::M2 => {
+                (Memory::Enumerated(memory), Mode::M2) => {
                     for a in self.mask.apply_address(*addr) {
-                        self.memory.insert(a, *value);
+                        memory.insert(a, *value);
                     }
                 }
+                (Memory::Symbolic(memory), Mode::M1) => {
+                    memory.write(AddressPattern::single(*addr), self.mask.apply_value(*value));
+                }
+                (Memory::Symbolic(memory), Mode::M2) => {
+                    memory.write(self.mask.address_pattern(*addr), *value);
+                }
             },
         }
     }
@@ -283,18 +406,26 @@ impl Computer {
         instructions.iter().for_each(|i| self.execute(i, mode));
     }
 
-    fn memory_sum(&self) -> u64 {
-        self.memory.values().sum()
+    fn memory_sum(&self) -> u128 {
+        match &self.memory {
+            Memory::Enumerated(memory) => memory.values().map(|&value| value as u128).sum(),
+            Memory::Symbolic(memory) => memory.sum(),
+        }
     }
 }
 
 #[aoc_generator(day14)]
-pub fn input_generator(input: &str) -> Vec<Instruction> {
-    many1(Instruction::parser)(input).unwrap().1
+pub fn input_generator(input: &str) -> Result<Vec<Instruction>, ParseError> {
+    parse_input(
+        14,
+        input,
+        "a mask like 'mask = 0X1' or a write like 'mem[8] = 11'",
+        many1(Instruction::parser),
+    )
 }
 
 #[aoc(day14, part1)]
-pub fn part1(input: &[Instruction]) -> u64 {
+pub fn part1(input: &[Instruction]) -> u128 {
     let mut computer = Computer::new();
     computer.execute_all(input, Mode::M1);
     let memory_sum = computer.memory_sum();
@@ -303,14 +434,36 @@ pub fn part1(input: &[Instruction]) -> u64 {
 }
 
 #[aoc(day14, part2)]
-pub fn part2(input: &[Instruction]) -> u64 {
-    let mut computer = Computer::new();
+pub fn part2(input: &[Instruction]) -> u128 {
+    let mut computer = Computer::symbolic();
     computer.execute_all(input, Mode::M2);
     let memory_sum = computer.memory_sum();
     assert_eq!(memory_sum, 3564822193820);
     memory_sum
 }
 
+pub struct Day14;
+
+impl Solution for Day14 {
+    const DAY: u32 = 14;
+
+    type Input = Vec<Instruction>;
+    type Answer1 = u128;
+    type Answer2 = u128;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -361,13 +514,92 @@ mem[26] = 1";
     #[test]
     fn test_memory_sum() {
         let mut computer = Computer::new();
-        let instructions = input_generator(EXAMPLE_INPUT1);
+        let instructions = input_generator(EXAMPLE_INPUT1).unwrap();
         computer.execute_all(&instructions, Mode::M1);
         assert_eq!(computer.memory_sum(), 165);
 
         let mut computer = Computer::new();
-        let instructions = input_generator(EXAMPLE_INPUT2);
+        let instructions = input_generator(EXAMPLE_INPUT2).unwrap();
         computer.execute_all(&instructions, Mode::M2);
         assert_eq!(computer.memory_sum(), 208);
     }
+
+    #[test]
+    fn test_address_pattern() {
+        let mask = Mask::from_string("000000000000000000000000000000X1001X");
+        let pattern = mask.address_pattern(42);
+        assert_eq!(pattern.len(), 4);
+
+        let other = Mask::from_string("00000000000000000000000000000000X0XX").address_pattern(26);
+        let overlap = pattern.intersection(&other).unwrap();
+        assert_eq!(
+            overlap,
+            AddressPattern {
+                fixed: 26,
+                floating: 1
+            }
+        );
+        assert_eq!(overlap.len(), 2);
+        assert_eq!(pattern.intersection(&AddressPattern::single(25)), None);
+
+        let pieces = pattern.subtract(&other);
+        assert_eq!(pieces.iter().map(AddressPattern::len).sum::<u64>(), 2);
+        let mut addresses: Vec<u64> = pieces
+            .iter()
+            .flat_map(|piece| [piece.fixed, piece.fixed | piece.floating])
+            .collect();
+        addresses.sort_unstable();
+        addresses.dedup();
+        assert_eq!(addresses, [58, 59]);
+        assert_eq!(other.subtract(&other), []);
+    }
+
+    #[test]
+    fn test_symbolic_memory_sum() {
+        let overlapping = "\
+mask = 000000000000000000000000000000XX0X1X
+mem[5] = 7
+mask = 0000000000000000000000000000001XXX0X
+mem[17] = 2
+mask = 000000000000000000000000000000X0X0XX
+mem[40] = 11
+mask = 0000000000000000000000000000000000X1
+mem[48] = 5";
+        for (input, mode) in [
+            (EXAMPLE_INPUT1, Mode::M1),
+            (EXAMPLE_INPUT2, Mode::M2),
+            (overlapping, Mode::M2),
+        ] {
+            let instructions = input_generator(input).unwrap();
+            let mut enumerated = Computer::new();
+            enumerated.execute_all(&instructions, mode);
+            let mut symbolic = Computer::symbolic();
+            symbolic.execute_all(&instructions, mode);
+            assert_eq!(symbolic.memory_sum(), enumerated.memory_sum());
+        }
+
+        // Far too many addresses to list, 2^34 of them with 2^32 written twice
+        let instructions = input_generator(
+            "\
+mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX00
+mem[0] = 1
+mask = 11XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX00
+mem[0] = 3",
+        )
+        .unwrap();
+        let mut computer = Computer::symbolic();
+        computer.execute_all(&instructions, Mode::M2);
+        assert_eq!(computer.memory_sum(), (1 << 34) - (1 << 32) + 3 * (1 << 32));
+
+        // Every address holding the largest value adds up to far more than fits in a u64
+        let instructions = input_generator(
+            "\
+mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX
+mem[0] = 18446744073709551615",
+        )
+        .unwrap();
+        let mut computer = Computer::symbolic();
+        computer.execute_all(&instructions, Mode::M2);
+        assert_eq!(computer.memory_sum(), (1 << 36) * u64::MAX as u128);
+    }
 }
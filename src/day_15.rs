@@ -49,6 +49,10 @@
     Given your starting numbers, what will be the 30000000th number spoken?
 */
 
+use crate::common::{parse_input, trim, unsigned, ParseError};
+use crate::Solution;
+use nom::{character::complete::char, multi::separated_list1};
+
 // Use a sentinel value to indicate that the value has never been spoken before. This is
 // this is significantly faster than using Option<32>, presumably because the compiler doesn't
 // know that we only need values up to 30 million and so uses more than 32 bits for the option,
@@ -101,11 +105,13 @@ impl Iterator for MemoryGame {
 }
 
 #[aoc_generator(day15)]
-pub fn input_generator(input: &str) -> Vec<usize> {
-    input
-        .split(',')
-        .map(|s| s.trim().parse::<usize>().unwrap())
-        .collect()
+pub fn input_generator(input: &str) -> Result<Vec<usize>, ParseError> {
+    parse_input(
+        15,
+        input,
+        "a comma-separated list of numbers",
+        separated_list1(char(','), trim(unsigned)),
+    )
 }
 
 #[aoc(day15, part1)]
@@ -124,6 +130,28 @@ pub fn part2(input: &[usize]) -> usize {
     spoken
 }
 
+pub struct Day15;
+
+impl Solution for Day15 {
+    const DAY: u32 = 15;
+
+    type Input = Vec<usize>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -138,38 +166,38 @@ mod test {
 
     #[test]
     fn test_next() {
-        let start = input_generator(INPUT_EXAMPLE1);
+        let start = input_generator(INPUT_EXAMPLE1).unwrap();
         let game = MemoryGame::from_slice(&start, 10);
         assert_eq!(
             game.take(10).collect::<Vec<usize>>(),
             [0, 3, 6, 0, 3, 3, 1, 0, 4, 0]
         );
 
-        let start = input_generator(INPUT_EXAMPLE1);
+        let start = input_generator(INPUT_EXAMPLE1).unwrap();
         let game = MemoryGame::from_slice(&start, 2020);
         assert_eq!(game.last(), Some(436));
 
-        let start = input_generator(INPUT_EXAMPLE2);
+        let start = input_generator(INPUT_EXAMPLE2).unwrap();
         let game = MemoryGame::from_slice(&start, 2020);
         assert_eq!(game.last(), Some(1));
 
-        let start = input_generator(INPUT_EXAMPLE3);
+        let start = input_generator(INPUT_EXAMPLE3).unwrap();
         let game = MemoryGame::from_slice(&start, 2020);
         assert_eq!(game.last(), Some(10));
 
-        let start = input_generator(INPUT_EXAMPLE4);
+        let start = input_generator(INPUT_EXAMPLE4).unwrap();
         let game = MemoryGame::from_slice(&start, 2020);
         assert_eq!(game.last(), Some(27));
 
-        let start = input_generator(INPUT_EXAMPLE5);
+        let start = input_generator(INPUT_EXAMPLE5).unwrap();
         let game = MemoryGame::from_slice(&start, 2020);
         assert_eq!(game.last(), Some(78));
 
-        let start = input_generator(INPUT_EXAMPLE6);
+        let start = input_generator(INPUT_EXAMPLE6).unwrap();
         let game = MemoryGame::from_slice(&start, 2020);
         assert_eq!(game.last(), Some(438));
 
-        let start = input_generator(INPUT_EXAMPLE7);
+        let start = input_generator(INPUT_EXAMPLE7).unwrap();
         let game = MemoryGame::from_slice(&start, 2020);
         assert_eq!(game.last(), Some(1836));
     }
@@ -177,31 +205,31 @@ mod test {
     #[test]
     #[ignore]
     fn test_next_long() {
-        let start = input_generator(INPUT_EXAMPLE1);
+        let start = input_generator(INPUT_EXAMPLE1).unwrap();
         let game = MemoryGame::from_slice(&start, 30_000_000);
         assert_eq!(game.last(), Some(175594));
 
-        let start = input_generator(INPUT_EXAMPLE2);
+        let start = input_generator(INPUT_EXAMPLE2).unwrap();
         let game = MemoryGame::from_slice(&start, 30_000_000);
         assert_eq!(game.last(), Some(2578));
 
-        let start = input_generator(INPUT_EXAMPLE3);
+        let start = input_generator(INPUT_EXAMPLE3).unwrap();
         let game = MemoryGame::from_slice(&start, 30_000_000);
         assert_eq!(game.last(), Some(3544142));
 
-        let start = input_generator(INPUT_EXAMPLE4);
+        let start = input_generator(INPUT_EXAMPLE4).unwrap();
         let game = MemoryGame::from_slice(&start, 30_000_000);
         assert_eq!(game.last(), Some(261214));
 
-        let start = input_generator(INPUT_EXAMPLE5);
+        let start = input_generator(INPUT_EXAMPLE5).unwrap();
         let game = MemoryGame::from_slice(&start, 30_000_000);
         assert_eq!(game.last(), Some(6895259));
 
-        let start = input_generator(INPUT_EXAMPLE6);
+        let start = input_generator(INPUT_EXAMPLE6).unwrap();
         let game = MemoryGame::from_slice(&start, 30_000_000);
         assert_eq!(game.last(), Some(18));
 
-        let start = input_generator(INPUT_EXAMPLE7);
+        let start = input_generator(INPUT_EXAMPLE7).unwrap();
         let game = MemoryGame::from_slice(&start, 30_000_000);
         assert_eq!(game.last(), Some(362));
     }
@@ -61,7 +61,8 @@
     Once you work out which field is which, look for the six fields on your ticket that start with the word departure. What do you get if you multiply those six values together?
 */
 
-use crate::common::{to_owned, trim, trim_start, unsigned};
+use crate::common::{parse_input, to_owned, trim, trim_start, unsigned, ParseError};
+use crate::Solution;
 use nom::{
     bytes::complete::{tag, take_while1},
     character::{
@@ -208,8 +209,13 @@ impl Notes {
 }
 
 #[aoc_generator(day16)]
-pub fn input_generator(input: &str) -> Notes {
-    Notes::parser(input).unwrap().1
+pub fn input_generator(input: &str) -> Result<Notes, ParseError> {
+    parse_input(
+        16,
+        input,
+        "ticket rules followed by 'your ticket:' and 'nearby tickets:'",
+        Notes::parser,
+    )
 }
 
 #[aoc(day16, part1)]
@@ -227,6 +233,28 @@ pub fn part2(input: &Notes) -> u64 {
     departure_product
 }
 
+pub struct Day16;
+
+impl Solution for Day16 {
+    const DAY: u32 = 16;
+
+    type Input = Notes;
+    type Answer1 = u32;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -247,13 +275,13 @@ nearby tickets:
 
     #[test]
     fn test_ticket_scanning_error_rate() {
-        let notes = input_generator(EXAMPLE_INPUT);
+        let notes = input_generator(EXAMPLE_INPUT).unwrap();
         assert_eq!(notes.ticket_scanning_error_rate(), 71);
     }
 
     #[test]
     fn test_find_field_ordering() {
-        let notes = input_generator(EXAMPLE_INPUT);
+        let notes = input_generator(EXAMPLE_INPUT).unwrap();
         assert_eq!(
             notes.find_field_ordering(),
             ["row".to_owned(), "class".to_owned(), "seat".to_owned()]
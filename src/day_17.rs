@@ -391,231 +391,98 @@
     Starting with your given initial configuration, simulate six cycles in a 4-dimensional space. How many cubes are left in the active state after the sixth cycle?
 */
 
-use crate::common::TileSet;
-use std::collections::HashMap;
-
-type Point2D = (i32, i32);
-type Point3D = (i32, i32, i32);
-type Point4D = (i32, i32, i32, i32);
-type DimRange = (i32, i32);
-
-const NEIGHBOR_OFFSETS_3D: [Point3D; 26] = [
-    (-1, -1, -1),
-    (-1, -1, 0),
-    (-1, -1, 1),
-    (-1, 0, -1),
-    (-1, 0, 0),
-    (-1, 0, 1),
-    (-1, 1, -1),
-    (-1, 1, 0),
-    (-1, 1, 1),
-    (0, -1, -1),
-    (0, -1, 0),
-    (0, -1, 1),
-    (0, 0, -1),
-    (0, 0, 1),
-    (0, 1, -1),
-    (0, 1, 0),
-    (0, 1, 1),
-    (1, -1, -1),
-    (1, -1, 0),
-    (1, -1, 1),
-    (1, 0, -1),
-    (1, 0, 0),
-    (1, 0, 1),
-    (1, 1, -1),
-    (1, 1, 0),
-    (1, 1, 1),
-];
-
-const NEIGHBOR_OFFSETS_4D: [Point4D; 80] = [
-    (-1, -1, -1, -1),
-    (-1, -1, -1, 0),
-    (-1, -1, -1, 1),
-    (-1, -1, 0, -1),
-    (-1, -1, 0, 0),
-    (-1, -1, 0, 1),
-    (-1, -1, 1, -1),
-    (-1, -1, 1, 0),
-    (-1, -1, 1, 1),
-    (-1, 0, -1, -1),
-    (-1, 0, -1, 0),
-    (-1, 0, -1, 1),
-    (-1, 0, 0, -1),
-    (-1, 0, 0, 0),
-    (-1, 0, 0, 1),
-    (-1, 0, 1, -1),
-    (-1, 0, 1, 0),
-    (-1, 0, 1, 1),
-    (-1, 1, -1, -1),
-    (-1, 1, -1, 0),
-    (-1, 1, -1, 1),
-    (-1, 1, 0, -1),
-    (-1, 1, 0, 0),
-    (-1, 1, 0, 1),
-    (-1, 1, 1, -1),
-    (-1, 1, 1, 0),
-    (-1, 1, 1, 1),
-    (0, -1, -1, -1),
-    (0, -1, -1, 0),
-    (0, -1, -1, 1),
-    (0, -1, 0, -1),
-    (0, -1, 0, 0),
-    (0, -1, 0, 1),
-    (0, -1, 1, -1),
-    (0, -1, 1, 0),
-    (0, -1, 1, 1),
-    (0, 0, -1, -1),
-    (0, 0, -1, 0),
-    (0, 0, -1, 1),
-    (0, 0, 0, -1),
-    (0, 0, 0, 1),
-    (0, 0, 1, -1),
-    (0, 0, 1, 0),
-    (0, 0, 1, 1),
-    (0, 1, -1, -1),
-    (0, 1, -1, 0),
-    (0, 1, -1, 1),
-    (0, 1, 0, -1),
-    (0, 1, 0, 0),
-    (0, 1, 0, 1),
-    (0, 1, 1, -1),
-    (0, 1, 1, 0),
-    (0, 1, 1, 1),
-    (1, -1, -1, -1),
-    (1, -1, -1, 0),
-    (1, -1, -1, 1),
-    (1, -1, 0, -1),
-    (1, -1, 0, 0),
-    (1, -1, 0, 1),
-    (1, -1, 1, -1),
-    (1, -1, 1, 0),
-    (1, -1, 1, 1),
-    (1, 0, -1, -1),
-    (1, 0, -1, 0),
-    (1, 0, -1, 1),
-    (1, 0, 0, -1),
-    (1, 0, 0, 0),
-    (1, 0, 0, 1),
-    (1, 0, 1, -1),
-    (1, 0, 1, 0),
-    (1, 0, 1, 1),
-    (1, 1, -1, -1),
-    (1, 1, -1, 0),
-    (1, 1, -1, 1),
-    (1, 1, 0, -1),
-    (1, 1, 0, 0),
-    (1, 1, 0, 1),
-    (1, 1, 1, -1),
-    (1, 1, 1, 0),
-    (1, 1, 1, 1),
-];
-
-struct State {
-    is_active: bool,
-    active_neighbors: u8,
+use crate::common::{
+    check_chars, record_cells, Backend, FrameWriter, LifeLike, LifeRule, ParseError, PointN, RectN,
+    Rule, Simulation, TileSet,
+};
+use crate::Solution;
+use std::collections::{HashMap, HashSet};
+use std::io;
+
+// Names for the dimensions beyond x and y when printing, any further ones are numbered
+const DIM_NAMES: [&str; 2] = ["z", "w"];
+
+// Active cubes stay active with 2 or 3 active neighbors, and inactive cubes become active with exactly 3
+const CUBE_RULE: &str = "B3/S23";
+
+fn cube_rule() -> LifeLike {
+    CUBE_RULE.parse().unwrap()
 }
 
-impl State {
-    fn new() -> Self {
-        Self {
-            is_active: false,
-            active_neighbors: 0,
-        }
-    }
-}
-struct PocketDimension3D {
-    cubes: HashMap<Point3D, State>,
+struct PocketDimension<const N: usize> {
+    cubes: Box<dyn Simulation<PointN<N>>>,
 }
 
-impl PocketDimension3D {
+type PocketDimension3D = PocketDimension<3>;
+type PocketDimension4D = PocketDimension<4>;
+
+impl<const N: usize> PocketDimension<N> {
     fn from_2d(tileset: &TileSet) -> Self {
-        let mut cubes = HashMap::new();
+        Self::from_2d_with(tileset, Backend::Sparse)
+    }
+
+    fn from_2d_with(tileset: &TileSet, backend: Backend) -> Self {
+        let mut cubes = cube_rule()
+            .cube_simulation(backend)
+            .expect("Backend doesn't support cubes");
         for p in tileset.iter() {
-            Self::set_active(&mut cubes, &(p.x, p.y, 0));
+            cubes.set_live(PointN::from_2d(*p), true);
         }
         Self { cubes }
     }
 
-    fn set_active(cubes: &mut HashMap<Point3D, State>, point: &Point3D) {
-        for neighbor in Self::neighbors(point) {
-            let entry = cubes.entry(neighbor).or_insert_with(State::new);
-            entry.active_neighbors += 1;
-        }
-        let entry = cubes.entry(*point).or_insert_with(State::new);
-        entry.is_active = true;
+    fn is_active(&self, point: &PointN<N>) -> bool {
+        self.cubes.is_live(point)
     }
 
-    fn neighbors(point: &Point3D) -> impl Iterator<Item = Point3D> + '_ {
-        NEIGHBOR_OFFSETS_3D
-            .iter()
-            .map(move |offset| (point.0 + offset.0, point.1 + offset.1, point.2 + offset.2))
+    fn active_cubes(&self) -> Vec<PointN<N>> {
+        self.cubes.live_cells()
     }
 
-    fn get_range(&self) -> (DimRange, DimRange, DimRange) {
-        let mut cubes_iter = self
-            .cubes
-            .iter()
-            .filter(|(_point, state)| state.is_active == true)
-            .map(|(point, _state)| point);
-        let first = cubes_iter.next().expect("No cubes in list");
-        cubes_iter.fold(
-            ((first.0, first.0), (first.1, first.1), (first.2, first.2)),
-            |(acc_x, acc_y, acc_z), p| {
-                (
-                    (acc_x.0.min(p.0), acc_x.1.max(p.0)),
-                    (acc_y.0.min(p.1), acc_y.1.max(p.1)),
-                    (acc_z.0.min(p.2), acc_z.1.max(p.2)),
-                )
-            },
-        )
+    fn get_range(&self) -> RectN<N> {
+        PointN::get_range(&self.active_cubes()).expect("No cubes in list")
     }
 
     fn step(&mut self) {
-        let mut new_cubes = HashMap::new();
-
-        for (cube, state) in self.cubes.iter() {
-            if state.is_active == true {
-                // Active. 2 or 3 to stay alive, otherwise die.
-                if state.active_neighbors == 2 || state.active_neighbors == 3 {
-                    Self::set_active(&mut new_cubes, &cube);
-                }
-            } else {
-                // Inactive. 3 to become alive, otherwise stay dead.
-                if state.active_neighbors == 3 {
-                    Self::set_active(&mut new_cubes, &cube);
-                }
-            }
-        }
-
-        self.cubes = new_cubes;
+        self.cubes.advance(1);
     }
 
     fn simulate(&mut self, steps: usize) {
-        for _ in 0..steps {
-            self.step();
-        }
+        self.cubes.advance(steps);
     }
 
     fn count_active_cubes(&self) -> usize {
-        self.cubes
-            .iter()
-            .filter(|(_point, state)| state.is_active == true)
-            .count()
+        self.cubes.count_live()
+    }
+
+    // Writes the cubes as they are now and after each step, as a grid of x/y slices
+    fn record(&mut self, steps: usize, writer: &mut FrameWriter) -> io::Result<()> {
+        record_cells(self.cubes.as_mut(), steps, writer)
     }
 }
 
-impl std::fmt::Display for PocketDimension3D {
+impl<const N: usize> std::fmt::Display for PocketDimension<N> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let (x_range, y_range, z_range) = self.get_range();
-        for z in z_range.0..=z_range.1 {
-            writeln!(f, "z={}", z)?;
-            for y in y_range.0..=y_range.1 {
-                for x in x_range.0..=x_range.1 {
-                    if let Some(State {
-                        is_active: true, ..
-                    }) = self.cubes.get(&(x, y, z))
-                    {
+        // Print one x/y slice for each combination of the remaining coordinates, with z changing fastest
+        let range = self.get_range();
+        let mut point = PointN::origin();
+        for dim in 2..N {
+            point[dim] = range.min[dim];
+        }
+
+        loop {
+            let names: Vec<String> = (2..N)
+                .map(|dim| match DIM_NAMES.get(dim - 2) {
+                    Some(name) => format!("{}={}", name, point[dim]),
+                    None => format!("d{}={}", dim, point[dim]),
+                })
+                .collect();
+            writeln!(f, "{}", names.join(", "))?;
+            for y in range.range(1) {
+                for x in range.range(0) {
+                    point[0] = x;
+                    point[1] = y;
+                    if self.is_active(&point) == true {
                         write!(f, "#")?;
                     } else {
                         write!(f, ".")?;
@@ -624,88 +491,89 @@ impl std::fmt::Display for PocketDimension3D {
                 writeln!(f)?;
             }
             writeln!(f)?;
+
+            // Advance to the next slice, or finish once every dimension has wrapped around
+            let next_dim = (2..N).find(|&dim| point[dim] < range.max[dim]);
+            match next_dim {
+                Some(dim) => {
+                    point[dim] += 1;
+                    for lower in 2..dim {
+                        point[lower] = range.min[lower];
+                    }
+                }
+                None => break,
+            }
         }
         Ok(())
     }
 }
 
-struct PocketDimension4D {
-    hypercubes: HashMap<Point4D, State>,
+// The seed is a single slice, so the whole simulation stays symmetric under flipping the sign of any
+// dimension beyond x and y, and under swapping any two of them. Only the canonical cubes are stored, i.e.
+// those with 0 <= z <= w <= ..., and each stands in for every cube it can be mapped to (its orbit).
+struct SymmetricPocketDimension<const N: usize> {
+    cubes: HashSet<PointN<N>>,
+    neighbor_offsets: Vec<PointN<N>>,
+    rule: LifeRule,
 }
 
-impl PocketDimension4D {
+impl<const N: usize> SymmetricPocketDimension<N> {
     fn from_2d(tileset: &TileSet) -> Self {
-        let mut hypercubes = HashMap::new();
-        for p in tileset.iter() {
-            Self::set_active(&mut hypercubes, &(p.x, p.y, 0, 0));
+        Self {
+            cubes: tileset.iter().map(|p| PointN::from_2d(*p)).collect(),
+            neighbor_offsets: PointN::neighbor_offsets().collect(),
+            rule: cube_rule().rule,
         }
-
-        Self { hypercubes }
     }
 
-    fn set_active(hypercubes: &mut HashMap<Point4D, State>, point: &Point4D) {
-        for neighbor in Self::neighbors(point) {
-            let entry = hypercubes.entry(neighbor).or_insert_with(State::new);
-            entry.active_neighbors += 1;
+    fn canonical(point: &PointN<N>) -> PointN<N> {
+        let mut canonical = *point;
+        for c in canonical.coords[2..].iter_mut() {
+            *c = c.abs();
         }
-        let entry = hypercubes.entry(*point).or_insert_with(State::new);
-        entry.is_active = true;
-    }
-
-    fn neighbors(point: &Point4D) -> impl Iterator<Item = Point4D> + '_ {
-        NEIGHBOR_OFFSETS_4D.iter().map(move |offset| {
-            (
-                point.0 + offset.0,
-                point.1 + offset.1,
-                point.2 + offset.2,
-                point.3 + offset.3,
-            )
-        })
+        canonical.coords[2..].sort_unstable();
+        canonical
     }
 
-    fn get_range(&self) -> (DimRange, DimRange, DimRange, DimRange) {
-        let mut hypercubes_iter = self
-            .hypercubes
-            .iter()
-            .filter(|(_point, state)| state.is_active == true)
-            .map(|(point, _state)| point);
-        let first = hypercubes_iter.next().expect("No cubes in list");
-        hypercubes_iter.fold(
-            (
-                (first.0, first.0),
-                (first.1, first.1),
-                (first.2, first.2),
-                (first.3, first.3),
-            ),
-            |(acc_x, acc_y, acc_z, acc_w), p| {
-                (
-                    (acc_x.0.min(p.0), acc_x.1.max(p.0)),
-                    (acc_y.0.min(p.1), acc_y.1.max(p.1)),
-                    (acc_z.0.min(p.2), acc_z.1.max(p.2)),
-                    (acc_w.0.min(p.3), acc_w.1.max(p.3)),
-                )
-            },
-        )
+    // Number of cubes that a canonical cube is equivalent to: 2 for each non-zero coordinate (its sign can
+    // be flipped) times the number of distinct orderings of the coordinates.
+    fn orbit_size(point: &PointN<N>) -> u64 {
+        let factorial = |n: usize| (1..=n as u64).product::<u64>();
+        let higher = &point.coords[2..];
+        let signs = 1 << higher.iter().filter(|&&c| c != 0).count();
+        let mut orderings = factorial(higher.len());
+        let mut i = 0;
+        while i < higher.len() {
+            let run = higher[i..].iter().take_while(|&&c| c == higher[i]).count();
+            orderings /= factorial(run);
+            i += run;
+        }
+        signs * orderings
     }
 
     fn step(&mut self) {
-        let mut new_hypercubes = HashMap::new();
-
-        for (hypercube, state) in self.hypercubes.iter() {
-            if state.is_active == true {
-                // Active. 2 or 3 to stay alive, otherwise die.
-                if state.active_neighbors == 2 || state.active_neighbors == 3 {
-                    Self::set_active(&mut new_hypercubes, &hypercube);
-                }
-            } else {
-                // Inactive. 3 to become alive, otherwise stay dead.
-                if state.active_neighbors == 3 {
-                    Self::set_active(&mut new_hypercubes, &hypercube);
-                }
+        // Each active cube A adds its orbit size to the canonical form C of every neighbor. For a canonical
+        // cube C, the real number of active neighbors is that sum divided by C's orbit size, since each
+        // cube in A's orbit is counted once from each member of C's orbit.
+        let mut weights: HashMap<PointN<N>, u64> = HashMap::new();
+        for cube in self.cubes.iter() {
+            let orbit = Self::orbit_size(cube);
+            for offset in self.neighbor_offsets.iter() {
+                *weights
+                    .entry(Self::canonical(&(cube + offset)))
+                    .or_insert(0) += orbit;
             }
         }
 
-        self.hypercubes = new_hypercubes;
+        self.cubes = weights
+            .into_iter()
+            .filter(|(cube, weight)| {
+                let active_neighbors = weight / Self::orbit_size(cube);
+                self.rule
+                    .next(self.cubes.contains(cube), active_neighbors as usize)
+            })
+            .map(|(cube, _weight)| cube)
+            .collect();
     }
 
     fn simulate(&mut self, steps: usize) {
@@ -715,47 +583,49 @@ impl PocketDimension4D {
     }
 
     fn count_active_cubes(&self) -> usize {
-        self.hypercubes
+        self.cubes
             .iter()
-            .filter(|(_point, state)| state.is_active == true)
-            .count()
+            .map(|cube| Self::orbit_size(cube) as usize)
+            .sum()
     }
-}
 
-impl std::fmt::Display for PocketDimension4D {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let (x_range, y_range, z_range, w_range) = self.get_range();
-        for w in w_range.0..=w_range.1 {
-            for z in z_range.0..=z_range.1 {
-                writeln!(f, "z={}, w={}", z, w)?;
-                for y in y_range.0..=y_range.1 {
-                    for x in x_range.0..=x_range.1 {
-                        if let Some(State {
-                            is_active: true, ..
-                        }) = self.hypercubes.get(&(x, y, z, w))
-                        {
-                            write!(f, "#")?;
-                        } else {
-                            write!(f, ".")?;
-                        }
-                    }
-                    writeln!(f)?;
+    // Every active cube in the full pocket dimension, found by applying each sign flip and ordering
+    fn unfold(&self) -> HashSet<PointN<N>> {
+        let mut unfolded = HashSet::new();
+        let mut pending: Vec<PointN<N>> = self.cubes.iter().copied().collect();
+        while let Some(cube) = pending.pop() {
+            if unfolded.insert(cube) == false {
+                continue;
+            }
+            for i in 2..N {
+                let mut flipped = cube;
+                flipped[i] = -flipped[i];
+                pending.push(flipped);
+                for j in (i + 1)..N {
+                    let mut swapped = cube;
+                    swapped.coords.swap(i, j);
+                    pending.push(swapped);
                 }
-                writeln!(f)?;
             }
         }
-        Ok(())
+        unfolded
     }
 }
 
 #[aoc_generator(day17)]
-pub fn input_generator(input: &str) -> TileSet {
-    TileSet::from_string(input, '#')
+pub fn input_generator(input: &str) -> Result<TileSet, ParseError> {
+    check_chars(
+        17,
+        input,
+        ".#",
+        "an inactive cube '.' or an active cube '#'",
+    )?;
+    Ok(TileSet::from_string(input, '#'))
 }
 
 #[aoc(day17, part1)]
 pub fn part1(input: &TileSet) -> usize {
-    let mut pocket_dimension = PocketDimension3D::from_2d(input);
+    let mut pocket_dimension = SymmetricPocketDimension::<3>::from_2d(input);
     pocket_dimension.simulate(6);
     let active_cubes = pocket_dimension.count_active_cubes();
     assert_eq!(active_cubes, 401);
@@ -764,16 +634,39 @@ pub fn part1(input: &TileSet) -> usize {
 
 #[aoc(day17, part2)]
 pub fn part2(input: &TileSet) -> usize {
-    let mut pocket_dimension = PocketDimension4D::from_2d(input);
+    let mut pocket_dimension = SymmetricPocketDimension::<4>::from_2d(input);
     pocket_dimension.simulate(6);
     let active_cubes = pocket_dimension.count_active_cubes();
     assert_eq!(active_cubes, 2224);
     active_cubes
 }
 
+pub struct Day17;
+
+impl Solution for Day17 {
+    const DAY: u32 = 17;
+
+    type Input = TileSet;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::common::FrameFormat;
 
     static EXAMPLE_INPUT: &str = "\
 .#.
@@ -782,7 +675,7 @@ mod test {
 
     #[test]
     fn test_input_generator() {
-        let input = input_generator(EXAMPLE_INPUT);
+        let input = input_generator(EXAMPLE_INPUT).unwrap();
 
         let pocket_dimension = PocketDimension3D::from_2d(&input);
         assert_eq!(
@@ -807,7 +700,7 @@ z=0, w=0
 
     #[test]
     fn test_step_3d() {
-        let input = input_generator(EXAMPLE_INPUT);
+        let input = input_generator(EXAMPLE_INPUT).unwrap();
         let mut pocket_dimension = PocketDimension3D::from_2d(&input);
         pocket_dimension.step();
         assert_eq!(
@@ -832,7 +725,7 @@ z=1
 
     #[test]
     fn test_simulate_3d() {
-        let input = input_generator(EXAMPLE_INPUT);
+        let input = input_generator(EXAMPLE_INPUT).unwrap();
         let mut pocket_dimension = PocketDimension3D::from_2d(&input);
         pocket_dimension.simulate(3);
         assert_eq!(
@@ -887,7 +780,7 @@ z=2
 
     #[test]
     fn test_count_active_cubes_3d() {
-        let input = input_generator(EXAMPLE_INPUT);
+        let input = input_generator(EXAMPLE_INPUT).unwrap();
         let mut pocket_dimension = PocketDimension3D::from_2d(&input);
         pocket_dimension.simulate(6);
         assert_eq!(pocket_dimension.count_active_cubes(), 112);
@@ -895,7 +788,7 @@ z=2
 
     #[test]
     fn test_step_4d() {
-        let input = input_generator(EXAMPLE_INPUT);
+        let input = input_generator(EXAMPLE_INPUT).unwrap();
         let mut pocket_dimension = PocketDimension4D::from_2d(&input);
         pocket_dimension.step();
         assert_eq!(
@@ -950,7 +843,7 @@ z=1, w=1
 
     #[test]
     fn test_simulate_4d() {
-        let input = input_generator(EXAMPLE_INPUT);
+        let input = input_generator(EXAMPLE_INPUT).unwrap();
         let mut pocket_dimension = PocketDimension4D::from_2d(&input);
         pocket_dimension.simulate(2);
         assert_eq!(
@@ -1135,9 +1028,131 @@ z=2, w=2
 
     #[test]
     fn test_count_active_cubes_4d() {
-        let input = input_generator(EXAMPLE_INPUT);
+        let input = input_generator(EXAMPLE_INPUT).unwrap();
         let mut pocket_dimension = PocketDimension4D::from_2d(&input);
         pocket_dimension.simulate(6);
         assert_eq!(pocket_dimension.count_active_cubes(), 848);
     }
+
+    #[test]
+    fn test_count_active_cubes_5d() {
+        let input = input_generator(EXAMPLE_INPUT).unwrap();
+        let mut pocket_dimension = PocketDimension::<5>::from_2d(&input);
+        pocket_dimension.simulate(6);
+        assert_eq!(pocket_dimension.count_active_cubes(), 5760);
+    }
+
+    #[test]
+    fn test_dense_matches_sparse() {
+        let input = input_generator(EXAMPLE_INPUT).unwrap();
+        let mut sparse = PocketDimension4D::from_2d(&input);
+        let mut dense = PocketDimension4D::from_2d_with(&input, Backend::Dense);
+        for _ in 0..6 {
+            sparse.step();
+            dense.step();
+            assert_eq!(dense.count_active_cubes(), sparse.count_active_cubes());
+            assert_eq!(dense.to_string(), sparse.to_string());
+        }
+        assert_eq!(dense.count_active_cubes(), 848);
+
+        let mut dense = PocketDimension3D::from_2d_with(&input, Backend::Dense);
+        dense.simulate(6);
+        assert_eq!(dense.count_active_cubes(), 112);
+    }
+
+    #[test]
+    fn test_record() {
+        let input = input_generator(EXAMPLE_INPUT).unwrap();
+        let mut pocket_dimension = PocketDimension4D::from_2d(&input);
+        let path = std::env::temp_dir().join(format!("aoc_2020_day_17_{}", std::process::id()));
+        let mut writer = FrameWriter::create(&path, FrameFormat::Ppm).unwrap();
+        pocket_dimension.record(2, &mut writer).unwrap();
+        assert_eq!(writer.frames(), 3);
+        assert_eq!(pocket_dimension.count_active_cubes(), 60);
+
+        // After two steps the cubes cover a 5x5 grid of z/w slices, each 5x5 with a gap between them
+        for frame in 0..3 {
+            let image = std::fs::read(path.join(format!("frame_{:05}.ppm", frame))).unwrap();
+            assert_eq!(&image[..13], b"P6\n29 29\n255\n");
+            assert_eq!(image.len(), 13 + 29 * 29 * 3);
+        }
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_orbit_size() {
+        assert_eq!(
+            SymmetricPocketDimension::<3>::orbit_size(&PointN::new([5, 5, 0])),
+            1
+        );
+        assert_eq!(
+            SymmetricPocketDimension::<3>::orbit_size(&PointN::new([5, 5, 2])),
+            2
+        );
+        assert_eq!(
+            SymmetricPocketDimension::<4>::orbit_size(&PointN::new([0, 0, 0, 1])),
+            4
+        );
+        assert_eq!(
+            SymmetricPocketDimension::<4>::orbit_size(&PointN::new([0, 0, 1, 1])),
+            4
+        );
+        assert_eq!(
+            SymmetricPocketDimension::<4>::orbit_size(&PointN::new([0, 0, 1, 2])),
+            8
+        );
+        assert_eq!(
+            SymmetricPocketDimension::<5>::orbit_size(&PointN::new([0, 0, 1, 2, 3])),
+            48
+        );
+        assert_eq!(
+            SymmetricPocketDimension::<5>::orbit_size(&PointN::new([0, 0, 0, 2, 2])),
+            12
+        );
+    }
+
+    fn check_symmetric<const N: usize>(steps: usize) {
+        let input = input_generator(EXAMPLE_INPUT).unwrap();
+        let mut naive = PocketDimension::<N>::from_2d(&input);
+        let mut symmetric = SymmetricPocketDimension::<N>::from_2d(&input);
+        for _ in 0..steps {
+            naive.step();
+            symmetric.step();
+
+            let naive_active: HashSet<PointN<N>> = naive.active_cubes().into_iter().collect();
+            assert_eq!(symmetric.unfold(), naive_active);
+            assert_eq!(symmetric.count_active_cubes(), naive.count_active_cubes());
+        }
+    }
+
+    #[test]
+    fn test_symmetric_matches_naive() {
+        check_symmetric::<3>(6);
+        check_symmetric::<4>(6);
+        check_symmetric::<5>(3);
+    }
+
+    #[test]
+    fn test_count_active_cubes_symmetric() {
+        let input = input_generator(EXAMPLE_INPUT).unwrap();
+        assert_eq!(simulate_symmetric::<3>(&input), 112);
+        assert_eq!(simulate_symmetric::<4>(&input), 848);
+        assert_eq!(simulate_symmetric::<5>(&input), 5760);
+        assert_eq!(simulate_symmetric::<6>(&input), 35936);
+    }
+
+    fn simulate_symmetric<const N: usize>(input: &TileSet) -> usize {
+        let mut pocket_dimension = SymmetricPocketDimension::<N>::from_2d(input);
+        pocket_dimension.simulate(6);
+        pocket_dimension.count_active_cubes()
+    }
+
+    #[test]
+    fn test_step_5d() {
+        let input = input_generator(EXAMPLE_INPUT).unwrap();
+        let mut pocket_dimension = PocketDimension::<5>::from_2d(&input);
+        assert!(pocket_dimension.to_string().starts_with("z=0, w=0, d4=0\n"));
+        pocket_dimension.step();
+        assert_eq!(pocket_dimension.get_range().range(4), -1..=1);
+    }
 }
@@ -55,7 +55,8 @@
     What do you get if you add up the results of evaluating the homework problems using these new rules?
 */
 
-use crate::common::{trim_start, unsigned};
+use crate::common::{parse_lines, trim_start, unsigned, ParseError};
+use crate::Solution;
 use nom::{
     branch::alt,
     character::complete::char,
@@ -179,11 +180,13 @@ fn precedence2(t: &Token) -> u32 {
 }
 
 #[aoc_generator(day18)]
-pub fn input_generator(input: &str) -> Vec<ExprInfix> {
-    input
-        .lines()
-        .map(|line| ExprInfix::parser(line).unwrap().1)
-        .collect()
+pub fn input_generator(input: &str) -> Result<Vec<ExprInfix>, ParseError> {
+    parse_lines(
+        18,
+        input,
+        "an expression of numbers, '+', '*' and parentheses",
+        ExprInfix::parser,
+    )
 }
 
 #[aoc(day18, part1)]
@@ -206,6 +209,28 @@ pub fn part2(input: &[ExprInfix]) -> u64 {
     sum
 }
 
+pub struct Day18;
+
+impl Solution for Day18 {
+    const DAY: u32 = 18;
+
+    type Input = Vec<ExprInfix>;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -220,7 +245,7 @@ mod test {
 
     #[test]
     fn test_evaluate() {
-        let expressions = input_generator(EXAMPLE_INPUT);
+        let expressions = input_generator(EXAMPLE_INPUT).unwrap();
         let results: Vec<u64> = expressions
             .iter()
             .map(|expr| expr.to_postfix(precedence1).evaluate())
@@ -228,7 +253,7 @@ mod test {
         let expected = [71, 51, 26, 437, 12240, 13632];
         assert_eq!(results, expected);
 
-        let expressions = input_generator(EXAMPLE_INPUT);
+        let expressions = input_generator(EXAMPLE_INPUT).unwrap();
         let results: Vec<u64> = expressions
             .iter()
             .map(|expr| expr.to_postfix(precedence2).evaluate())
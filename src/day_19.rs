@@ -129,7 +129,8 @@
     After updating rules 8 and 11, how many messages completely match rule 0?
 */
 
-use crate::common::{to_owned, trim_start, unsigned};
+use crate::common::{parse_input, to_owned, trim_start, unsigned, ParseError};
+use crate::Solution;
 use nom::{
     branch::alt,
     bytes::complete::tag,
@@ -216,7 +217,7 @@ impl<'a> FrameStack<'a> {
             Some(Frame::RulesIter(ref mut rules_iter)) => {
                 if let Some(rule_id) = rules_iter.next() {
                     // This frame has more rule IDs to process
-                    new_frame_stacks = self.process_rule(&rules[&rule_id]);
+                    new_frame_stacks = self.process_rule(&rules[rule_id]);
                 } else {
                     // This frame is done being processed. Remove it from the stack, but keep the stack on the frontier so its next frame can be processed.
                     self.stack.pop();
@@ -292,7 +293,7 @@ impl Comms {
 
         for msg_char in message.chars() {
             // First, process each frame stack until its current frame is at a value.
-            let mut frontier_frame_stacks: Vec<FrameStack> = frame_stacks.drain(..).collect();
+            let mut frontier_frame_stacks: Vec<FrameStack> = std::mem::take(&mut frame_stacks);
             while let Some(frame_stack) = frontier_frame_stacks.last_mut() {
                 let (done, mut new_frame_stacks) = frame_stack.advance_to_value(&self.rules);
                 match done {
@@ -338,8 +339,13 @@ impl Comms {
 }
 
 #[aoc_generator(day19)]
-pub fn input_generator(input: &str) -> Comms {
-    Comms::parser(input).unwrap().1
+pub fn input_generator(input: &str) -> Result<Comms, ParseError> {
+    parse_input(
+        19,
+        input,
+        "a rule like '0: 4 1 5' or a message of letters",
+        Comms::parser,
+    )
 }
 
 #[aoc(day19, part1)]
@@ -366,6 +372,28 @@ pub fn part2(input: &Comms) -> usize {
     count
 }
 
+pub struct Day19;
+
+impl Solution for Day19 {
+    const DAY: u32 = 19;
+
+    type Input = Comms;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -449,7 +477,7 @@ aabbbbbaabbbaaaaaabbbbbababaaaaabbaaabba";
 
     #[test]
     fn test_match_message() {
-        let comms = input_generator(EXAMPLE_INPUT1);
+        let comms = input_generator(EXAMPLE_INPUT1).unwrap();
         let messages = [
             ("aab", true),
             ("aba", true),
@@ -462,7 +490,7 @@ aabbbbbaabbbaaaaaabbbbbababaaaaabbaaabba";
             assert_eq!(comms.match_message(m), expected);
         }
 
-        let comms = input_generator(EXAMPLE_INPUT2);
+        let comms = input_generator(EXAMPLE_INPUT2).unwrap();
         let messages = [
             ("aaaabb", true),
             ("aaabab", true),
@@ -484,7 +512,7 @@ aabbbbbaabbbaaaaaabbbbbababaaaaabbaaabba";
 
     #[test]
     fn test_count_matches() {
-        let comms = input_generator(EXAMPLE_INPUT4);
+        let comms = input_generator(EXAMPLE_INPUT4).unwrap();
         let count = comms
             .messages
             .iter()
@@ -492,7 +520,7 @@ aabbbbbaabbbaaaaaabbbbbababaaaaabbaaabba";
             .count();
         assert_eq!(count, 3);
 
-        let mut comms = input_generator(EXAMPLE_INPUT4);
+        let mut comms = input_generator(EXAMPLE_INPUT4).unwrap();
         comms.patch_rules();
         let count = comms
             .messages
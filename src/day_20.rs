@@ -257,7 +257,11 @@
     How many # are not part of a sea monster?
 */
 
-use crate::common::{modulo, trim_start, unsigned, Point, TileChar, TileMap, TileSet};
+use crate::common::{
+    modulo, parse_input, trim_start, unsigned, Orientation, ParseError, Pattern, PatternMatch,
+    Point, TileSet, Transform,
+};
+use crate::Solution;
 use nom::{
     bytes::complete::tag,
     character::complete::char,
@@ -265,10 +269,14 @@ use nom::{
     sequence::{delimited, pair},
     IResult,
 };
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 const TILE_SIZE: usize = 10;
-const MAX_ROT: u32 = 4;
+const SEA_MONSTER: [&str; 3] = [
+    "                  # ",
+    "#    ##    ##    ###",
+    " #  #  #  #  #  #   ",
+];
 
 fn bit_reverse(input: &u32, size: usize) -> u32 {
     assert!(size > 0);
@@ -283,32 +291,6 @@ fn bit_reverse(input: &u32, size: usize) -> u32 {
     output
 }
 
-fn transform(pixels: &TileSet, orientation: TileOrientation) -> TileSet {
-    assert!(orientation.rotation < MAX_ROT);
-    let (mut x_range, mut y_range) = pixels.get_range().unwrap();
-    let mut output = TileSet::new();
-    for p in pixels.iter() {
-        let mut p_new = *p;
-        if orientation.flipped == true {
-            p_new.x = x_range.1 - (p_new.x - x_range.0);
-        }
-
-        for _ in 0..orientation.rotation {
-            let tmp = p_new.x;
-            p_new.x = x_range.0 + (y_range.1 - p_new.y);
-            p_new.y = y_range.0 + (tmp - x_range.0);
-
-            // Swap the ranges too since we're not zero-centered
-            let tmp = x_range;
-            x_range = (x_range.0, x_range.0 + y_range.1 - y_range.0);
-            y_range = (y_range.0, y_range.0 + tmp.1 - tmp.0);
-        }
-
-        output.insert(p_new);
-    }
-    output
-}
-
 #[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
 enum TileSide {
     Top = 0,
@@ -318,7 +300,7 @@ enum TileSide {
 }
 
 impl TileSide {
-    fn to_unit_point(&self) -> Point {
+    fn to_unit_point(self) -> Point {
         match self {
             Self::Top => (0, -1),
             Self::Right => (1, 0),
@@ -344,12 +326,6 @@ impl TileSide {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default, Eq, Ord, PartialEq, PartialOrd)]
-struct TileOrientation {
-    rotation: u32,
-    flipped: bool,
-}
-
 #[derive(Clone, Debug)]
 pub struct ImageTile {
     id: u64,
@@ -365,7 +341,7 @@ impl ImageTile {
             TileSet::parser('#'),
         )(input)?;
 
-        let (x_range, y_range) = pixels.get_range().unwrap();
+        let range = pixels.get_range().unwrap();
         fn make_side_id<I>(pixels: &TileSet, values: I) -> u32
         where
             I: Iterator<Item = Point>,
@@ -380,19 +356,19 @@ impl ImageTile {
         }
         let top_a = make_side_id(
             &pixels,
-            (x_range.0..=x_range.1).map(|x| Point { x, y: y_range.0 }),
+            range.x_range().map(|x| Point { x, y: range.min.y }),
         );
         let bottom_a = make_side_id(
             &pixels,
-            (x_range.0..=x_range.1).map(|x| Point { x, y: y_range.1 }),
+            range.x_range().map(|x| Point { x, y: range.max.y }),
         );
         let left_a = make_side_id(
             &pixels,
-            (y_range.0..=y_range.1).map(|y| Point { x: x_range.0, y }),
+            range.y_range().map(|y| Point { x: range.min.x, y }),
         );
         let right_a = make_side_id(
             &pixels,
-            (y_range.0..=y_range.1).map(|y| Point { x: x_range.1, y }),
+            range.y_range().map(|y| Point { x: range.max.x, y }),
         );
 
         // Flip horizontally; top and bottom are reversed bitwise, left and right swap
@@ -412,12 +388,15 @@ impl ImageTile {
         ))
     }
 
-    fn get_side_id(&self, side: TileSide, orientation: TileOrientation) -> u32 {
+    fn get_side_id(&self, side: TileSide, orientation: Orientation) -> u32 {
         // Side IDs are encoded left to right and top to bottom. So, when rotating,
         // the ID will flip sometimes - for example rotating 180 degrees means all
         // sides are now reverse of their original encoding.
-        let rot_idx = modulo(MAX_ROT - modulo(orientation.rotation, MAX_ROT), MAX_ROT);
-        let idx = modulo(side as u32 + rot_idx, MAX_ROT) as usize;
+        let rot_idx = modulo(
+            Orientation::ROTATIONS - modulo(orientation.rotation, Orientation::ROTATIONS),
+            Orientation::ROTATIONS,
+        );
+        let idx = modulo(side as u32 + rot_idx, Orientation::ROTATIONS) as usize;
 
         let ary = if orientation.flipped == false {
             &self.side_a_ids
@@ -437,29 +416,20 @@ impl ImageTile {
 
     fn all_side_ids(&self) -> Vec<u32> {
         Self::all_orientations()
-            .iter()
-            .map(|&(s, o)| self.get_side_id(s, o))
+            .map(|(s, o)| self.get_side_id(s, o))
             .collect()
     }
 
-    fn all_orientations() -> Vec<(TileSide, TileOrientation)> {
-        let mut output = Vec::new();
-        for side in TileSide::iter() {
-            for rotation in 0..MAX_ROT {
-                for &flipped in &[false, true] {
-                    output.push((side, TileOrientation { rotation, flipped }));
-                }
-            }
-        }
-        output
+    fn all_orientations() -> impl Iterator<Item = (TileSide, Orientation)> {
+        TileSide::iter().flat_map(|side| Orientation::all().map(move |o| (side, o)))
     }
 }
 
 impl std::fmt::Display for ImageTile {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let (x_range, y_range) = self.pixels.get_range().unwrap();
-        for y in y_range.0..=y_range.1 {
-            for x in x_range.0..=x_range.1 {
+        let range = self.pixels.get_range().unwrap();
+        for y in range.y_range() {
+            for x in range.x_range() {
                 if self.pixels.contains(&Point { x, y }) == true {
                     write!(f, "#")?;
                 } else {
@@ -475,7 +445,7 @@ impl std::fmt::Display for ImageTile {
 #[derive(Debug)]
 struct Image {
     tiles: HashMap<u64, ImageTile>,
-    possibilities: HashMap<u32, Vec<(u64, TileSide, TileOrientation)>>,
+    possibilities: HashMap<u32, Vec<(u64, TileSide, Orientation)>>,
     combined_pixels: TileSet,
 }
 
@@ -513,8 +483,7 @@ impl Image {
             .filter(|tile_id| {
                 TileSide::iter()
                     .filter(|&side| {
-                        let side_id =
-                            self.tiles[&tile_id].get_side_id(side, TileOrientation::default());
+                        let side_id = self.tiles[tile_id].get_side_id(side, Orientation::default());
                         let mut possible_tiles: Vec<u64> = self.possibilities[&side_id]
                             .iter()
                             .map(|&(t, ..)| t)
@@ -544,21 +513,21 @@ impl Image {
         fn place_tile(
             point: Point,
             tile_id: u64,
-            orientation: TileOrientation,
-            tile_map: &mut HashMap<Point, (u64, TileOrientation)>,
+            orientation: Orientation,
+            tile_map: &mut HashMap<Point, (u64, Orientation)>,
             unplaced_tiles: &mut Vec<u64>,
         ) {
             unplaced_tiles.remove(unplaced_tiles.iter().position(|x| *x == tile_id).unwrap());
             tile_map.insert(point, (tile_id, orientation));
         }
 
-        let mut tile_map: HashMap<Point, (u64, TileOrientation)> = HashMap::new();
+        let mut tile_map: HashMap<Point, (u64, Orientation)> = HashMap::new();
         let mut unplaced_tiles: Vec<u64> = self.tiles.keys().copied().collect();
         let mut frontier: Vec<Point> = Point::origin().orthogonals().collect();
         place_tile(
             Point::origin(),
             corners[0],
-            TileOrientation::default(),
+            Orientation::default(),
             &mut tile_map,
             &mut unplaced_tiles,
         );
@@ -569,7 +538,7 @@ impl Image {
             }
 
             // Get the candidate tiles; in practice there will be at most one!
-            let mut candidates: Vec<(u64, TileSide, TileOrientation)> = Vec::new();
+            let mut candidates: Vec<(u64, TileSide, Orientation)> = Vec::new();
             for side in TileSide::iter() {
                 if let Some(&(tile_id, orientation)) =
                     tile_map.get(&(next_point + side.to_unit_point()))
@@ -593,7 +562,7 @@ impl Image {
             assert_eq!(tile_ids.len(), 1);
 
             // Find the first candidate that meets all requirements
-            let mut picked: Option<(u64, TileOrientation)> = None;
+            let mut picked: Option<(u64, Orientation)> = None;
             for (candidate_tile_id, _, candidate_orientation) in candidates {
                 let all_ok = TileSide::iter().all(|side| {
                     if let Some(&(tile_id, orientation)) =
@@ -630,17 +599,21 @@ impl Image {
         // Now create the image from each tile
         self.combined_pixels.clear();
         for (tile_point, (tile_id, tile_orientation)) in tile_map {
-            let transformed_pixels = transform(&self.tiles[&tile_id].pixels, tile_orientation);
-            let (x_range, y_range) = transformed_pixels.get_range().unwrap();
-            let x_size = x_range.1 - x_range.0 - 1;
-            let y_size = y_range.1 - y_range.0 - 1;
+            let transformed_pixels = self.tiles[&tile_id]
+                .pixels
+                .orient(tile_orientation)
+                .normalize();
+            let range = transformed_pixels.get_range().unwrap();
+            let x_size = range.width() as i32 - 2;
+            let y_size = range.height() as i32 - 2;
+            let interior = range.shrink(1).unwrap();
             let base_point = Point {
                 x: tile_point.x * x_size,
                 y: tile_point.y * y_size,
             };
             for p in transformed_pixels.iter() {
                 // Strip off outer border
-                if p.x != x_range.0 && p.x != x_range.1 && p.y != y_range.0 && p.y != y_range.1 {
+                if interior.contains(p) == true {
                     self.combined_pixels.insert(base_point + p);
                 }
             }
@@ -648,89 +621,24 @@ impl Image {
     }
 
     fn find_sea_monsters(&self) -> usize {
-        #[derive(PartialEq)]
-        enum PixelTile {
-            Empty,
-            Wave,
-            Monster,
-        }
-
-        impl TileChar for PixelTile {
-            fn to_char(&self) -> char {
-                match self {
-                    Self::Empty => '.',
-                    Self::Wave => '#',
-                    Self::Monster => 'O',
-                }
-            }
-
-            fn from_char(c: char) -> Option<Self> {
-                Some(match c {
-                    '.' => Self::Empty,
-                    '#' => Self::Wave,
-                    'O' => Self::Monster,
-                    _ => return None,
-                })
-            }
-
-            fn all_chars() -> Vec<char> {
-                vec!['.', '#', 'O']
-            }
-        }
-
-        let sea_monster = "\
-..................#.
-#....##....##....###
-.#..#..#..#..#..#...";
-        let sea_monster_pixels = TileSet::from_string(sea_monster, '#');
-        let (sea_x_range, sea_y_range) = sea_monster_pixels.get_range().unwrap();
-        let sea_x_width = sea_x_range.1 - sea_x_range.0;
-        let sea_y_width = sea_y_range.1 - sea_y_range.0;
-
-        // Check each orientation; only one should show sea monsters
-        for &flipped in &[false, true] {
-            for rotation in 0.. {
-                let orientation = TileOrientation { rotation, flipped };
-                let input = transform(&self.combined_pixels, orientation);
-                let mut pixels_highlighted =
-                    TileMap::new().with_tiles(input.iter().map(|p| (p, PixelTile::Wave)));
-                let (x_range, y_range) = input.get_range().unwrap();
-                for y in y_range.0..=y_range.1 - sea_y_width {
-                    for x in x_range.0..=x_range.1 - sea_x_width {
-                        let mut found_monster = true;
-                        for sea_p in sea_monster_pixels.iter() {
-                            let offset_p = Point { x, y } + sea_p;
-                            if input.contains(&offset_p) == false {
-                                found_monster = false;
-                                break;
-                            }
-                        }
-
-                        if found_monster == true {
-                            for sea_p in sea_monster_pixels.iter() {
-                                let offset_p = Point { x, y } + sea_p;
-                                pixels_highlighted.insert(offset_p, PixelTile::Monster);
-                            }
-                        }
-                    }
-                }
+        // Only the monster's own pixels matter, the water around it can be anything
+        let sea_monster = Pattern::from_string(&SEA_MONSTER.join("\n"));
+        let found = self.combined_pixels.find_pattern(&sea_monster);
+
+        // Only one orientation of the image should show sea monsters, so anything that happens to look like
+        // one in another orientation is just rough water
+        let orientation = Orientation::all()
+            .find(|&orientation| found.iter().any(|m| m.orientation == orientation))
+            .expect("No sea monsters found!");
+        let found: Vec<PatternMatch> = found
+            .into_iter()
+            .filter(|m| m.orientation == orientation)
+            .collect();
 
-                if pixels_highlighted
-                    .values()
-                    .filter(|c| c == &&PixelTile::Monster)
-                    .count()
-                    != 0
-                {
-                    // Found the answer!
-                    //println!("{}", pixels_highlighted);
-                    return pixels_highlighted
-                        .values()
-                        .filter(|c| c == &&PixelTile::Wave)
-                        .count();
-                }
-            }
-        }
-        panic!("No sea monsters found!");
+        // Monsters can overlap, so count each of their pixels once
+        let monster_pixels: HashSet<Point> =
+            found.iter().flat_map(|m| sea_monster.tiles_at(m)).collect();
+        self.combined_pixels.len() - monster_pixels.len()
     }
 }
 
@@ -741,8 +649,13 @@ impl std::fmt::Display for Image {
 }
 
 #[aoc_generator(day20)]
-pub fn input_generator(input: &str) -> Vec<ImageTile> {
-    many1(ImageTile::parser)(input).unwrap().1
+pub fn input_generator(input: &str) -> Result<Vec<ImageTile>, ParseError> {
+    parse_input(
+        20,
+        input,
+        "an image tile like 'Tile 1234:' followed by its pixels",
+        many1(ImageTile::parser),
+    )
 }
 
 #[aoc(day20, part1)]
@@ -763,6 +676,28 @@ pub fn part2(input: &[ImageTile]) -> usize {
     roughness
 }
 
+pub struct Day20;
+
+impl Solution for Day20 {
+    const DAY: u32 = 20;
+
+    type Input = Vec<ImageTile>;
+    type Answer1 = u64;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -935,7 +870,7 @@ Tile 3079:
             ((TileSide::Left, 3, true), 0b0011010010),
         ];
         for &((s, r, f), ans) in expected.iter() {
-            let o = TileOrientation {
+            let o = Orientation {
                 rotation: r,
                 flipped: f,
             };
@@ -945,51 +880,13 @@ Tile 3079:
 
     #[test]
     fn test_corner_product() {
-        let image_tiles = input_generator(EXAMPLE_INPUT);
+        let image_tiles = input_generator(EXAMPLE_INPUT).unwrap();
         let image = Image::from_image_tiles(&image_tiles);
         let corners = image.find_corners();
         let product: u64 = corners.iter().product();
         assert_eq!(product, 20899048083289);
     }
 
-    #[test]
-    fn test_transform() {
-        let tiles: Vec<Point> = vec![(-2, -1), (2, -1), (2, 3), (-2, 3)]
-            .into_iter()
-            .map(|p| p.into())
-            .collect();
-        let expected = TileSet::new().with_tiles(&tiles);
-
-        let transformed = expected.clone();
-        let orientation = TileOrientation {
-            rotation: 1,
-            flipped: false,
-        };
-        let transformed = transform(&transformed, orientation);
-        let transformed = transform(&transformed, orientation);
-        let transformed = transform(&transformed, orientation);
-        let transformed = transform(&transformed, orientation);
-        assert_eq!(expected, transformed);
-
-        let transformed = expected.clone();
-        let orientation = TileOrientation {
-            rotation: 2,
-            flipped: false,
-        };
-        let transformed = transform(&transformed, orientation);
-        let transformed = transform(&transformed, orientation);
-        assert_eq!(expected, transformed);
-
-        let transformed = expected.clone();
-        let orientation = TileOrientation {
-            rotation: 0,
-            flipped: true,
-        };
-        let transformed = transform(&transformed, orientation);
-        let transformed = transform(&transformed, orientation);
-        assert_eq!(expected, transformed);
-    }
-
     #[test]
     fn test_image_assemble() {
         let expected = "\
@@ -1018,19 +915,16 @@ Tile 3079:
 .#.###..##..##..####.##.
 ...###...##...#...#..###";
 
-        let image_tiles = input_generator(EXAMPLE_INPUT);
+        let image_tiles = input_generator(EXAMPLE_INPUT).unwrap();
         let mut image = Image::from_image_tiles(&image_tiles);
         image.assemble();
 
         let original = image.combined_pixels.clone();
         let mut any = false;
-        for &flipped in &[false, true] {
-            for rotation in 0..MAX_ROT {
-                let orientation = TileOrientation { rotation, flipped };
-                image.combined_pixels = transform(&original, orientation);
-                if image.to_string().trim() == expected {
-                    any = true;
-                }
+        for orientation in Orientation::all() {
+            image.combined_pixels = original.orient(orientation);
+            if image.to_string().trim() == expected {
+                any = true;
             }
         }
         assert!(any);
@@ -1038,10 +932,25 @@ Tile 3079:
 
     #[test]
     fn test_find_sea_monsters() {
-        let image_tiles = input_generator(EXAMPLE_INPUT);
+        let image_tiles = input_generator(EXAMPLE_INPUT).unwrap();
         let mut image = Image::from_image_tiles(&image_tiles);
         image.assemble();
         let roughness = image.find_sea_monsters();
         assert_eq!(roughness, 273);
+
+        // A monster upside down below one the right way up is only rough water, since the image can't be
+        // both ways up at once
+        let upside_down: Vec<String> = SEA_MONSTER
+            .iter()
+            .rev()
+            .map(|line| line.chars().rev().collect())
+            .collect();
+        let pixels = format!("{}\n.\n{}", SEA_MONSTER.join("\n"), upside_down.join("\n"));
+        let image = Image {
+            tiles: HashMap::new(),
+            possibilities: HashMap::new(),
+            combined_pixels: TileSet::from_string(&pixels, '#'),
+        };
+        assert_eq!(image.find_sea_monsters(), 15);
     }
 }
@@ -33,7 +33,8 @@
     Time to stock your raft with supplies. What is your canonical dangerous ingredient list?
 */
 
-use crate::common::{to_owned, trim_start};
+use crate::common::{parse_input, to_owned, trim_start, ParseError};
+use crate::Solution;
 use nom::{
     bytes::complete::tag,
     character::complete::{alpha1, char},
@@ -118,7 +119,7 @@ impl FoodList {
         // First, find all ingredients that have no possible allergens
         let mut no_allergens: HashSet<Ingredient> = self.ingredients.clone();
         for a in &self.allergens {
-            for i in self.possible_ingredients(&a) {
+            for i in self.possible_ingredients(a) {
                 no_allergens.remove(&i);
             }
         }
@@ -178,8 +179,13 @@ impl FoodList {
 }
 
 #[aoc_generator(day21)]
-pub fn input_generator(input: &str) -> FoodList {
-    FoodList::parser(input).unwrap().1
+pub fn input_generator(input: &str) -> Result<FoodList, ParseError> {
+    parse_input(
+        21,
+        input,
+        "a food like 'mxmxvkd kfcds (contains dairy, fish)'",
+        FoodList::parser,
+    )
 }
 
 #[aoc(day21, part1)]
@@ -197,6 +203,28 @@ pub fn part2(input: &FoodList) -> String {
     string
 }
 
+pub struct Day21;
+
+impl Solution for Day21 {
+    const DAY: u32 = 21;
+
+    type Input = FoodList;
+    type Answer1 = usize;
+    type Answer2 = String;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -209,14 +237,14 @@ sqjhc mxmxvkd sbzzf (contains fish)";
 
     #[test]
     fn test_no_allergen_count() {
-        let food_list = input_generator(EXAMPLE_INPUT);
+        let food_list = input_generator(EXAMPLE_INPUT).unwrap();
         let count = food_list.no_allergen_count();
         assert_eq!(count, 5);
     }
 
     #[test]
     fn test_match_ingredients_allergens() {
-        let food_list = input_generator(EXAMPLE_INPUT);
+        let food_list = input_generator(EXAMPLE_INPUT).unwrap();
         let pairs = food_list.match_ingredients_allergens();
         assert_eq!(
             pairs,
@@ -230,7 +258,7 @@ sqjhc mxmxvkd sbzzf (contains fish)";
 
     #[test]
     fn test_ingredient_string() {
-        let food_list = input_generator(EXAMPLE_INPUT);
+        let food_list = input_generator(EXAMPLE_INPUT).unwrap();
         let pairs = food_list.match_ingredients_allergens();
         let string = FoodList::ingredient_string(&pairs);
         assert_eq!(string, "mxmxvkd,sqjhc,fvjkl".to_string());
@@ -370,7 +370,8 @@
     Defend your honor as Raft Captain by playing the small crab in a game of Recursive Combat using the same two decks as before. What is the winning player's score?
 */
 
-use crate::common::{trim_start, unsigned};
+use crate::common::{parse_input, trim_start, unsigned, ParseError};
+use crate::Solution;
 use nom::{
     bytes::complete::tag,
     multi::many1,
@@ -425,8 +426,8 @@ impl Game {
     }
 
     fn new_game(&self, player_card: u8, crab_card: u8) -> Self {
-        let new_player_cards = (&self.player.cards[0..player_card as usize]).to_vec();
-        let new_crab_cards = (&self.crab.cards[0..crab_card as usize]).to_vec();
+        let new_player_cards = self.player.cards[0..player_card as usize].to_vec();
+        let new_crab_cards = self.crab.cards[0..crab_card as usize].to_vec();
         Self {
             player: Deck {
                 cards: new_player_cards,
@@ -542,8 +543,13 @@ impl Game {
 }
 
 #[aoc_generator(day22)]
-pub fn input_generator(input: &str) -> Game {
-    Game::parser(input).unwrap().1
+pub fn input_generator(input: &str) -> Result<Game, ParseError> {
+    parse_input(
+        22,
+        input,
+        "a deck of cards after 'Player 1:' and 'Player 2:'",
+        Game::parser,
+    )
 }
 
 #[aoc(day22, part1)]
@@ -564,6 +570,28 @@ pub fn part2(input: &Game) -> u32 {
     score
 }
 
+pub struct Day22;
+
+impl Solution for Day22 {
+    const DAY: u32 = 22;
+
+    type Input = Game;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -585,7 +613,7 @@ Player 2:
 
     #[test]
     fn test_play_round() {
-        let mut game = input_generator(EXAMPLE_INPUT);
+        let mut game = input_generator(EXAMPLE_INPUT).unwrap();
 
         game.play_round();
         assert_eq!(game.player.cards, [2, 6, 3, 1, 9, 5]);
@@ -624,7 +652,7 @@ Player 2:
 
     #[test]
     fn test_play_game() {
-        let mut game = input_generator(EXAMPLE_INPUT);
+        let mut game = input_generator(EXAMPLE_INPUT).unwrap();
         let winner = game.play_game();
         assert_eq!(winner, Player::P2);
         assert_eq!(game.score(), 306);
@@ -632,7 +660,7 @@ Player 2:
 
     #[test]
     fn test_play_round_recursive() {
-        let mut game = input_generator(EXAMPLE_INPUT);
+        let mut game = input_generator(EXAMPLE_INPUT).unwrap();
         assert_eq!(game.player.cards, [9, 2, 6, 3, 1]);
         assert_eq!(game.crab.cards, [5, 8, 4, 7, 10]);
 
@@ -707,7 +735,7 @@ Player 2:
 
     #[test]
     fn test_play_game_recursive() {
-        let mut game = input_generator(EXAMPLE_INPUT);
+        let mut game = input_generator(EXAMPLE_INPUT).unwrap();
         let winner = game.play_game_recursive();
         assert_eq!(winner, Player::P2);
         assert_eq!(game.score(), 291);
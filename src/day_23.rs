@@ -86,6 +86,8 @@
     Determine which two cups will end up immediately clockwise of cup 1. What do you get if you multiply their labels together?
 */
 
+use crate::common::{parse_input, ParseError};
+use crate::Solution;
 use nom::{character::complete::one_of, combinator::map, multi::many1, IResult};
 use std::fmt::Write;
 
@@ -199,8 +201,13 @@ impl std::fmt::Display for CrabCups {
 }
 
 #[aoc_generator(day23)]
-pub fn input_generator(input: &str) -> Vec<u32> {
-    u32_list_parser(input).unwrap().1
+pub fn input_generator(input: &str) -> Result<Vec<u32>, ParseError> {
+    parse_input(
+        23,
+        input,
+        "a list of cup labels (digits 1-9)",
+        u32_list_parser,
+    )
 }
 
 #[aoc(day23, part1)]
@@ -223,6 +230,28 @@ pub fn part2(input: &[u32]) -> u64 {
     label_product
 }
 
+pub struct Day23;
+
+impl Solution for Day23 {
+    const DAY: u32 = 23;
+
+    type Input = Vec<u32>;
+    type Answer1 = String;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -231,7 +260,7 @@ mod test {
 
     #[test]
     fn test_from_list() {
-        let input = input_generator(EXAMPLE_INPUT);
+        let input = input_generator(EXAMPLE_INPUT).unwrap();
 
         let crab_cups = CrabCups::from_list(&input, 9);
         assert_eq!(crab_cups.cups, [0, 2, 5, 8, 6, 4, 7, 3, 9, 1]);
@@ -245,7 +274,7 @@ mod test {
 
     #[test]
     fn test_make_move() {
-        let input = input_generator(EXAMPLE_INPUT);
+        let input = input_generator(EXAMPLE_INPUT).unwrap();
         let mut crab_cups = CrabCups::from_list(&input, 9);
         assert_eq!(crab_cups.to_string(), "389125467");
 
@@ -283,7 +312,7 @@ mod test {
     #[test]
     #[ignore]
     fn test_cups_order() {
-        let input = input_generator(EXAMPLE_INPUT);
+        let input = input_generator(EXAMPLE_INPUT).unwrap();
 
         let mut crab_cups = CrabCups::from_list(&input, 9);
         crab_cups.make_move(10);
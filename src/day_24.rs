@@ -76,9 +76,11 @@
     How many tiles will be black after 100 days?
 */
 
-use crate::common::trim_start;
+use crate::common::{
+    parse_lines, trim_start, Backend, HexPoint, LifeLike, ParseError, Point, Simulation, TileSet,
+};
+use crate::Solution;
 use nom::{branch::alt, bytes::complete::tag, combinator::value, multi::many1, IResult};
-use std::collections::{HashMap, HashSet};
 
 #[derive(Clone, Copy)]
 pub enum HexDir {
@@ -101,119 +103,92 @@ impl HexDir {
             value(Self::NorthEast, tag("ne")),
         )))(input)
     }
-}
-
-#[derive(Clone, Copy, Eq, Hash, PartialEq)]
-struct HexPoint {
-    x: i8,
-    y: i8,
-    z: i8,
-}
-
-impl HexPoint {
-    fn new() -> Self {
-        Self { x: 0, y: 0, z: 0 }
-    }
 
-    fn step(&self, dir: &HexDir) -> Self {
-        let (dx, dy, dz) = match dir {
-            HexDir::East => (1, -1, 0),
-            HexDir::SouthEast => (0, -1, 1),
-            HexDir::SouthWest => (-1, 0, 1),
-            HexDir::West => (-1, 1, 0),
-            HexDir::NorthWest => (0, 1, -1),
-            HexDir::NorthEast => (1, 0, -1),
-        };
-
-        Self {
-            x: self.x + dx,
-            y: self.y + dy,
-            z: self.z + dz,
-        }
-    }
-
-    fn adjacents(&self) -> [Self; 6] {
-        [
-            self.step(&HexDir::East),
-            self.step(&HexDir::SouthEast),
-            self.step(&HexDir::SouthWest),
-            self.step(&HexDir::West),
-            self.step(&HexDir::NorthWest),
-            self.step(&HexDir::NorthEast),
-        ]
+    fn offset(&self) -> HexPoint {
+        // Directions are declared in the same clockwise order as HexPoint's
+        HexPoint::DIRECTIONS[*self as usize]
     }
 }
 
+// Black tiles are live. They stay black with one or two black neighbors, and white tiles flip with exactly
+// two.
+const FLIP_RULE: &str = "B2/S12H";
+
 struct Floor {
-    tiles: HashSet<HexPoint>,
+    tiles: Box<dyn Simulation<HexPoint>>,
 }
 
 impl Floor {
     fn from_rules(rules: &[Vec<HexDir>]) -> Self {
-        let mut tiles = HashSet::new();
+        Self::from_rules_with(rules, Backend::Sparse)
+    }
+
+    fn from_rules_with(rules: &[Vec<HexDir>], backend: Backend) -> Self {
+        let flip_rule: LifeLike = FLIP_RULE.parse().unwrap();
+        let mut tiles = flip_rule
+            .hex_simulation(backend)
+            .expect("Backend doesn't support hex grids");
         for rule in rules {
             // Walk through the directions
-            let mut point = HexPoint::new();
+            let mut point = HexPoint::origin();
             for dir in rule {
-                point = point.step(dir);
+                point += dir.offset();
             }
 
             // Toggle the final tile
-            if tiles.contains(&point) == true {
-                tiles.remove(&point);
-            } else {
-                tiles.insert(point);
-            }
+            let black = tiles.is_live(&point);
+            tiles.set_live(point, !black);
         }
 
         Self { tiles }
     }
 
     fn count_black(&self) -> usize {
-        self.tiles.len()
+        self.tiles.count_live()
     }
 
-    fn days_passed(&mut self, num: usize) {
-        let mut counts: HashMap<HexPoint, u8> = HashMap::new();
-        for _ in 0..num {
-            counts.clear();
-            for t in &self.tiles {
-                for adj in &t.adjacents() {
-                    let e = counts.entry(*adj).or_insert(0);
-                    *e += 1;
-                }
-            }
+    // Lays the hexes out in rows of constant z, with x and y doubled so neighboring rows interleave. Each
+    // tile is two columns apart from the ones east and west of it, and one column from those in the rows
+    // above and below.
+    fn to_tileset(&self) -> TileSet {
+        let tiles: Vec<Point> = self
+            .tiles
+            .live_cells()
+            .iter()
+            .map(|h| Point {
+                x: 2 * h.x + h.z,
+                y: h.z,
+            })
+            .collect();
+        TileSet::new().with_tiles(&tiles)
+    }
 
-            self.tiles = counts
-                .iter()
-                .filter_map(|(&tile, &count)| {
-                    if self.tiles.contains(&tile) {
-                        // Black tile rules
-                        if count == 0 || count > 2 {
-                            None // Flip to white (don't do anything)
-                        } else {
-                            Some(tile) // Keep as black
-                        }
-                    } else {
-                        // White tile rules
-                        if count == 2 {
-                            Some(tile) // Flip to black
-                        } else {
-                            None // Keep as white (don't do anything)
-                        }
-                    }
-                })
-                .collect();
-        }
+    fn days_passed(&mut self, num: usize) {
+        self.tiles.advance(num);
     }
 }
 
+// The black tiles the floor starts with, using the first two cube coordinates of each like LifeLike does for
+// hex neighborhoods, so any rule can be run on them
+pub fn life_seed(input: &str) -> Result<TileSet, ParseError> {
+    let floor = Floor::from_rules(&input_generator(input)?);
+    let tiles: Vec<Point> = floor
+        .tiles
+        .live_cells()
+        .iter()
+        .map(|h| Point { x: h.x, y: h.y })
+        .collect();
+    Ok(TileSet::new().with_tiles(&tiles))
+}
+
 #[aoc_generator(day24)]
-pub fn input_generator(input: &str) -> Vec<Vec<HexDir>> {
-    input
-        .lines()
-        .map(|line| many1(HexDir::parser)(line).unwrap().1)
-        .collect()
+pub fn input_generator(input: &str) -> Result<Vec<Vec<HexDir>>, ParseError> {
+    parse_lines(
+        24,
+        input,
+        "a hex direction (e, se, sw, w, nw or ne)",
+        many1(HexDir::parser),
+    )
 }
 
 #[aoc(day24, part1)]
@@ -226,16 +201,39 @@ pub fn part1(input: &[Vec<HexDir>]) -> usize {
 
 #[aoc(day24, part2)]
 pub fn part2(input: &[Vec<HexDir>]) -> usize {
-    let mut floor = Floor::from_rules(input);
+    let mut floor = Floor::from_rules_with(input, Backend::Dense);
     floor.days_passed(100);
     let black = floor.count_black();
     assert_eq!(black, 4353);
     black
 }
 
+pub struct Day24;
+
+impl Solution for Day24 {
+    const DAY: u32 = 24;
+
+    type Input = Vec<Vec<HexDir>>;
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input) -> Self::Answer2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::common::{NetpbmFormat, NetpbmWriter};
 
     static EXAMPLE_INPUT: &str = "\
 sesenwnenenewseeswwswswwnenewsewsw
@@ -261,15 +259,55 @@ wseweeenwnesenwwwswnew";
 
     #[test]
     fn test_setup() {
-        let input = input_generator(EXAMPLE_INPUT);
+        let input = input_generator(EXAMPLE_INPUT).unwrap();
         let floor = Floor::from_rules(&input);
         assert_eq!(floor.count_black(), 10);
     }
 
+    #[test]
+    fn test_dense_matches_sparse() {
+        let input = input_generator(EXAMPLE_INPUT).unwrap();
+        let mut sparse = Floor::from_rules(&input);
+        let mut dense = Floor::from_rules_with(&input, Backend::Dense);
+        for _ in 0..20 {
+            sparse.days_passed(1);
+            dense.days_passed(1);
+            assert_eq!(dense.count_black(), sparse.count_black());
+        }
+        assert_eq!(dense.count_black(), 132);
+    }
+
+    #[test]
+    fn test_life_seed() {
+        let seed = life_seed(EXAMPLE_INPUT).unwrap();
+        assert_eq!(seed.len(), 10);
+        let flip_rule: LifeLike = FLIP_RULE.parse().unwrap();
+        assert_eq!(flip_rule.simulate(&seed, 20), 132);
+    }
+
+    #[test]
+    fn test_to_tileset() {
+        // The reference tile, the one east of it and the two below them
+        let input = input_generator("nwse\ne\nse\nsw\n").unwrap();
+        let floor = Floor::from_rules(&input);
+        let tileset = floor.to_tileset();
+        assert_eq!(tileset.to_string(), ".#.#\n#.#.\n");
+
+        let writer = NetpbmWriter::new(NetpbmFormat::Ppm)
+            .with_scale(2)
+            .with_color('#', [20, 20, 20]);
+        let image = writer.tileset_image(&tileset);
+        assert_eq!((image.width(), image.height()), (8, 4));
+        assert_eq!(image.get(2, 0), [20, 20, 20]);
+        assert_eq!(image.get(1, 1), [255, 255, 255]);
+        assert_eq!(image.get(0, 2), [20, 20, 20]);
+        assert_eq!(image.get(6, 3), [255, 255, 255]);
+    }
+
     #[test]
     #[ignore]
     fn test_days_passed() {
-        let input = input_generator(EXAMPLE_INPUT);
+        let input = input_generator(EXAMPLE_INPUT).unwrap();
         let mut floor = Floor::from_rules(&input);
 
         floor.days_passed(1);
@@ -51,7 +51,8 @@
     Looks like you only needed 49 stars after all.
 */
 
-use crate::common::{trim_start, unsigned};
+use crate::common::{parse_input, trim_start, unsigned, ParseError};
+use crate::Solution;
 use nom::{sequence::pair, IResult};
 
 pub struct Handshake {
@@ -102,8 +103,13 @@ impl Handshake {
 }
 
 #[aoc_generator(day25)]
-pub fn input_generator(input: &str) -> Handshake {
-    Handshake::parser(input).unwrap().1
+pub fn input_generator(input: &str) -> Result<Handshake, ParseError> {
+    parse_input(
+        25,
+        input,
+        "the card and door public keys",
+        Handshake::parser,
+    )
 }
 
 #[aoc(day25, part1)]
@@ -113,6 +119,29 @@ pub fn part1(input: &Handshake) -> u64 {
     encryption_key
 }
 
+pub struct Day25;
+
+impl Solution for Day25 {
+    const DAY: u32 = 25;
+    const PARTS: u32 = 1;
+
+    type Input = Handshake;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input_generator(input)
+    }
+
+    fn part1(input: &Self::Input) -> Self::Answer1 {
+        part1(input)
+    }
+
+    fn part2(_input: &Self::Input) -> Self::Answer2 {
+        unreachable!("Day 25 only has one part")
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
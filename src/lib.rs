@@ -1,3 +1,4 @@
+#![allow(clippy::bool_assert_comparison)]
 #![allow(clippy::bool_comparison)]
 #![allow(clippy::needless_bool)]
 #![allow(dead_code)]
@@ -7,8 +8,11 @@
 extern crate aoc_runner_derive;
 
 use aoc_runner_derive::aoc_lib;
+use common::{Backend, LifeLike};
+use std::{any::Any, fmt::Display, marker::PhantomData};
 
 mod common;
+mod console;
 mod day_01;
 mod day_02;
 mod day_03;
@@ -35,4 +39,200 @@ mod day_23;
 mod day_24;
 mod day_25;
 
+pub use common::ParseError;
+pub use console::{debug, trace, TraceFormat, TRACE_STEP_LIMIT};
+
+pub trait Solution {
+    const DAY: u32;
+    const PARTS: u32 = 2;
+
+    type Input: 'static;
+    type Answer1: Display;
+    type Answer2: Display;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError>;
+    fn part1(input: &Self::Input) -> Self::Answer1;
+    fn part2(input: &Self::Input) -> Self::Answer2;
+}
+
+// Object safe version of Solution, so that every day can be stored in the same registry. The parsed
+// input is passed around as Any and handed back to the same day to run.
+pub trait DaySolution: Sync {
+    fn day(&self) -> u32;
+    fn parts(&self) -> u32;
+    fn parse(&self, input: &str) -> Result<Box<dyn Any>, ParseError>;
+    fn run(&self, input: &dyn Any, part: u32) -> String;
+}
+
+struct Registered<S>(PhantomData<S>);
+
+impl<S> DaySolution for Registered<S>
+where
+    S: Solution + Sync,
+{
+    fn day(&self) -> u32 {
+        S::DAY
+    }
+
+    fn parts(&self) -> u32 {
+        S::PARTS
+    }
+
+    fn parse(&self, input: &str) -> Result<Box<dyn Any>, ParseError> {
+        S::parse(input).map(|input| Box::new(input) as Box<dyn Any>)
+    }
+
+    fn run(&self, input: &dyn Any, part: u32) -> String {
+        let input = input
+            .downcast_ref::<S::Input>()
+            .unwrap_or_else(|| panic!("Input was not parsed by day {}", S::DAY));
+        match part {
+            1 => S::part1(input).to_string(),
+            2 if S::PARTS >= 2 => S::part2(input).to_string(),
+            _ => panic!("Day {} has no part {}", S::DAY, part),
+        }
+    }
+}
+
+pub static SOLUTIONS: [&dyn DaySolution; 25] = [
+    &Registered::<day_01::Day01>(PhantomData),
+    &Registered::<day_02::Day02>(PhantomData),
+    &Registered::<day_03::Day03>(PhantomData),
+    &Registered::<day_04::Day04>(PhantomData),
+    &Registered::<day_05::Day05>(PhantomData),
+    &Registered::<day_06::Day06>(PhantomData),
+    &Registered::<day_07::Day07>(PhantomData),
+    &Registered::<day_08::Day08>(PhantomData),
+    &Registered::<day_09::Day09>(PhantomData),
+    &Registered::<day_10::Day10>(PhantomData),
+    &Registered::<day_11::Day11>(PhantomData),
+    &Registered::<day_12::Day12>(PhantomData),
+    &Registered::<day_13::Day13>(PhantomData),
+    &Registered::<day_14::Day14>(PhantomData),
+    &Registered::<day_15::Day15>(PhantomData),
+    &Registered::<day_16::Day16>(PhantomData),
+    &Registered::<day_17::Day17>(PhantomData),
+    &Registered::<day_18::Day18>(PhantomData),
+    &Registered::<day_19::Day19>(PhantomData),
+    &Registered::<day_20::Day20>(PhantomData),
+    &Registered::<day_21::Day21>(PhantomData),
+    &Registered::<day_22::Day22>(PhantomData),
+    &Registered::<day_23::Day23>(PhantomData),
+    &Registered::<day_24::Day24>(PhantomData),
+    &Registered::<day_25::Day25>(PhantomData),
+];
+
+pub fn solution(day: u32) -> Option<&'static dyn DaySolution> {
+    SOLUTIONS.iter().copied().find(|s| s.day() == day)
+}
+
+// Runs a life-like rule such as "B3/S23/4D" or "B2/S12H" on the starting pattern from day 17 or day 24's
+// input, or a rule such as "B0/S01234L" on day 11's seats, to try other rules on them without recompiling.
+// Returns how many cells are live after the given number of steps, which defaults to as many as the puzzle
+// runs for. Day 11's waiting area is a bounded grid which is always simulated the same way, whatever the
+// backend, and it's the only one which can run B0 rules.
+pub fn life(
+    day: u32,
+    input: &str,
+    rule: &str,
+    steps: Option<usize>,
+    backend: &str,
+) -> Result<usize, String> {
+    let life_like: LifeLike = rule.parse().map_err(|_| {
+        format!(
+            "invalid rule '{}', expected B/S notation like B3/S23, B2/S12H, B3/S23/4D or B0/S0123L",
+            rule
+        )
+    })?;
+    let backend_type = Backend::from_str(backend).ok_or(format!(
+        "invalid backend '{}', expected sparse, dense or hashlife",
+        backend
+    ))?;
+    let (seed, default_steps) = match day {
+        11 => return day_11::life(input, &life_like, steps),
+        17 => (day_17::input_generator(input), 6),
+        24 => (day_24::life_seed(input), 100),
+        _ => {
+            return Err(format!(
+                "life only supports days 11, 17 and 24, not {}",
+                day
+            ))
+        }
+    };
+    if life_like.births_from_nothing() == true {
+        return Err(format!(
+            "the rule {} brings empty space to life, which only day 11's bounded grid can run",
+            life_like
+        ));
+    }
+    let seed = seed.map_err(|e| e.to_string())?;
+    life_like
+        .simulate_with(&seed, steps.unwrap_or(default_steps), backend_type)
+        .ok_or(format!(
+            "the {} backend doesn't support the rule {}",
+            backend, life_like
+        ))
+}
+
 aoc_lib! { year = 2020 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_registry() {
+        for (i, solution) in SOLUTIONS.iter().enumerate() {
+            assert_eq!(solution.day(), i as u32 + 1);
+            let expected_parts = if solution.day() == 25 { 1 } else { 2 };
+            assert_eq!(solution.parts(), expected_parts);
+        }
+        assert_eq!(solution(8).map(|s| s.day()), Some(8));
+        assert!(solution(26).is_none());
+    }
+
+    #[test]
+    fn test_life() {
+        let seed = ".#.\n..#\n###";
+        assert_eq!(life(17, seed, "B3/S23/3D", None, "sparse"), Ok(112));
+        assert_eq!(life(17, seed, "B3/S23/4D", Some(6), "dense"), Ok(848));
+        assert_eq!(life(17, seed, "B3/S23", Some(4), "hashlife"), Ok(5));
+        assert!(life(17, seed, "B0/S23", None, "sparse").is_err());
+        assert!(life(17, seed, "B3/S23/3D", None, "hashlife").is_err());
+        assert!(life(17, seed, "B3/S23", None, "quadtree").is_err());
+        assert!(life(12, seed, "B3/S23", None, "sparse").is_err());
+
+        // Day 11's puzzle rules, on its example
+        let seats = "\
+L.LL.LL.LL
+LLLLLLL.LL
+L.L.L..L..
+LLLL.LL.LL
+L.LL.LL.LL
+L.LLLLL.LL
+..L.L.....
+LLLLLLLLLL
+L.LLLLLL.L
+L.LLLLL.LL";
+        assert_eq!(life(11, seats, "B0/S0123", None, "sparse"), Ok(37));
+        assert_eq!(life(11, seats, "B0/S01234L", None, "dense"), Ok(26));
+        assert_eq!(life(11, seats, "B0/S0123", Some(1), "sparse"), Ok(71));
+        assert!(life(11, seats, "B3/S23/3D", None, "sparse").is_err());
+    }
+
+    #[test]
+    fn test_registry_parse() {
+        let day8 = solution(8).unwrap();
+        assert!(day8.parse("nop +0\nacc +1\njmp -2").is_ok());
+        assert_eq!(
+            day8.parse("nop +0\nfoo +1").err(),
+            Some(ParseError::new(
+                8,
+                2,
+                1,
+                "an instruction (acc, jmp or nop) with a signed argument",
+                "foo +1"
+            ))
+        );
+    }
+}
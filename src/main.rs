@@ -1,3 +1,654 @@
-use aoc_runner_derive::aoc_main;
+#![allow(clippy::bool_assert_comparison)]
+#![allow(clippy::bool_comparison)]
+#![forbid(unsafe_code)]
 
-aoc_main! { lib = advent_of_code_2020 }
+mod answers;
+mod bench;
+
+use advent_of_code_2020::*;
+use answers::Answers;
+use bench::{Format, Measurement};
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Read},
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
+    process,
+    time::{Duration, Instant},
+};
+
+const USAGE: &str = "\
+Usage: advent_of_code_2020 [bench | verify | debug | trace | life] [OPTIONS]
+
+Options:
+    -d, --day DAY       Only run the given day (1-25)
+    -p, --part PART     Only run the given part (1-2)
+    -i, --input PATH    Read the input from PATH instead of input/2020/dayD.txt,
+                        or from stdin if PATH is '-'. Requires --day.
+    -h, --help          Print this message
+
+Bench options:
+    -n, --runs N            Time each part N times (default 10)
+    -f, --format FORMAT     Write the results as 'csv' (default) or 'json'
+    -o, --output PATH       Write the results to PATH instead of stdout
+    -b, --baseline PATH     Compare the median times against a previous result file
+    -t, --threshold PCT     Flag parts more than PCT percent slower than the baseline (default 10)
+
+Verify options:
+    -a, --answers PATH      Read the expected answers from PATH (default answers.toml)
+
+Debug and trace run the day 8 boot code, or the assembly program given with --input. Debug reads
+commands from stdin, type 'help' at the prompt for the list. Trace writes every step the program takes,
+and a summary of the run to stderr.
+
+Trace options:
+    -f, --format FORMAT     Write the steps as 'csv' (default) or 'jsonl'
+    -o, --output PATH       Write the steps to PATH instead of stdout
+    -n, --max-steps N       Stop after N steps (default 1000000)
+
+Life runs a life-like rule on the seats from day 11, the starting cubes from day 17 or the black tiles from
+day 24, which needs --day, and prints how many cells are live at the end.
+
+Life options:
+    -r, --rule RULE         The rule in B/S notation, e.g. B3/S23/4D, B2/S12H or B0/S01234L
+    -n, --steps N           Run N steps (default until stable for day 11, 6 for day 17, 100 for day 24)
+    -b, --backend BACKEND   Simulate with 'sparse' (default), 'dense' or 'hashlife'";
+
+enum InputSource {
+    Default,
+    Path(PathBuf),
+    Stdin,
+}
+
+impl InputSource {
+    fn read(&self, day: u32) -> Result<String, String> {
+        match self {
+            Self::Default => {
+                let path = format!("input/2020/day{}.txt", day);
+                fs::read_to_string(&path).map_err(|e| format!("unable to read {}: {}", path, e))
+            }
+            Self::Path(path) => fs::read_to_string(path)
+                .map_err(|e| format!("unable to read {}: {}", path.display(), e)),
+            Self::Stdin => {
+                let mut input = String::new();
+                io::stdin()
+                    .read_to_string(&mut input)
+                    .map_err(|e| format!("unable to read stdin: {}", e))?;
+                Ok(input)
+            }
+        }
+    }
+}
+
+struct BenchOptions {
+    runs: usize,
+    format: Format,
+    output: Option<PathBuf>,
+    baseline: Option<PathBuf>,
+    threshold: f64,
+}
+
+struct TraceOptions {
+    format: TraceFormat,
+    output: Option<PathBuf>,
+    max_steps: usize,
+}
+
+struct VerifyOptions {
+    answers: PathBuf,
+}
+
+struct LifeOptions {
+    rule: Option<String>,
+    steps: Option<usize>,
+    backend: String,
+}
+
+enum Command {
+    Run,
+    Bench(BenchOptions),
+    Verify(VerifyOptions),
+    Debug,
+    Trace(TraceOptions),
+    Life(LifeOptions),
+}
+
+struct Options {
+    command: Command,
+    day: Option<u32>,
+    part: Option<u32>,
+    input: InputSource,
+}
+
+impl Options {
+    fn from_args<I>(args: I) -> Result<Self, String>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut options = Self {
+            command: Command::Run,
+            day: None,
+            part: None,
+            input: InputSource::Default,
+        };
+
+        let mut args = args.into_iter().peekable();
+        match args.peek().map(String::as_str) {
+            Some("bench") => {
+                args.next();
+                options.command = Command::Bench(BenchOptions {
+                    runs: 10,
+                    format: Format::Csv,
+                    output: None,
+                    baseline: None,
+                    threshold: 0.1,
+                });
+            }
+            Some("verify") => {
+                args.next();
+                options.command = Command::Verify(VerifyOptions {
+                    answers: PathBuf::from("answers.toml"),
+                });
+            }
+            Some("debug") => {
+                args.next();
+                options.command = Command::Debug;
+            }
+            Some("trace") => {
+                args.next();
+                options.command = Command::Trace(TraceOptions {
+                    format: TraceFormat::Csv,
+                    output: None,
+                    max_steps: TRACE_STEP_LIMIT,
+                });
+            }
+            Some("life") => {
+                args.next();
+                options.command = Command::Life(LifeOptions {
+                    rule: None,
+                    steps: None,
+                    backend: "sparse".to_string(),
+                });
+            }
+            _ => (),
+        }
+
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("missing value for {}", arg));
+            if let Command::Bench(bench) = &mut options.command {
+                match arg.as_str() {
+                    "-n" | "--runs" => {
+                        let runs = value()?;
+                        bench.runs = match runs.parse() {
+                            Ok(n) if n > 0 => n,
+                            _ => return Err(format!("invalid number of runs '{}'", runs)),
+                        };
+                        continue;
+                    }
+                    "-f" | "--format" => {
+                        let format = value()?;
+                        bench.format = Format::from_str(&format)
+                            .ok_or(format!("invalid format '{}', expected csv or json", format))?;
+                        continue;
+                    }
+                    "-o" | "--output" => {
+                        bench.output = Some(value()?.into());
+                        continue;
+                    }
+                    "-b" | "--baseline" => {
+                        bench.baseline = Some(value()?.into());
+                        continue;
+                    }
+                    "-t" | "--threshold" => {
+                        let threshold = value()?;
+                        bench.threshold = match threshold.parse::<f64>() {
+                            Ok(t) if t >= 0.0 => t / 100.0,
+                            _ => return Err(format!("invalid threshold '{}'", threshold)),
+                        };
+                        continue;
+                    }
+                    _ => (),
+                }
+            }
+            if let Command::Trace(trace) = &mut options.command {
+                match arg.as_str() {
+                    "-f" | "--format" => {
+                        trace.format = value()?.parse()?;
+                        continue;
+                    }
+                    "-o" | "--output" => {
+                        trace.output = Some(value()?.into());
+                        continue;
+                    }
+                    "-n" | "--max-steps" => {
+                        let steps = value()?;
+                        trace.max_steps = steps
+                            .parse()
+                            .map_err(|_| format!("invalid number of steps '{}'", steps))?;
+                        continue;
+                    }
+                    _ => (),
+                }
+            }
+            if let Command::Life(life) = &mut options.command {
+                match arg.as_str() {
+                    "-r" | "--rule" => {
+                        life.rule = Some(value()?);
+                        continue;
+                    }
+                    "-n" | "--steps" => {
+                        let steps = value()?;
+                        life.steps = Some(
+                            steps
+                                .parse()
+                                .map_err(|_| format!("invalid number of steps '{}'", steps))?,
+                        );
+                        continue;
+                    }
+                    "-b" | "--backend" => {
+                        life.backend = value()?;
+                        continue;
+                    }
+                    _ => (),
+                }
+            }
+            if let Command::Verify(verify) = &mut options.command {
+                if arg == "-a" || arg == "--answers" {
+                    verify.answers = value()?.into();
+                    continue;
+                }
+            }
+
+            match arg.as_str() {
+                "-d" | "--day" => options.day = Some(parse_in_range(&value()?, 1..=25, "day")?),
+                "-p" | "--part" => options.part = Some(parse_in_range(&value()?, 1..=2, "part")?),
+                "-i" | "--input" => {
+                    options.input = match value()?.as_str() {
+                        "-" => InputSource::Stdin,
+                        path => InputSource::Path(path.into()),
+                    }
+                }
+                "-h" | "--help" => {
+                    println!("{}", USAGE);
+                    process::exit(0);
+                }
+                _ => return Err(format!("unknown argument '{}'", arg)),
+            }
+        }
+
+        if matches!(options.command, Command::Debug | Command::Trace(_)) {
+            if options.day.is_some_and(|d| d != 8) || options.part.is_some() {
+                return Err("debug and trace only support day 8 and take no --part".to_string());
+            }
+            if let (Command::Debug, InputSource::Stdin) = (&options.command, &options.input) {
+                return Err("debug reads commands from stdin, so --input can't be '-'".to_string());
+            }
+            options.day = Some(8);
+        }
+        if let Command::Life(life) = &options.command {
+            if matches!(options.day, Some(11) | Some(17) | Some(24)) == false
+                || options.part.is_some()
+            {
+                return Err("life needs --day 11, 17 or 24 and takes no --part".to_string());
+            }
+            if life.rule.is_none() {
+                return Err("life needs a --rule".to_string());
+            }
+        }
+        if options.day.is_none() && !matches!(options.input, InputSource::Default) {
+            return Err("--input can only be used together with --day".to_string());
+        }
+
+        Ok(options)
+    }
+}
+
+fn parse_in_range(
+    s: &str,
+    range: std::ops::RangeInclusive<u32>,
+    what: &str,
+) -> Result<u32, String> {
+    match s.parse::<u32>() {
+        Ok(n) if range.contains(&n) => Ok(n),
+        _ => Err(format!(
+            "invalid {} '{}', expected {}-{}",
+            what,
+            s,
+            range.start(),
+            range.end()
+        )),
+    }
+}
+
+struct Outcome {
+    answer: String,
+    generator: Duration,
+    runner: Duration,
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+// Solutions are free to panic on bad input, so contain it here rather than giving up on the remaining days
+fn catch_panic<T>(f: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(result) => result,
+        Err(payload) => Err(format!("FAILED with panic: {}", panic_message(payload))),
+    }
+}
+
+fn run_solution(solution: &dyn DaySolution, part: u32, input: &str) -> Result<Outcome, String> {
+    catch_panic(|| {
+        let start_time = Instant::now();
+        let parsed = solution
+            .parse(input)
+            .map_err(|e| format!("FAILED while generating: {}", e))?;
+        let inter_time = Instant::now();
+        let answer = solution.run(&*parsed, part);
+        let final_time = Instant::now();
+
+        Ok(Outcome {
+            answer,
+            generator: inter_time - start_time,
+            runner: final_time - inter_time,
+        })
+    })
+}
+
+// Only the runner is timed, the input is parsed once up front
+fn bench_solution(
+    solution: &dyn DaySolution,
+    part: u32,
+    input: &str,
+    runs: usize,
+) -> Result<Measurement, String> {
+    catch_panic(|| {
+        let parsed = solution
+            .parse(input)
+            .map_err(|e| format!("FAILED while generating: {}", e))?;
+        let samples: Vec<Duration> = (0..runs)
+            .map(|_| {
+                let start_time = Instant::now();
+                solution.run(&*parsed, part);
+                Instant::now() - start_time
+            })
+            .collect();
+        Ok(Measurement::from_samples(solution.day(), part, &samples))
+    })
+}
+
+// Calls f for each selected day and part, reading each day's input once and sharing it between parts. An
+// input which can't be read is still handed to f, as the error, so it's counted against every part.
+// Returns whether anything failed.
+fn for_each_selected<F>(options: &Options, mut f: F) -> bool
+where
+    F: FnMut(&dyn DaySolution, u32, Result<&str, String>) -> Result<(), String>,
+{
+    let mut failed = false;
+    let selected = SOLUTIONS
+        .iter()
+        .filter(|s| options.day.is_none_or(|d| d == s.day()));
+    for solution in selected {
+        let input = options
+            .input
+            .read(solution.day())
+            .map(|s| s.trim_end_matches('\n').to_string());
+
+        let parts = (1..=solution.parts()).filter(|&p| options.part.is_none_or(|part| part == p));
+        for part in parts {
+            let input = match &input {
+                Ok(input) => Ok(input.as_str()),
+                Err(e) => Err(format!("FAILED while reading input: {}", e)),
+            };
+            if let Err(e) = f(*solution, part, input) {
+                eprintln!("Day {} - Part {}: {}\n", solution.day(), part, e);
+                failed = true;
+            }
+        }
+    }
+    failed
+}
+
+fn run(options: &Options) -> bool {
+    println!("Advent of code 2020");
+
+    for_each_selected(options, |solution, part, input| {
+        let outcome = run_solution(solution, part, input?)?;
+        println!(
+            "Day {} - Part {}: {}\n\tgenerator: {:?},\n\trunner: {:?}\n",
+            solution.day(),
+            part,
+            outcome.answer,
+            outcome.generator,
+            outcome.runner
+        );
+        Ok(())
+    })
+}
+
+fn bench(options: &Options, bench: &BenchOptions) -> bool {
+    let baseline = match &bench.baseline {
+        Some(path) => match fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|s| bench::parse_baseline(&s))
+        {
+            Ok(baseline) => Some(baseline),
+            Err(e) => {
+                eprintln!("Unable to read baseline {}: {}", path.display(), e);
+                return true;
+            }
+        },
+        None => None,
+    };
+
+    let mut measurements = Vec::new();
+    let mut failed = for_each_selected(options, |solution, part, input| {
+        let m = bench_solution(solution, part, input?, bench.runs)?;
+        eprintln!(
+            "Day {} - Part {}: median {:?} (min {:?}, mean {:?}, stddev {:?})",
+            m.day, m.part, m.median, m.min, m.mean, m.stddev
+        );
+        measurements.push(m);
+        Ok(())
+    });
+
+    let output = bench::to_string(&measurements, bench.format);
+    match &bench.output {
+        Some(path) => {
+            if let Err(e) = fs::write(path, output) {
+                eprintln!("Unable to write {}: {}", path.display(), e);
+                failed = true;
+            }
+        }
+        None => print!("{}", output),
+    }
+
+    if let Some(baseline) = baseline {
+        let regressions = bench::find_regressions(&measurements, &baseline, bench.threshold);
+        for r in regressions.iter() {
+            eprintln!(
+                "Day {} - Part {}: SLOWER than baseline by {:.1}% ({:?} -> {:?})",
+                r.day,
+                r.part,
+                r.slowdown() * 100.0,
+                r.baseline,
+                r.current
+            );
+        }
+        if regressions.is_empty() == false {
+            failed = true;
+        }
+    }
+
+    failed
+}
+
+fn verify(options: &Options, verify: &VerifyOptions) -> bool {
+    let answers = match fs::read_to_string(&verify.answers)
+        .map_err(|e| e.to_string())
+        .and_then(|s| Answers::from_string(&s))
+    {
+        Ok(answers) => answers,
+        Err(e) => {
+            eprintln!("Unable to read answers {}: {}", verify.answers.display(), e);
+            return true;
+        }
+    };
+
+    // Failures are collected and listed after the matrix, with the reason
+    let mut results: HashMap<(u32, u32), &str> = HashMap::new();
+    let mut failures = Vec::new();
+    let mut failed = for_each_selected(options, |solution, part, input| {
+        let day = solution.day();
+        let outcome = input.and_then(|input| run_solution(solution, part, input));
+        let status = match (answers.get(day, part), outcome) {
+            (_, Err(e)) => {
+                failures.push(format!("Day {} - Part {}: {}", day, part, e));
+                "FAIL"
+            }
+            (None, Ok(_)) => "?",
+            (Some(expected), Ok(outcome)) if outcome.answer == expected => "pass",
+            (Some(expected), Ok(outcome)) => {
+                failures.push(format!(
+                    "Day {} - Part {}: expected {}, found {}",
+                    day, part, expected, outcome.answer
+                ));
+                "FAIL"
+            }
+        };
+        results.insert((day, part), status);
+        Ok(())
+    });
+
+    println!("Day | Part 1 | Part 2");
+    println!("----+--------+-------");
+    let selected = SOLUTIONS
+        .iter()
+        .filter(|s| options.day.is_none_or(|d| d == s.day()));
+    for solution in selected {
+        let day = solution.day();
+        let cell = |part| results.get(&(day, part)).copied().unwrap_or("-");
+        println!("{:>3} | {:<6} | {}", day, cell(1), cell(2));
+    }
+
+    let count = |status| results.values().filter(|&&s| s == status).count();
+    println!(
+        "\n{} passed, {} failed, {} without an expected answer",
+        count("pass"),
+        count("FAIL"),
+        count("?")
+    );
+    for failure in failures.iter() {
+        println!("{}", failure);
+    }
+
+    failed |= failures.is_empty() == false;
+    failed
+}
+
+fn debug_console(options: &Options) -> bool {
+    let result = options
+        .input
+        .read(8)
+        .and_then(|program| debug(&program, io::stdin().lock(), io::stdout()));
+    match result {
+        Ok(()) => false,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            true
+        }
+    }
+}
+
+fn trace_console(options: &Options, trace_options: &TraceOptions) -> bool {
+    let result = options
+        .input
+        .read(8)
+        .and_then(|program| match &trace_options.output {
+            Some(path) => {
+                let file = fs::File::create(path)
+                    .map_err(|e| format!("unable to create {}: {}", path.display(), e))?;
+                let output = io::BufWriter::new(file);
+                trace(
+                    &program,
+                    trace_options.format,
+                    trace_options.max_steps,
+                    output,
+                )
+            }
+            None => trace(
+                &program,
+                trace_options.format,
+                trace_options.max_steps,
+                io::stdout().lock(),
+            ),
+        });
+    match result {
+        Ok(summary) => {
+            eprintln!("{}", summary);
+            false
+        }
+        Err(e) => {
+            eprintln!("error: {}", e);
+            true
+        }
+    }
+}
+
+fn life_console(options: &Options, life_options: &LifeOptions) -> bool {
+    let day = options.day.unwrap();
+    let rule = life_options.rule.as_deref().unwrap();
+    let result = options.input.read(day).and_then(|input| {
+        let input = input.trim_end_matches('\n');
+        life(day, input, rule, life_options.steps, &life_options.backend)
+    });
+    match result {
+        Ok(live) => {
+            println!("{}", live);
+            false
+        }
+        Err(e) => {
+            eprintln!("error: {}", e);
+            true
+        }
+    }
+}
+
+fn main() {
+    let options = match Options::from_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+
+    // Panics are reported alongside the failing day and part instead. The console isn't run through
+    // catch_panic, so it keeps the default hook rather than dying without a word.
+    if matches!(
+        options.command,
+        Command::Run | Command::Bench(_) | Command::Verify(_)
+    ) {
+        panic::set_hook(Box::new(|_| {}));
+    }
+
+    let failed = match &options.command {
+        Command::Run => run(&options),
+        Command::Bench(bench_options) => bench(&options, bench_options),
+        Command::Verify(verify_options) => verify(&options, verify_options),
+        Command::Debug => debug_console(&options),
+        Command::Trace(trace_options) => trace_console(&options, trace_options),
+        Command::Life(life_options) => life_console(&options, life_options),
+    };
+    if failed {
+        process::exit(1);
+    }
+}